@@ -71,6 +71,7 @@ def textgrid_to_df(
     file_name_column: Optional[bool] = None,
     file_name_func: Optional[Any] = None,
     backend: Literal["pandas", "polars"] = "pandas",
+    on_error: Literal["raise", "skip"] = "raise",
 ):
     """Parse TextGrid files into a DataFrame-like structure.
 
@@ -94,6 +95,10 @@ def textgrid_to_df(
     backend:
         ``"pandas"`` yields a :class:`pandas.DataFrame`; ``"polars"`` yields a
         :class:`polars.DataFrame`.
+    on_error:
+        For multi-file inputs, ``"raise"`` raises :class:`OSError` on the first
+        file that cannot be read, and ``"skip"`` leaves such files out with a
+        :class:`RuntimeWarning`. A single file always raises.
 
     Returns
     -------
@@ -101,6 +106,12 @@ def textgrid_to_df(
         Tabular representation containing ``tmin``, ``tmax``, ``label``, ``tier``
         and interval flags; optionally includes ``filename``.
 
+    Raises
+    ------
+    OSError
+        If a file cannot be read or parsed, unless skipped with
+        ``on_error="skip"``.
+
     Examples
     --------
     >>> df = textgrid_to_df("data/short_format.TextGrid")
@@ -123,7 +134,9 @@ def textgrid_to_df(
     vectors = _dispatch_files(
         file,
         func_single=lambda f: rc_tg2vecs(f, strict=strict, file_type=file_type),
-        func_multiple=lambda fs: rc_tgs2vecs(fs, strict=strict, file_type=file_type),
+        func_multiple=lambda fs: rc_tgs2vecs(
+            fs, strict=strict, file_type=file_type, on_error=on_error
+        ),
     )
 
     tmins, tmaxs, labels, tiers, is_intervals = vectors[:5]
@@ -163,6 +176,7 @@ def textgrid_to_data(
     strict: bool = False,
     file_name_func: Optional[Any] = None,
     file_type: str = "auto",
+    on_error: Literal["raise", "skip"] = "raise",
 ):
     """Parse TextGrid files into nested data resembling the Rust output.
 
@@ -177,6 +191,11 @@ def textgrid_to_data(
         dictionary for multi-file inputs.
     file_type:
         ``"short"``, ``"long"`` or ``"auto"`` to control dialect detection.
+    on_error:
+        For multi-file inputs, ``"raise"`` raises :class:`OSError` on the first
+        file that cannot be read, and ``"skip"`` maps such files to
+        ``(0.0, 0.0, [])`` with a :class:`RuntimeWarning`. A single file always
+        raises.
 
     Returns
     -------
@@ -184,6 +203,12 @@ def textgrid_to_data(
         The raw structured data from the Rust bindings. For multiple files a
         dictionary keyed by filename is returned.
 
+    Raises
+    ------
+    OSError
+        If a file cannot be read or parsed, unless skipped with
+        ``on_error="skip"``.
+
     Examples
     --------
     >>> data = textgrid_to_data("data/short_format.TextGrid")
@@ -209,7 +234,9 @@ def textgrid_to_data(
     data = _dispatch_files(
         file,
         func_single=lambda f: rc_tg2data(f, strict=strict, file_type=file_type),
-        func_multiple=lambda fs: rc_tgs2data(fs, strict=strict, file_type=file_type),
+        func_multiple=lambda fs: rc_tgs2data(
+            fs, strict=strict, file_type=file_type, on_error=on_error
+        ),
     )
     if isinstance(file, (str, Path)):
         return data
@@ -8,7 +8,222 @@ mod gridio {
     use numpy::{IntoPyArray, PyArray1};
     use pyo3::prelude::*;
 
-    use textgrid::{files_to_data, files_to_vectors, read_from_file, TextGrid};
+    use textgrid::{
+        files_to_data, files_to_vectors, read_from_file, FileType, TextGrid, TextGridData,
+        TextGridFormat, TierData, WriteOptions,
+    };
+
+    /// Maps a Python `file_type` argument to the format to write: "long" or "short".
+    ///
+    /// # Errors
+    ///
+    /// Returns a PyValueError for any other value, including "auto".
+    fn text_grid_format(file_type: &str) -> PyResult<TextGridFormat> {
+        let value_error =
+            |message: String| PyErr::new::<pyo3::exceptions::PyValueError, _>(message);
+        match file_type.parse::<FileType>() {
+            Ok(FileType::Long) => Ok(TextGridFormat::Long),
+            Ok(FileType::Short) => Ok(TextGridFormat::Short),
+            Ok(other) => Err(value_error(format!(
+                "Cannot write file type \"{}\" (expected \"long\" or \"short\")",
+                other
+            ))),
+            Err(e) => Err(value_error(e.to_string())),
+        }
+    }
+
+    /// Collects the per-file results of a batch function according to its `on_error` argument.
+    ///
+    /// With "raise", the first file that cannot be read raises a PyIOError. With "skip", such
+    /// files are replaced by `empty` and reported with a `RuntimeWarning`, as batch functions
+    /// did before they raised.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PyValueError if `on_error` is not "raise" or "skip".
+    fn collect_results<T>(
+        py: Python<'_>,
+        results: Vec<std::io::Result<T>>,
+        on_error: &str,
+        empty: impl Fn() -> T,
+    ) -> PyResult<Vec<T>> {
+        let skip = match on_error {
+            "raise" => false,
+            "skip" => true,
+            other => {
+                return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Unknown on_error \"{}\" (expected \"raise\" or \"skip\")",
+                    other
+                )))
+            }
+        };
+        results
+            .into_iter()
+            .map(|result| match result {
+                Ok(value) => Ok(value),
+                Err(e) => {
+                    // The error of each file starts with its path
+                    let message = format!("Failed to read TextGrid file {}", e);
+                    if !skip {
+                        return Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(message));
+                    }
+                    let warning = std::ffi::CString::new(message.replace('\0', ""))
+                        .expect("NUL bytes were removed");
+                    let category = py.get_type::<pyo3::exceptions::PyRuntimeWarning>();
+                    PyErr::warn(py, &category, &warning, 1)?;
+                    Ok(empty())
+                }
+            })
+            .collect()
+    }
+
+    /// Vectorized TextGrid content: (tmins, tmaxs, labels, tier_names, is_intervals).
+    type PyVectors<'py> = (
+        Bound<'py, PyArray1<f64>>,
+        Bound<'py, PyArray1<f64>>,
+        Vec<String>,
+        Vec<String>,
+        Bound<'py, PyArray1<bool>>,
+    );
+
+    /// Vectorized content of several TextGrids, with a trailing file ID column.
+    type PyFileVectors<'py> = (
+        Bound<'py, PyArray1<f64>>,
+        Bound<'py, PyArray1<f64>>,
+        Vec<String>,
+        Vec<String>,
+        Bound<'py, PyArray1<bool>>,
+        Bound<'py, PyArray1<u32>>,
+    );
+
+    /// Native TextGrid object backed by the Rust `textgrid` crate.
+    ///
+    /// Unlike the pure-Python wrapper in `gridio.textgrid`, this class keeps the parsed
+    /// TextGrid in Rust memory and only converts to Python objects when data is requested.
+    #[pyclass(name = "TextGrid", module = "gridio.gridio")]
+    pub struct PyTextGrid {
+        inner: TextGrid,
+    }
+
+    #[pymethods]
+    impl PyTextGrid {
+        /// Creates a TextGrid from structured data: Vec of (tier_name, is_interval, items).
+        ///
+        /// # Errors
+        ///
+        /// Returns a PyValueError if the resulting TextGrid is invalid.
+        #[new]
+        #[pyo3(signature = (data, tmin=None, tmax=None, name=None))]
+        fn new(
+            data: Vec<TierData>,
+            tmin: Option<f64>,
+            tmax: Option<f64>,
+            name: Option<String>,
+        ) -> PyResult<Self> {
+            let name = name.or_else(|| Some("TextGrid".to_string()));
+            match TextGrid::from_data(data, name, tmin, tmax) {
+                Ok(inner) => Ok(PyTextGrid { inner }),
+                Err(e) => Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                    "Failed to create TextGrid because: {}",
+                    e
+                ))),
+            }
+        }
+
+        /// Reads a TextGrid file.
+        ///
+        /// # Errors
+        ///
+        /// Returns a PyIOError if the file cannot be read or parsed.
+        #[staticmethod]
+        #[pyo3(signature = (file, strict=false, file_type="auto"))]
+        fn from_file(file: &str, strict: bool, file_type: &str) -> PyResult<Self> {
+            match read_from_file(file, strict, file_type) {
+                Ok(inner) => Ok(PyTextGrid { inner }),
+                Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                    "Failed to read TextGrid file {} because: {}",
+                    file, e
+                ))),
+            }
+        }
+
+        /// Global start time of the TextGrid.
+        #[getter]
+        fn tmin(&self) -> f64 {
+            self.inner.tmin
+        }
+
+        /// Global end time of the TextGrid.
+        #[getter]
+        fn tmax(&self) -> f64 {
+            self.inner.tmax
+        }
+
+        /// Name of the TextGrid (the file stem when read from disk).
+        #[getter]
+        fn name(&self) -> String {
+            self.inner.name.clone()
+        }
+
+        /// Names of all tiers, in file order.
+        #[getter]
+        fn tier_names(&self) -> Vec<String> {
+            self.inner
+                .tier_names()
+                .into_iter()
+                .map(String::from)
+                .collect()
+        }
+
+        /// Returns the structured `(tmin, tmax, tiers)` representation.
+        fn to_data(&self) -> TextGridData {
+            self.inner.to_data()
+        }
+
+        /// Returns `(tmins, tmaxs, labels, tier_names, is_intervals)`, with the numeric
+        /// columns moved into `numpy.ndarray`s.
+        fn to_vectors<'py>(&self, py: Python<'py>) -> PyVectors<'py> {
+            let (tmins, tmaxs, labels, tier_names, is_intervals) = self.inner.to_vectors();
+            (
+                tmins.into_pyarray(py),
+                tmaxs.into_pyarray(py),
+                labels,
+                tier_names,
+                is_intervals.into_pyarray(py),
+            )
+        }
+
+        /// Writes the TextGrid to `file` in the "long" or "short" format.
+        #[pyo3(signature = (file, file_type="long"))]
+        fn save(&self, file: &str, file_type: &str) -> PyResult<()> {
+            self.inner
+                .save_textgrid(file, text_grid_format(file_type)?, &WriteOptions::default())
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                        "Failed to write {} because: {}",
+                        file, e
+                    ))
+                })
+        }
+
+        fn __len__(&self) -> usize {
+            self.inner.tiers.len()
+        }
+
+        fn __repr__(&self) -> String {
+            format!(
+                "TextGrid(name={:?}, tmin={}, tmax={}, tiers={:?})",
+                self.inner.name,
+                self.inner.tmin,
+                self.inner.tmax,
+                self.tier_names()
+            )
+        }
+
+        fn __str__(&self) -> String {
+            self.inner.to_string()
+        }
+    }
 
     /// Converts a single TextGrid file to vectorized format.
     ///
@@ -40,13 +255,7 @@ mod gridio {
         file: &str,
         strict: bool,
         file_type: &str,
-    ) -> PyResult<(
-        Bound<'py, PyArray1<f64>>,
-        Bound<'py, PyArray1<f64>>,
-        Vec<String>,
-        Vec<String>,
-        Bound<'py, PyArray1<bool>>,
-    )> {
+    ) -> PyResult<PyVectors<'py>> {
         // Parse the TextGrid file into Rust structure
         let tgt_result = read_from_file(file, strict, file_type);
         match tgt_result {
@@ -81,6 +290,8 @@ mod gridio {
     /// * `files` - Vector of file paths to process
     /// * `strict` - If true, enforces strict parsing rules; if false, allows more lenient parsing
     /// * `file_type` - Format type: "long" or "short"
+    /// * `on_error` - "raise" to fail on the first file that cannot be read, or "skip" to leave
+    ///   it out with a `RuntimeWarning`
     ///
     /// # Returns
     ///
@@ -94,23 +305,20 @@ mod gridio {
     ///
     /// # Errors
     ///
-    /// Returns a PyIOError if any file cannot be read or parsed.
+    /// With `on_error="raise"`, returns a PyIOError if any file cannot be read or parsed.
+    /// Returns a PyValueError if `on_error` is not "raise" or "skip".
     #[pyfunction]
+    #[pyo3(signature = (files, strict, file_type, on_error="raise"))]
     pub fn textgrids2vectors<'py>(
         py: Python<'py>,
         files: Vec<String>,
         strict: bool,
         file_type: &str,
-    ) -> PyResult<(
-        Bound<'py, PyArray1<f64>>,
-        Bound<'py, PyArray1<f64>>,
-        Vec<String>,
-        Vec<String>,
-        Bound<'py, PyArray1<bool>>,
-        Bound<'py, PyArray1<u32>>,
-    )> {
+        on_error: &str,
+    ) -> PyResult<PyFileVectors<'py>> {
         // Process all files and get their individual vector representations
-        let vec_vectors = files_to_vectors(&files, strict, file_type);
+        let results = files_to_vectors(&files, strict, file_type);
+        let vec_vectors = collect_results(py, results, on_error, Default::default)?;
 
         // Initialize vectors to hold concatenated results from all files
         let mut tmins = Vec::new();
@@ -171,17 +379,13 @@ mod gridio {
     ///
     /// Returns a PyIOError if the file cannot be read or parsed.
     #[pyfunction]
-    pub fn textgrid2data(
-        file: &str,
-        strict: bool,
-        file_type: &str,
-    ) -> PyResult<(f64, f64, Vec<(String, bool, Vec<(f64, f64, String)>)>)> {
+    pub fn textgrid2data(file: &str, strict: bool, file_type: &str) -> PyResult<TextGridData> {
         // Parse TextGrid file
         let tgt_result = read_from_file(file, strict, file_type);
         match tgt_result {
             // Type note: Rust nested tuples/vectors are automatically converted
             // to Python nested tuples/lists by PyO3
-            Ok(tgt) => Ok(tgt.to_data()),
+            Ok(tgt) => Ok(tgt.into_data()),
             Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
                 "Failed to read TextGrid file {} because: {}",
                 file, e
@@ -199,6 +403,8 @@ mod gridio {
     /// * `files` - Vector of file paths to process
     /// * `strict` - If true, enforces strict parsing; if false, allows lenient parsing
     /// * `file_type` - Format type: "long" or "short"
+    /// * `on_error` - "raise" to fail on the first file that cannot be read, or "skip" to
+    ///   return `(0.0, 0.0, [])` for it with a `RuntimeWarning`
     ///
     /// # Returns
     ///
@@ -209,15 +415,20 @@ mod gridio {
     ///
     /// # Errors
     ///
-    /// Returns a PyIOError if any file cannot be read or parsed.
+    /// With `on_error="raise"`, returns a PyIOError if any file cannot be read or parsed.
+    /// Returns a PyValueError if `on_error` is not "raise" or "skip".
     #[pyfunction]
+    #[pyo3(signature = (files, strict, file_type, on_error="raise"))]
     pub fn textgrids2data(
+        py: Python<'_>,
         files: Vec<String>,
         strict: bool,
         file_type: &str,
-    ) -> PyResult<Vec<(f64, f64, Vec<(String, bool, Vec<(f64, f64, String)>)>)>> {
-        // Process all files in batch
-        let vec_data = files_to_data(&files, strict, file_type);
+        on_error: &str,
+    ) -> PyResult<Vec<TextGridData>> {
+        // Process all files in batch, handling unreadable files as asked by `on_error`
+        let results = files_to_data(&files, strict, file_type);
+        let vec_data = collect_results(py, results, on_error, || (0.0, 0.0, Vec::new()))?;
         // Type note: Rust Vec<nested_tuple> -> Python list of nested tuples
         Ok(vec_data)
     }
@@ -235,7 +446,7 @@ mod gridio {
     /// * `tmin` - Optional global start time. If None, calculated from data
     /// * `tmax` - Optional global end time. If None, calculated from data
     /// * `output_file` - Path where the TextGrid file will be saved
-    /// * `file_type` - Output format: "long" for long format, anything else for short format
+    /// * `file_type` - Output format: "long" or "short"
     ///
     /// # Returns
     ///
@@ -243,7 +454,8 @@ mod gridio {
     ///
     /// # Errors
     ///
-    /// Returns a PyIOError if the TextGrid cannot be created or saved.
+    /// Returns a PyValueError if `file_type` is not "long" or "short", or a PyIOError if the
+    /// TextGrid cannot be created or saved.
     ///
     /// # Type Conversions
     ///
@@ -256,7 +468,7 @@ mod gridio {
     /// * Python None -> Rust Option::None
     #[pyfunction]
     pub fn data2textgrid(
-        data: Vec<(String, bool, Vec<(f64, f64, String)>)>,
+        data: Vec<TierData>,
         tmin: Option<f64>,
         tmax: Option<f64>,
         output_file: &str,
@@ -265,12 +477,16 @@ mod gridio {
         // Create TextGrid structure from data
         // Type conversion: Python nested structures -> Rust nested structures (automatic via PyO3)
         let tgt_result = TextGrid::from_data(data, Some("TextGrid".to_string()), tmin, tmax);
+        let format = text_grid_format(file_type)?;
         match tgt_result {
-            Ok(tgt) => {
-                // Write to file: true for long format, false for short format
-                tgt.save_textgrid(output_file, file_type == "long");
-                Ok(())
-            }
+            Ok(tgt) => tgt
+                .save_textgrid(output_file, format, &WriteOptions::default())
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                        "Failed to write {} because: {}",
+                        output_file, e
+                    ))
+                }),
             Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
                 "Failed to create TextGrid because: {}",
                 e
@@ -294,7 +510,7 @@ mod gridio {
     /// * `tmin` - Optional global start time. If None, calculated from data
     /// * `tmax` - Optional global end time. If None, calculated from data
     /// * `output_file` - Path where the TextGrid file will be saved
-    /// * `file_type` - Output format: "long" for long format, anything else for short format
+    /// * `file_type` - Output format: "long" or "short"
     ///
     /// # Returns
     ///
@@ -318,6 +534,7 @@ mod gridio {
     /// All vectors must have the same length. Items with the same tier_name
     /// will be grouped into the same tier in the output file.
     #[pyfunction]
+    #[allow(clippy::too_many_arguments)]
     pub fn vectors2textgrid(
         tmins: Vec<f64>,
         tmaxs: Vec<f64>,
@@ -342,12 +559,16 @@ mod gridio {
             tmax,
             Some("TextGrid".to_string()),
         );
+        let format = text_grid_format(file_type)?;
         match tgt_result {
-            Ok(tgt) => {
-                // Write to file: true for long format, false for short format
-                tgt.save_textgrid(output_file, file_type == "long");
-                Ok(())
-            }
+            Ok(tgt) => tgt
+                .save_textgrid(output_file, format, &WriteOptions::default())
+                .map_err(|e| {
+                    PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
+                        "Failed to write {} because: {}",
+                        output_file, e
+                    ))
+                }),
             Err(e) => Err(PyErr::new::<pyo3::exceptions::PyIOError, _>(format!(
                 "Failed to create TextGrid because: {}",
                 e
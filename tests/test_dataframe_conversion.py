@@ -43,3 +43,12 @@ def test_df_to_textgrid_roundtrip(tmp_path):
                 new_tier.get_item(item_index).data
                 == orig_tier.get_item(item_index).data
             )
+
+
+def test_textgrid_to_df_unreadable_file(tmp_path):
+    files = [LONG_TG, tmp_path / "missing.TextGrid"]
+    with pytest.raises(OSError):
+        textgrid_to_df(files)
+    with pytest.warns(RuntimeWarning, match="missing.TextGrid"):
+        df = textgrid_to_df(files, on_error="skip")
+    assert len(df) == len(textgrid_to_df(LONG_TG))
@@ -0,0 +1,33 @@
+from pathlib import Path
+
+import pytest
+
+from gridio.gridio import TextGrid as NativeTextGrid
+
+DATA_DIR = Path(__file__).parent.parent / "data"
+LONG_TG = DATA_DIR / "long_format.TextGrid"
+
+
+def test_native_from_file():
+    tg = NativeTextGrid.from_file(str(LONG_TG))
+    assert len(tg) == 5
+    assert tg.tier_names[0] == "phone"
+    tmins, tmaxs, labels, tiers, is_intervals = tg.to_vectors()
+    assert len(tmins) == len(labels) == len(tiers)
+
+
+def test_native_roundtrip(tmp_path):
+    tg = NativeTextGrid([("phone", True, [(0.0, 0.5, "a"), (0.5, 1.0, "b")])])
+    out_file = tmp_path / "native.TextGrid"
+    tg.save(str(out_file), file_type="short")
+
+    tg2 = NativeTextGrid.from_file(str(out_file))
+    assert tg2.to_data() == (0.0, 1.0, [("phone", True, [(0.0, 0.5, "a"), (0.5, 1.0, "b")])])
+
+
+def test_native_save_rejects_unknown_file_type(tmp_path):
+    tg = NativeTextGrid([("phone", True, [(0.0, 0.5, "a")])])
+    out_file = tmp_path / "native.TextGrid"
+    with pytest.raises(ValueError):
+        tg.save(str(out_file), file_type="lnog")
+    assert not out_file.exists()
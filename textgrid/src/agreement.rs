@@ -0,0 +1,535 @@
+//! Inter-annotator agreement between tiers.
+//!
+//! The functions compare two annotations of the same recording: boundary agreement within a
+//! tolerance, Cohen's kappa over time-sliced labels, and the overlap ratio of labelled time.
+//! [`align_boundaries`] evaluates a segmentation, such as forced aligner output, against a
+//! reference, and [`confusion_matrix`] compares the labels of two tiers category by category.
+
+use crate::edit::require_interval_tier;
+use crate::textgrid::{TIME_EPSILON, TextGrid, Tier, input_error};
+use std::collections::HashMap;
+use std::io::Result;
+
+/// Agreement measures between two tiers with the same name, as returned by
+/// [`TextGrid::agreement`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierAgreement {
+    /// Name of the compared tiers.
+    pub tier_name: String,
+    /// See [`boundary_agreement`].
+    pub boundary_agreement: f64,
+    /// See [`cohen_kappa`].
+    pub kappa: f64,
+    /// See [`label_overlap_ratio`].
+    pub label_overlap: f64,
+}
+
+/// Result of matching the boundaries of a hypothesis tier against a reference tier, as returned
+/// by [`align_boundaries`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundaryAlignment {
+    /// Proportion of hypothesis boundaries matched to a reference boundary.
+    pub precision: f64,
+    /// Proportion of reference boundaries matched to a hypothesis boundary.
+    pub recall: f64,
+    /// Harmonic mean of precision and recall.
+    pub f1: f64,
+    /// `(reference, hypothesis)` times of the matched boundaries.
+    pub matched: Vec<(f64, f64)>,
+    /// Reference boundaries without a match (deletions).
+    pub unmatched_reference: Vec<f64>,
+    /// Hypothesis boundaries without a match (insertions).
+    pub unmatched_hypothesis: Vec<f64>,
+}
+
+/// How [`confusion_matrix`] measures the time during which two labels overlap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfusionSampling {
+    /// Measure exact overlaps, by cutting time at every boundary of either tier.
+    Exact,
+    /// Cut time into slices of the given duration in seconds, each labelled with the label of
+    /// the interval at its midpoint, as [`cohen_kappa`] does.
+    Step(f64),
+}
+
+/// Time-weighted confusion matrix between the labels of two tiers, as returned by
+/// [`confusion_matrix`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfusionMatrix {
+    /// Labels of the first tier, sorted; the empty label stands for unlabelled or uncovered time.
+    pub labels_a: Vec<String>,
+    /// Labels of the second tier, sorted; the empty label stands for unlabelled or uncovered
+    /// time.
+    pub labels_b: Vec<String>,
+    /// `durations[i][j]` is the time in seconds during which the first tier is labelled
+    /// `labels_a[i]` and the second tier `labels_b[j]`.
+    pub durations: Vec<Vec<f64>>,
+}
+
+impl ConfusionMatrix {
+    /// Returns the time during which the first tier is labelled `label_a` and the second tier
+    /// `label_b`, which is 0 if either label does not occur.
+    pub fn get(&self, label_a: &str, label_b: &str) -> f64 {
+        let i = self
+            .labels_a
+            .binary_search_by(|label| label.as_str().cmp(label_a));
+        let j = self
+            .labels_b
+            .binary_search_by(|label| label.as_str().cmp(label_b));
+        match (i, j) {
+            (Ok(i), Ok(j)) => self.durations[i][j],
+            _ => 0.0,
+        }
+    }
+
+    /// Returns the total time during which both tiers carry the same label, including the time
+    /// during which both are unlabelled.
+    pub fn agreed(&self) -> f64 {
+        self.labels_a
+            .iter()
+            .map(|label| self.get(label, label))
+            .sum()
+    }
+
+    /// Returns the total time compared.
+    pub fn total(&self) -> f64 {
+        self.durations.iter().flatten().sum()
+    }
+}
+
+/// Sorts times and removes those within a small epsilon of their predecessor.
+fn sorted_unique(mut times: Vec<f64>) -> Vec<f64> {
+    times.sort_by(|a, b| a.total_cmp(b));
+    times.dedup_by(|b, a| (*b - *a).abs() <= TIME_EPSILON);
+    times
+}
+
+/// Greedily pairs two sorted lists of times that lie within `tolerance` of each other.
+///
+/// Each time is used at most once; the returned pairs are indices into `reference` and
+/// `hypothesis`, in time order.
+pub(crate) fn match_times(
+    reference: &[f64],
+    hypothesis: &[f64],
+    tolerance: f64,
+) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < reference.len() && j < hypothesis.len() {
+        if (reference[i] - hypothesis[j]).abs() <= tolerance {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if reference[i] < hypothesis[j] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Returns the label of the interval at `time`, or an empty label if there is none.
+#[inline]
+fn label_at(tier: &Tier, time: f64) -> &str {
+    tier.item_at(time)
+        .map(|(_, item)| item.label.as_str())
+        .unwrap_or("")
+}
+
+impl Tier {
+    /// Returns the boundaries of the tier in time order.
+    ///
+    /// For interval tiers, these are the distinct start and end times of the intervals,
+    /// excluding the bounds of the tier itself. For point tiers, these are the point times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(
+    ///     String::from("words"),
+    ///     true,
+    ///     vec![(0.0, 0.5, String::from("hello")), (0.5, 1.0, String::from("world"))],
+    /// )];
+    /// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+    /// assert_eq!(tg.tiers[0].boundaries(), vec![0.5]);
+    /// ```
+    pub fn boundaries(&self) -> Vec<f64> {
+        if !self.interval_tier {
+            return sorted_unique(self.items.iter().map(|item| item.tmin).collect());
+        }
+        let times = self
+            .items
+            .iter()
+            .flat_map(|item| [item.tmin, item.tmax])
+            .filter(|time| {
+                (time - self.tmin).abs() > TIME_EPSILON && (time - self.tmax).abs() > TIME_EPSILON
+            })
+            .collect();
+        sorted_unique(times)
+    }
+}
+
+/// Computes the proportion of boundaries that agree between two tiers.
+///
+/// Boundaries (see [`Tier::boundaries`]) are paired one-to-one when they lie within `tolerance`
+/// of each other; the result is `2 * pairs / (boundaries of a + boundaries of b)`. Two tiers
+/// without boundaries fully agree.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::{TextGrid, agreement};
+///
+/// let words = |boundary: f64| {
+///     vec![(
+///         String::from("words"),
+///         true,
+///         vec![(0.0, boundary, String::from("hello")), (boundary, 1.0, String::from("world"))],
+///     )]
+/// };
+/// let a = TextGrid::from_data(words(0.5), None, None, None).unwrap();
+/// let b = TextGrid::from_data(words(0.51), None, None, None).unwrap();
+/// assert_eq!(agreement::boundary_agreement(&a.tiers[0], &b.tiers[0], 0.02), 1.0);
+/// assert_eq!(agreement::boundary_agreement(&a.tiers[0], &b.tiers[0], 0.005), 0.0);
+/// ```
+pub fn boundary_agreement(a: &Tier, b: &Tier, tolerance: f64) -> f64 {
+    let (a_times, b_times) = (a.boundaries(), b.boundaries());
+    let total = a_times.len() + b_times.len();
+    if total == 0 {
+        return 1.0;
+    }
+    let pairs = match_times(&a_times, &b_times, tolerance).len();
+    2.0 * pairs as f64 / total as f64
+}
+
+/// Matches the boundaries of a hypothesis tier against those of a reference tier.
+///
+/// Boundaries (see [`Tier::boundaries`]) are matched greedily in time order, each at most once,
+/// when they lie within `tolerance` of each other. Precision and recall are `1.0` when there is
+/// nothing to match.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::{TextGrid, agreement};
+///
+/// let phones = |boundaries: &[f64]| {
+///     let mut times = vec![0.0];
+///     times.extend_from_slice(boundaries);
+///     times.push(1.0);
+///     let items = times
+///         .windows(2)
+///         .map(|w| (w[0], w[1], String::from("a")))
+///         .collect();
+///     vec![(String::from("phones"), true, items)]
+/// };
+/// let reference = TextGrid::from_data(phones(&[0.2, 0.5, 0.8]), None, None, None).unwrap();
+/// let hypothesis = TextGrid::from_data(phones(&[0.21, 0.6]), None, None, None).unwrap();
+///
+/// let result = agreement::align_boundaries(&reference.tiers[0], &hypothesis.tiers[0], 0.02);
+/// assert_eq!(result.precision, 0.5);
+/// assert_eq!(result.recall, 1.0 / 3.0);
+/// assert_eq!(result.unmatched_reference, vec![0.5, 0.8]);
+/// assert_eq!(result.unmatched_hypothesis, vec![0.6]);
+/// ```
+pub fn align_boundaries(reference: &Tier, hypothesis: &Tier, tolerance: f64) -> BoundaryAlignment {
+    let (ref_times, hyp_times) = (reference.boundaries(), hypothesis.boundaries());
+    let pairs = match_times(&ref_times, &hyp_times, tolerance);
+    let mut ref_matched = vec![false; ref_times.len()];
+    let mut hyp_matched = vec![false; hyp_times.len()];
+    for &(i, j) in &pairs {
+        ref_matched[i] = true;
+        hyp_matched[j] = true;
+    }
+    let unmatched = |times: &[f64], matched: &[bool]| -> Vec<f64> {
+        times
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !**matched)
+            .map(|(time, _)| *time)
+            .collect()
+    };
+    let ratio = |count: usize, total: usize| {
+        if total == 0 {
+            1.0
+        } else {
+            count as f64 / total as f64
+        }
+    };
+    let precision = ratio(pairs.len(), hyp_times.len());
+    let recall = ratio(pairs.len(), ref_times.len());
+    let f1 = if precision + recall > 0.0 {
+        2.0 * precision * recall / (precision + recall)
+    } else {
+        0.0
+    };
+    BoundaryAlignment {
+        precision,
+        recall,
+        f1,
+        matched: pairs
+            .iter()
+            .map(|&(i, j)| (ref_times[i], hyp_times[j]))
+            .collect(),
+        unmatched_reference: unmatched(&ref_times, &ref_matched),
+        unmatched_hypothesis: unmatched(&hyp_times, &hyp_matched),
+    }
+}
+
+/// Computes Cohen's kappa between the labels of two interval tiers.
+///
+/// The time span covered by both tiers is cut into slices of `step` seconds, and each slice is
+/// labelled with the label of the interval at its midpoint; uncovered slices get an empty label.
+///
+/// # Errors
+///
+/// Returns an error if either tier is a point tier, `step` is not positive, or the tiers do not
+/// overlap in time.
+pub fn cohen_kappa(a: &Tier, b: &Tier, step: f64) -> Result<f64> {
+    require_interval_tier(a, "Cohen's kappa")?;
+    require_interval_tier(b, "Cohen's kappa")?;
+    if !(step > 0.0 && step.is_finite()) {
+        return Err(input_error(&format!(
+            "Step should be positive and finite, got {}",
+            step
+        )));
+    }
+    let (t0, t1) = (a.tmin.max(b.tmin), a.tmax.min(b.tmax));
+    if t1 - t0 <= TIME_EPSILON {
+        return Err(input_error(&format!(
+            "Tiers {} and {} do not overlap in time",
+            a.name, b.name
+        )));
+    }
+    let n_slices = ((t1 - t0) / step).ceil() as usize;
+    let mut agreed = 0usize;
+    let mut marginals: HashMap<&str, (usize, usize)> = HashMap::new();
+    for k in 0..n_slices {
+        let time = (t0 + (k as f64 + 0.5) * step).min(t1);
+        let (label_a, label_b) = (label_at(a, time), label_at(b, time));
+        if label_a == label_b {
+            agreed += 1;
+        }
+        marginals.entry(label_a).or_default().0 += 1;
+        marginals.entry(label_b).or_default().1 += 1;
+    }
+    let n = n_slices as f64;
+    let observed = agreed as f64 / n;
+    let expected: f64 = marginals
+        .values()
+        .map(|&(count_a, count_b)| (count_a as f64 / n) * (count_b as f64 / n))
+        .sum();
+    if (1.0 - expected).abs() <= f64::EPSILON {
+        return Ok(1.0);
+    }
+    Ok((observed - expected) / (1.0 - expected))
+}
+
+/// Computes the time-weighted confusion matrix between the labels of two interval tiers, e.g.
+/// automatic against manual phone labels, or the category systems of two annotators.
+///
+/// Only the time span covered by both tiers is compared. Time without an interval, or with an
+/// interval with an empty label, is counted under the empty label.
+///
+/// # Arguments
+///
+/// * `a` - The first tier, whose labels are the rows of the matrix
+/// * `b` - The second tier, whose labels are the columns of the matrix
+/// * `sampling` - Whether to measure exact overlaps or to sample time slices
+///
+/// # Errors
+///
+/// Returns an error if either tier is a point tier, the step is not positive, or the tiers do
+/// not overlap in time.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::TierBuilder;
+/// use textgrid::agreement::{ConfusionSampling, confusion_matrix};
+///
+/// let manual = TierBuilder::intervals("phones")
+///     .interval(0.0, 0.5, "a")
+///     .interval(0.5, 1.0, "b")
+///     .build()
+///     .unwrap();
+/// let automatic = TierBuilder::intervals("phones")
+///     .interval(0.0, 0.75, "a")
+///     .interval(0.75, 1.0, "b")
+///     .build()
+///     .unwrap();
+///
+/// let matrix = confusion_matrix(&manual, &automatic, ConfusionSampling::Exact).unwrap();
+/// assert_eq!(matrix.labels_a, ["a", "b"]);
+/// assert_eq!(matrix.get("a", "a"), 0.5);
+/// assert_eq!(matrix.get("b", "a"), 0.25);
+/// assert_eq!(matrix.get("b", "b"), 0.25);
+/// assert_eq!(matrix.agreed(), 0.75);
+/// ```
+pub fn confusion_matrix(
+    a: &Tier,
+    b: &Tier,
+    sampling: ConfusionSampling,
+) -> Result<ConfusionMatrix> {
+    require_interval_tier(a, "Confusion matrix")?;
+    require_interval_tier(b, "Confusion matrix")?;
+    let (t0, t1) = (a.tmin.max(b.tmin), a.tmax.min(b.tmax));
+    if t1 - t0 <= TIME_EPSILON {
+        return Err(input_error(&format!(
+            "Tiers {} and {} do not overlap in time",
+            a.name, b.name
+        )));
+    }
+    let slices: Vec<(f64, f64)> = match sampling {
+        ConfusionSampling::Exact => {
+            let mut times: Vec<f64> = a
+                .items
+                .iter()
+                .chain(b.items.iter())
+                .flat_map(|item| [item.tmin, item.tmax])
+                .filter(|&time| time > t0 && time < t1)
+                .collect();
+            times.extend([t0, t1]);
+            sorted_unique(times)
+                .windows(2)
+                .map(|window| (window[0], window[1]))
+                .collect()
+        }
+        ConfusionSampling::Step(step) => {
+            if !(step > 0.0 && step.is_finite()) {
+                return Err(input_error(&format!(
+                    "Step should be positive and finite, got {}",
+                    step
+                )));
+            }
+            let n_slices = ((t1 - t0) / step).ceil() as usize;
+            (0..n_slices)
+                .map(|k| (t0 + k as f64 * step, (t0 + (k + 1) as f64 * step).min(t1)))
+                .collect()
+        }
+    };
+    let mut cells: HashMap<(&str, &str), f64> = HashMap::new();
+    for (start, end) in slices {
+        let midpoint = (start + end) / 2.0;
+        *cells
+            .entry((label_at(a, midpoint), label_at(b, midpoint)))
+            .or_default() += end - start;
+    }
+    let sorted_labels = |labels: Vec<&str>| -> Vec<String> {
+        let mut labels: Vec<String> = labels.into_iter().map(String::from).collect();
+        labels.sort();
+        labels.dedup();
+        labels
+    };
+    let labels_a = sorted_labels(cells.keys().map(|&(label, _)| label).collect());
+    let labels_b = sorted_labels(cells.keys().map(|&(_, label)| label).collect());
+    let mut durations = vec![vec![0.0; labels_b.len()]; labels_a.len()];
+    for ((label_a, label_b), duration) in cells {
+        let i = labels_a.binary_search_by(|label| label.as_str().cmp(label_a));
+        let j = labels_b.binary_search_by(|label| label.as_str().cmp(label_b));
+        if let (Ok(i), Ok(j)) = (i, j) {
+            durations[i][j] = duration;
+        }
+    }
+    Ok(ConfusionMatrix {
+        labels_a,
+        labels_b,
+        durations,
+    })
+}
+
+/// Computes the overlap ratio of labelled time between two interval tiers.
+///
+/// This is the duration during which both tiers carry the same non-empty label, divided by the
+/// duration during which either tier carries a non-empty label. Two tiers without labels fully
+/// agree.
+///
+/// # Errors
+///
+/// Returns an error if either tier is a point tier.
+pub fn label_overlap_ratio(a: &Tier, b: &Tier) -> Result<f64> {
+    require_interval_tier(a, "Label overlap")?;
+    require_interval_tier(b, "Label overlap")?;
+    let times = sorted_unique(
+        a.items
+            .iter()
+            .chain(b.items.iter())
+            .flat_map(|item| [item.tmin, item.tmax])
+            .collect(),
+    );
+    let (mut shared, mut labelled) = (0.0, 0.0);
+    for window in times.windows(2) {
+        let duration = window[1] - window[0];
+        let midpoint = (window[0] + window[1]) / 2.0;
+        let (label_a, label_b) = (label_at(a, midpoint), label_at(b, midpoint));
+        if !label_a.is_empty() || !label_b.is_empty() {
+            labelled += duration;
+            if label_a == label_b {
+                shared += duration;
+            }
+        }
+    }
+    if labelled <= TIME_EPSILON {
+        return Ok(1.0);
+    }
+    Ok(shared / labelled)
+}
+
+impl TextGrid {
+    /// Computes agreement measures between this TextGrid and another annotation of the same
+    /// recording, tier by tier.
+    ///
+    /// Interval tiers are paired by name; point tiers and tiers without a counterpart are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other annotation
+    /// * `tolerance` - Tolerance in seconds for [`boundary_agreement`]
+    /// * `step` - Slice duration in seconds for [`cohen_kappa`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `step` is not positive, or two paired tiers do not overlap in time.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{FileType, read_from_str};
+    ///
+    /// let content_a = std::fs::read_to_string("annotator_a.TextGrid").unwrap();
+    /// let a = read_from_str(&content_a, false, FileType::Auto).unwrap();
+    /// let content_b = std::fs::read_to_string("annotator_b.TextGrid").unwrap();
+    /// let b = read_from_str(&content_b, false, FileType::Auto).unwrap();
+    /// for tier in a.agreement(&b, 0.02, 0.01).unwrap() {
+    ///     println!("{}: kappa = {:.3}", tier.tier_name, tier.kappa);
+    /// }
+    /// ```
+    pub fn agreement(
+        &self,
+        other: &TextGrid,
+        tolerance: f64,
+        step: f64,
+    ) -> Result<Vec<TierAgreement>> {
+        self.tiers
+            .iter()
+            .filter(|tier| tier.interval_tier)
+            .filter_map(|tier| {
+                other
+                    .get_tier(&tier.name)
+                    .filter(|other_tier| other_tier.interval_tier)
+                    .map(|other_tier| (tier, other_tier))
+            })
+            .map(|(tier, other_tier)| {
+                Ok(TierAgreement {
+                    tier_name: tier.name.clone(),
+                    boundary_agreement: boundary_agreement(tier, other_tier, tolerance),
+                    kappa: cohen_kappa(tier, other_tier, step)?,
+                    label_overlap: label_overlap_ratio(tier, other_tier)?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,167 @@
+//! Import of speech recognition output with word-level timestamps.
+
+use crate::builder::clipped_tier;
+use crate::textgrid::{TIME_EPSILON, TextGrid, data_error, input_error};
+use serde_json::Value;
+use std::io::Result;
+
+/// The shape of an ASR JSON file read by [`read_asr_json`].
+///
+/// All supported tools write a list of segments, each with a `start`, an `end`, a `text` and a
+/// list of `words` with their own `word`, `start` and `end`, either at the top level or under
+/// a `segments` key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AsrSchema {
+    /// Detect the tool: files with a `word_segments` key are read as WhisperX output, others as
+    /// Whisper output.
+    #[default]
+    Auto,
+    /// Output of openai-whisper run with `word_timestamps=True`. Every word must have
+    /// timestamps.
+    Whisper,
+    /// Output of WhisperX. Words that could not be aligned, such as numbers, have no timestamps
+    /// and are skipped, and the speakers assigned by diarization are read into a `speakers` tier.
+    WhisperX,
+    /// Output of stable-ts, read as Whisper output.
+    StableTs,
+}
+
+/// Reads a time field of a segment or word.
+fn time_field(value: &Value, key: &str) -> Option<f64> {
+    value.get(key).and_then(Value::as_f64)
+}
+
+/// Reads a text field of a segment or word, without the surrounding spaces Whisper keeps.
+fn text_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+impl TextGrid {
+    /// Converts ASR JSON output with word-level timestamps into a TextGrid, see
+    /// [`read_asr_json`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the content is not JSON, has no list of segments, or a segment or,
+    /// unless skipped by the schema, a word has no timestamps. Returns an `InvalidInput` error
+    /// if no segment or word ends after 0, e.g. for a recording without speech, as the TextGrid
+    /// would then be empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{AsrSchema, TextGrid};
+    ///
+    /// let json = r#"{"text": " Hello world.", "segments": [{
+    ///     "start": 0.0, "end": 1.2, "text": " Hello world.",
+    ///     "words": [
+    ///         {"word": " Hello", "start": 0.1, "end": 0.5, "probability": 0.9},
+    ///         {"word": " world.", "start": 0.5, "end": 1.1, "probability": 0.8}
+    ///     ]
+    /// }]}"#;
+    /// let tg = TextGrid::from_asr_json(json, AsrSchema::Whisper).unwrap();
+    /// assert_eq!(tg.tiers[0].name, "segments");
+    /// let words: Vec<&str> = tg.tiers[1].labeled_items().map(|i| i.label.as_str()).collect();
+    /// assert_eq!(words, ["Hello", "world."]);
+    /// ```
+    pub fn from_asr_json(content: &str, schema: AsrSchema) -> Result<TextGrid> {
+        let value: Value = serde_json::from_str(content)
+            .map_err(|e| data_error(&format!("Invalid ASR JSON: {}", e)))?;
+        let schema = match schema {
+            AsrSchema::Auto if value.get("word_segments").is_some() => AsrSchema::WhisperX,
+            AsrSchema::Auto => AsrSchema::Whisper,
+            schema => schema,
+        };
+        let segments = value
+            .as_array()
+            .or_else(|| value.get("segments").and_then(Value::as_array))
+            .ok_or_else(|| data_error("ASR JSON has no list of segments"))?;
+
+        let (mut segment_spans, mut word_spans, mut speaker_spans) = (vec![], vec![], vec![]);
+        for (i, segment) in segments.iter().enumerate() {
+            let (Some(start), Some(end)) =
+                (time_field(segment, "start"), time_field(segment, "end"))
+            else {
+                return Err(data_error(&format!("Segment {} has no timestamps", i)));
+            };
+            segment_spans.push((start, end, text_field(segment, "text")));
+            if schema == AsrSchema::WhisperX && segment.get("speaker").is_some() {
+                speaker_spans.push((start, end, text_field(segment, "speaker")));
+            }
+            let words = segment.get("words").and_then(Value::as_array);
+            for (j, word) in words.into_iter().flatten().enumerate() {
+                match (time_field(word, "start"), time_field(word, "end")) {
+                    (Some(start), Some(end)) => {
+                        word_spans.push((start, end, text_field(word, "word")))
+                    }
+                    _ if schema == AsrSchema::WhisperX => {}
+                    _ => {
+                        return Err(data_error(&format!(
+                            "Word {} of segment {} has no timestamps",
+                            j, i
+                        )));
+                    }
+                }
+            }
+        }
+
+        let end = segment_spans
+            .iter()
+            .chain(&word_spans)
+            .map(|span| span.1)
+            .fold(0.0, f64::max);
+        if end <= TIME_EPSILON {
+            return Err(input_error(
+                "ASR JSON has no segments or words to build a TextGrid from",
+            ));
+        }
+
+        let mut builder = TextGrid::builder()
+            .tmin(0.0)
+            .tier(clipped_tier("segments", segment_spans))
+            .tier(clipped_tier("words", word_spans));
+        if !speaker_spans.is_empty() {
+            builder = builder.tier(clipped_tier("speakers", speaker_spans));
+        }
+        builder.build()
+    }
+}
+
+/// Reads the JSON output of a speech recognizer with word-level timestamps into a TextGrid,
+/// bridging modern ASR output into Praat.
+///
+/// The TextGrid starts at 0, ends with the last segment or word, and is named after the file.
+/// It has a `segments` tier with the text of each segment and a `words` tier with one interval
+/// per word, plus a `speakers` tier for diarized WhisperX output. Timestamps that overlap the
+/// previous segment or word are moved to its end, and unlabelled gaps are filled with empty
+/// intervals.
+///
+/// # Arguments
+///
+/// * `path` - Path to the JSON file
+/// * `schema` - The tool that wrote the file, see [`AsrSchema`]
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, or see [`TextGrid::from_asr_json`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use textgrid::{AsrSchema, TextGridFormat, WriteOptions, read_asr_json};
+///
+/// let tg = read_asr_json("interview.json", AsrSchema::Auto).unwrap();
+/// tg.save_textgrid("interview.TextGrid", TextGridFormat::Long, &WriteOptions::default())
+///     .unwrap();
+/// ```
+pub fn read_asr_json(path: &str, schema: AsrSchema) -> Result<TextGrid> {
+    let content = std::fs::read_to_string(path)?;
+    let mut tg = TextGrid::from_asr_json(&content, schema)?;
+    tg.name = crate::file_stem(path);
+    Ok(tg)
+}
@@ -0,0 +1,172 @@
+//! Bounds of TextGrids derived from their paired WAV files.
+
+use crate::converter::TierData;
+use crate::textgrid::{TIME_EPSILON, TextGrid, data_error};
+use std::io::Result;
+
+/// Reads the duration of a WAV file in seconds from its header.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is not a valid WAV file.
+///
+/// # Examples
+///
+/// ```no_run
+/// let duration = textgrid::audio_duration("recording.wav").unwrap();
+/// println!("{} s", duration);
+/// ```
+pub fn audio_duration(wav_path: &str) -> Result<f64> {
+    let reader = hound::WavReader::open(wav_path).map_err(|e| match e {
+        hound::Error::IoError(e) => e,
+        e => data_error(&format!("{}: {}", wav_path, e)),
+    })?;
+    Ok(reader.duration() as f64 / reader.spec().sample_rate as f64)
+}
+
+impl TextGrid {
+    /// Returns an error if an item ends after the end of the audio.
+    ///
+    /// If `old_tmax` is given, the last interval of a tier ending there is allowed to, since
+    /// [`TextGrid::with_audio_duration`] moves its end to the end of the audio, as long as it
+    /// starts before it.
+    fn check_within_audio(
+        &self,
+        duration: f64,
+        wav_path: &str,
+        old_tmax: Option<f64>,
+    ) -> Result<()> {
+        for tier in &self.tiers {
+            for (index, item) in tier.items.iter().enumerate() {
+                let moved = tier.interval_tier
+                    && index + 1 == tier.items.len()
+                    && old_tmax.is_some_and(|tmax| (item.tmax - tmax).abs() <= TIME_EPSILON);
+                let past_end = if moved {
+                    duration - item.tmin <= TIME_EPSILON
+                } else {
+                    item.tmax - duration > TIME_EPSILON
+                };
+                if past_end {
+                    return Err(data_error(&format!(
+                        "Item [{}, {}] \"{}\" of tier {} ends after the end of {} at {}",
+                        item.tmin, item.tmax, item.label, tier.name, wav_path, duration
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the end of the TextGrid to the exact duration of its audio file, whether it is
+    /// longer or shorter than the TextGrid.
+    ///
+    /// Tiers ending with the TextGrid are moved to the new end, and so are the last intervals
+    /// ending there, so tiers that covered the whole TextGrid still do. Tiers ending after the
+    /// audio are cut at its end.
+    ///
+    /// # Arguments
+    ///
+    /// * `wav_path` - Path to the WAV file the TextGrid annotates
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the WAV file cannot be read, the TextGrid or a tier starts at or after
+    /// the end of the audio, an item other than such a last interval ends after the end of the
+    /// audio, or such a last interval starts after it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{FileType, read_from_file};
+    ///
+    /// let tg = read_from_file("recording.TextGrid", false, FileType::Auto)
+    ///     .unwrap()
+    ///     .with_audio_duration("recording.wav")
+    ///     .unwrap();
+    /// ```
+    pub fn with_audio_duration(mut self, wav_path: &str) -> Result<TextGrid> {
+        let duration = audio_duration(wav_path)?;
+        let old_tmax = self.tmax;
+        let starts = std::iter::once((String::from("The TextGrid"), self.tmin)).chain(
+            self.tiers
+                .iter()
+                .map(|tier| (format!("Tier {}", tier.name), tier.tmin)),
+        );
+        for (what, tmin) in starts {
+            if duration - tmin <= TIME_EPSILON {
+                return Err(data_error(&format!(
+                    "{} starts at {}, not before the end of {} at {}",
+                    what, tmin, wav_path, duration
+                )));
+            }
+        }
+        self.check_within_audio(duration, wav_path, Some(old_tmax))?;
+        for tier in &mut self.tiers {
+            if tier.tmax - old_tmax >= -TIME_EPSILON || tier.tmax > duration {
+                tier.tmax = duration;
+            }
+            if let (true, Some(last)) = (tier.interval_tier, tier.items.last_mut())
+                && (last.tmax - old_tmax).abs() <= TIME_EPSILON
+            {
+                last.tmax = duration;
+            }
+        }
+        self.tmax = duration;
+        Ok(self)
+    }
+
+    /// Creates a TextGrid from data, as [`TextGrid::from_data`] does, starting at 0 and ending
+    /// at the exact duration of its audio file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the WAV file cannot be read, an item ends after the end of the
+    /// audio, or [`TextGrid::from_data`] fails.
+    pub fn from_data_with_audio(
+        data: Vec<TierData>,
+        name: Option<String>,
+        wav_path: &str,
+    ) -> Result<TextGrid> {
+        let duration = audio_duration(wav_path)?;
+        let tg = TextGrid::from_data(data, name, Some(0.0), Some(duration))?;
+        tg.check_within_audio(duration, wav_path, None)?;
+        Ok(tg)
+    }
+
+    /// Checks that the end of the TextGrid agrees with the duration of its audio file, e.g. to
+    /// flag TextGrids of a corpus that were made for another version of the recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `wav_path` - Path to the WAV file the TextGrid annotates
+    /// * `tolerance` - Largest accepted difference in seconds
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error stating both durations if they differ by more than
+    /// `tolerance`, or an error if the WAV file cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::read_corpus;
+    ///
+    /// let corpus = read_corpus("corpus", true, false).unwrap();
+    /// for (path, tg) in &corpus.textgrids {
+    ///     let wav = std::path::Path::new("corpus").join(path).with_extension("wav");
+    ///     if let Err(e) = tg.check_audio_duration(wav.to_str().unwrap(), 0.01) {
+    ///         println!("{}: {}", path.display(), e);
+    ///     }
+    /// }
+    /// ```
+    pub fn check_audio_duration(&self, wav_path: &str, tolerance: f64) -> Result<()> {
+        let duration = audio_duration(wav_path)?;
+        if (self.tmax - duration).abs() > tolerance {
+            return Err(data_error(&format!(
+                "TextGrid {} ends at {} but {} lasts {} s",
+                self.name, self.tmax, wav_path, duration
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,437 @@
+//! `gridio`, a command-line tool for common operations on TextGrid files.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use regex::Regex;
+use std::borrow::Cow;
+use std::io::{Error, ErrorKind, Result};
+use std::process::ExitCode;
+use textgrid::{
+    FileType, LineEnding, ParseOptions, PipelineConfig, TextGrid, TextGridFormat, WriteOptions,
+    read_from_file, read_from_str_with, run_pipeline,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "gridio",
+    version,
+    about = "Common operations on Praat TextGrid files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Combine the tiers of several TextGrids of the same recording, or concatenate them
+    Merge(MergeArgs),
+    /// Keep some tiers and crop a time window of a TextGrid
+    Extract(ExtractArgs),
+    /// Replace a regex in the labels of TextGrid files, in place
+    Relabel(RelabelArgs),
+    /// Split TextGrids into windows or groups of utterances, each written to its own file
+    Chunk(ChunkArgs),
+    /// Report the differences between two revisions of a TextGrid
+    ///
+    /// Like diff(1), exits with 0 if the TextGrids agree, 1 if they differ and 2 if either
+    /// cannot be read.
+    Diff(DiffArgs),
+    /// Run the batch pipeline described by a TOML or YAML file
+    Run(RunArgs),
+}
+
+/// The format of written TextGrids.
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Long,
+    Short,
+}
+
+impl From<Format> for FileType {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Long => FileType::Long,
+            Format::Short => FileType::Short,
+        }
+    }
+}
+
+impl From<Format> for TextGridFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Long => TextGridFormat::Long,
+            Format::Short => TextGridFormat::Short,
+        }
+    }
+}
+
+#[derive(Args)]
+struct MergeArgs {
+    /// TextGrid files to merge, in order
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    /// Output TextGrid file
+    #[arg(short, long)]
+    output: String,
+    /// Join the files end to end in time instead of stacking their tiers; tiers with the same
+    /// name are concatenated
+    #[arg(long)]
+    concat: bool,
+    /// Format of the output file
+    #[arg(long, value_enum, default_value = "long")]
+    format: Format,
+}
+
+#[derive(Args)]
+struct ExtractArgs {
+    /// Input TextGrid file
+    input: String,
+    /// Output TextGrid file
+    #[arg(short, long)]
+    output: String,
+    /// Comma-separated names of the tiers to keep, in output order; all tiers by default
+    #[arg(long, value_delimiter = ',')]
+    tiers: Vec<String>,
+    /// Start of the time window, the start of the TextGrid by default
+    #[arg(long)]
+    from: Option<f64>,
+    /// End of the time window, the end of the TextGrid by default
+    #[arg(long)]
+    to: Option<f64>,
+    /// Shift the times of the window to start at zero
+    #[arg(long)]
+    rebase: bool,
+    /// Format of the output file
+    #[arg(long, value_enum, default_value = "long")]
+    format: Format,
+}
+
+#[derive(Args)]
+struct RelabelArgs {
+    /// TextGrid files or glob patterns, e.g. 'corpus/**/*.TextGrid'
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    /// Comma-separated names of the tiers to relabel; all tiers by default
+    #[arg(long, value_delimiter = ',')]
+    tier: Vec<String>,
+    /// Regular expression to search for in the labels
+    #[arg(long)]
+    pattern: String,
+    /// Replacement text, which may refer to capture groups as `$1` or `${name}`
+    #[arg(long)]
+    replace: String,
+    /// Only report how many labels would change, without writing any file
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Args)]
+struct ChunkArgs {
+    /// TextGrid files or glob patterns, e.g. 'corpus/**/*.TextGrid'
+    #[arg(required = true)]
+    inputs: Vec<String>,
+    /// Output directory, where chunks are named after their file and index, e.g. `a_0.TextGrid`;
+    /// inputs whose chunks would get the same names are rejected
+    #[arg(short, long)]
+    output: String,
+    /// Duration of each chunk in seconds, or the longest duration of a chunk with `--by-tier`
+    #[arg(long)]
+    window: f64,
+    /// Time between the starts of two consecutive chunks, the window by default
+    #[arg(long, conflicts_with = "by_tier")]
+    hop: Option<f64>,
+    /// Group consecutive intervals of this tier into chunks instead of cutting fixed windows
+    #[arg(long)]
+    by_tier: Option<String>,
+    /// Format of the output files
+    #[arg(long, value_enum, default_value = "long")]
+    format: Format,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// Old revision of the TextGrid
+    old: String,
+    /// New revision of the TextGrid
+    new: String,
+    /// Boundaries moving by at most this many seconds are considered unchanged
+    #[arg(long, default_value_t = 0.0)]
+    tol: f64,
+}
+
+#[derive(Args)]
+struct RunArgs {
+    /// Pipeline file, in YAML if its extension is `.yaml` or `.yml` and in TOML otherwise
+    config: String,
+}
+
+/// Reads a TextGrid file of either format.
+fn read(path: &str) -> Result<TextGrid> {
+    read_from_file(path, false, FileType::Auto)
+        .map_err(|e| std::io::Error::new(e.kind(), format!("{}: {}", path, e)))
+}
+
+/// Reads a TextGrid file of either format, keeping its unknown fields, along with its format
+/// and the options that write it back with the same line endings, trailing spaces and byte
+/// order mark.
+fn read_with_layout(path: &str) -> Result<(TextGrid, Format, WriteOptions)> {
+    let with_path = |e: Error| Error::new(e.kind(), format!("{}: {}", path, e));
+    let content = std::fs::read_to_string(path).map_err(with_path)?;
+    let format = match FileType::detect(&content).map_err(with_path)? {
+        FileType::Short => Format::Short,
+        _ => Format::Long,
+    };
+    let parse_options = ParseOptions {
+        preserve_unknown_fields: true,
+        ..Default::default()
+    };
+    let tg = read_from_str_with(&content, false, FileType::from(format), &parse_options)
+        .map_err(with_path)?;
+    let write_options = WriteOptions {
+        line_ending: if content.contains("\r\n") {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        },
+        praat_trailing_spaces: content
+            .lines()
+            .find(|line| line.starts_with("xmin"))
+            .is_some_and(|line| line.ends_with(' ')),
+        bom: content.starts_with('\u{feff}'),
+        ..Default::default()
+    };
+    Ok((tg, format, write_options))
+}
+
+/// Writes a TextGrid file.
+fn write(tg: &TextGrid, path: &str, format: Format) -> Result<()> {
+    tg.save_textgrid(path, format.into(), &WriteOptions::default())
+}
+
+/// Returns the file name of a path without its extension.
+fn stem(path: &str) -> &str {
+    std::path::Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(path)
+}
+
+/// Merges the input files into one TextGrid.
+///
+/// Without `--concat`, tiers are stacked and a tier whose name is taken gets the name of its
+/// file as a suffix, e.g. `words_b` for the `words` tier of `b.TextGrid`.
+fn merge(args: MergeArgs) -> Result<()> {
+    let mut merged: Option<TextGrid> = None;
+    for path in &args.inputs {
+        let tg = read(path)?;
+        match merged.as_mut() {
+            None => merged = Some(tg),
+            Some(merged) if args.concat => merged.concat(tg)?,
+            Some(merged) => merged.merge_with(tg, &format!("_{}", stem(path))),
+        }
+    }
+    let mut merged = merged.unwrap_or_default();
+    merged.name = stem(&args.output).to_string();
+    write(&merged, &args.output, args.format)
+}
+
+/// Keeps the selected tiers of the input file and crops them to the time window.
+fn extract(args: ExtractArgs) -> Result<()> {
+    let mut tg = read(&args.input)?;
+    if !args.tiers.is_empty() {
+        let tiers = args
+            .tiers
+            .iter()
+            .map(|name| {
+                tg.get_tier(name).cloned().ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("{}: no tier \"{}\"", args.input, name),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        tg.tiers.clear();
+        for tier in tiers {
+            tg.push_tier(tier);
+        }
+    }
+    if args.from.is_some() || args.to.is_some() || args.rebase {
+        let (t0, t1) = (args.from.unwrap_or(tg.tmin), args.to.unwrap_or(tg.tmax));
+        tg = tg.extract(t0, t1, args.rebase)?;
+    }
+    write(&tg, &args.output, args.format)
+}
+
+/// Expands the glob patterns of the inputs, keeping paths that match nothing so that reading
+/// them reports the missing file.
+fn expand_inputs(inputs: &[String]) -> Result<Vec<String>> {
+    let mut paths = vec![];
+    for input in inputs {
+        let matches = glob::glob(input)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("{}: {}", input, e)))?
+            .filter_map(|entry| entry.ok())
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        if matches.is_empty() {
+            paths.push(input.clone());
+        } else {
+            paths.extend(matches);
+        }
+    }
+    Ok(paths)
+}
+
+/// Replaces the pattern in the labels of the selected tiers of each file, writing the files
+/// back in their own format and layout, and prints how many labels changed in each file.
+fn relabel(args: RelabelArgs) -> Result<()> {
+    let invalid = |e: regex::Error| Error::new(ErrorKind::InvalidInput, e.to_string());
+    let pattern = Regex::new(&args.pattern).map_err(invalid)?;
+    let tier_filter = if args.tier.is_empty() {
+        None
+    } else {
+        let names: Vec<String> = args.tier.iter().map(|name| regex::escape(name)).collect();
+        Some(Regex::new(&format!("^(?:{})$", names.join("|"))).map_err(invalid)?)
+    };
+
+    let (mut total, mut files) = (0, 0);
+    for path in expand_inputs(&args.inputs)? {
+        let (mut tg, format, options) = read_with_layout(&path)?;
+        let mut changed = 0;
+        let tiers = tg.tiers.iter_mut().filter(|tier| {
+            tier_filter
+                .as_ref()
+                .is_none_or(|re| re.is_match(&tier.name))
+        });
+        for item in tiers.flat_map(|tier| tier.items.iter_mut()) {
+            if let Cow::Owned(label) = pattern.replace_all(&item.label, args.replace.as_str())
+                && label != item.label
+            {
+                item.label = label;
+                changed += 1;
+            }
+        }
+        if changed == 0 {
+            continue;
+        }
+        if args.dry_run {
+            println!("{}: {} labels would change", path, changed);
+        } else {
+            tg.save_textgrid(&path, format.into(), &options)?;
+            println!("{}: {} labels changed", path, changed);
+        }
+        total += changed;
+        files += 1;
+    }
+    println!(
+        "{} labels in {} files {}",
+        total,
+        files,
+        if args.dry_run {
+            "would change"
+        } else {
+            "changed"
+        }
+    );
+    Ok(())
+}
+
+/// Splits each file into chunks with times starting at zero and writes them to the output
+/// directory.
+///
+/// Fails before writing the chunks of a file if one of them would overwrite a chunk of an
+/// earlier file with the same name, e.g. `a/s1.TextGrid` and `b/s1.TextGrid`.
+fn chunk(args: ChunkArgs) -> Result<()> {
+    let output = std::path::Path::new(&args.output);
+    std::fs::create_dir_all(output)?;
+    let mut written = std::collections::HashMap::new();
+    let mut total = 0;
+    for path in expand_inputs(&args.inputs)? {
+        let tg = read(&path)?;
+        let chunks = match &args.by_tier {
+            Some(tier) => tg.chunk_by_tier(tier.as_str(), args.window),
+            None => tg.chunk(args.window, args.hop.unwrap_or(args.window)),
+        }
+        .map_err(|e| Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        let chunk_paths: Vec<_> = chunks
+            .iter()
+            .map(|chunk| output.join(format!("{}.TextGrid", chunk.name)))
+            .collect();
+        for chunk_path in &chunk_paths {
+            if let Some(other) = written.get(chunk_path) {
+                return Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!(
+                        "{}: chunk {} would overwrite a chunk of {}",
+                        path,
+                        chunk_path.display(),
+                        other
+                    ),
+                ));
+            }
+        }
+        for (chunk, chunk_path) in chunks.iter().zip(chunk_paths) {
+            write(chunk, &chunk_path.to_string_lossy(), args.format)?;
+            written.insert(chunk_path, path.clone());
+        }
+        println!("{}: {} chunks", path, chunks.len());
+        total += chunks.len();
+    }
+    println!("{} chunks written to {}", total, args.output);
+    Ok(())
+}
+
+/// Prints the differences between the two revisions, returning whether there are any.
+fn diff(args: DiffArgs) -> Result<bool> {
+    let (old, new) = (read(&args.old)?, read(&args.new)?);
+    let diff = old.diff(&new, args.tol);
+    print!("--- {}\n+++ {}\n{}", args.old, args.new, diff);
+    Ok(!diff.is_empty())
+}
+
+/// Runs a pipeline, printing the errors of the files that could not be processed, and fails if
+/// there are any.
+fn run(args: RunArgs) -> Result<()> {
+    let config = PipelineConfig::from_file(&args.config)?;
+    let results = run_pipeline(&config)?;
+    let failures = results.values().filter(|result| result.is_err()).count();
+    for result in results.values() {
+        if let Err(e) = result {
+            eprintln!("{}", e);
+        }
+    }
+    println!(
+        "{} files written to {}, {} failed",
+        results.len() - failures,
+        config.output.dir,
+        failures
+    );
+    if failures > 0 {
+        return Err(Error::other(format!("{} files failed", failures)));
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // Like diff(1), `diff` keeps 1 for "differences found" and reports errors with 2
+    let error_code = match cli.command {
+        Command::Diff(_) => ExitCode::from(2),
+        _ => ExitCode::FAILURE,
+    };
+    let result = match cli.command {
+        Command::Merge(args) => merge(args).map(|()| ExitCode::SUCCESS),
+        Command::Extract(args) => extract(args).map(|()| ExitCode::SUCCESS),
+        Command::Relabel(args) => relabel(args).map(|()| ExitCode::SUCCESS),
+        Command::Chunk(args) => chunk(args).map(|()| ExitCode::SUCCESS),
+        Command::Diff(args) => diff(args).map(|differ| ExitCode::from(differ as u8)),
+        Command::Run(args) => run(args).map(|()| ExitCode::SUCCESS),
+    };
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("gridio: {}", e);
+            error_code
+        }
+    }
+}
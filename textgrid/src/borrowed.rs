@@ -0,0 +1,364 @@
+//! Borrowed TextGrid types whose strings point into the parsed content.
+//!
+//! Parsing into these types avoids allocating a `String` for every label, which matters for
+//! large corpora whose labels are only inspected.
+
+use crate::textgrid::{Item, TIME_EPSILON, TextGrid, Tier, data_error};
+use crate::validation::{ValidationReport, ZeroLengthPolicy};
+use std::borrow::Cow;
+use std::io::Result;
+
+/// An item (interval or point) whose label borrows from the parsed content.
+///
+/// For point tiers, `tmin` equals `tmax`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ItemRef<'a> {
+    /// Start time of the item.
+    pub tmin: f64,
+    /// End time of the item.
+    pub tmax: f64,
+    /// Text label of the item; owned only if it contained escaped quotes.
+    pub label: Cow<'a, str>,
+}
+
+/// A tier whose name and labels borrow from the parsed content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierRef<'a> {
+    /// Name of the tier; owned only if it contained escaped quotes.
+    pub name: Cow<'a, str>,
+    /// Number of items in the tier.
+    pub size: usize,
+    /// Vector of items contained in the tier.
+    pub items: Vec<ItemRef<'a>>,
+    /// Whether this is an interval tier (true) or point tier (false).
+    pub interval_tier: bool,
+    /// Minimum time of the tier.
+    pub tmin: f64,
+    /// Maximum time of the tier.
+    pub tmax: f64,
+    /// Key/value lines of the tier not recognized by the parser, see [`Tier::unknown_fields`].
+    pub unknown_fields: Vec<(&'a str, &'a str)>,
+}
+
+/// A TextGrid whose tier names and labels borrow from the parsed content, as returned by
+/// [`read_from_str_ref`](crate::read_from_str_ref).
+///
+/// Unlike [`TextGrid`], it has no name, since it is not read from a file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextGridRef<'a> {
+    /// Minimum time of the TextGrid.
+    pub tmin: f64,
+    /// Maximum time of the TextGrid.
+    pub tmax: f64,
+    /// Number of tiers in the TextGrid.
+    pub size: usize,
+    /// Vector of tiers contained in the TextGrid.
+    pub tiers: Vec<TierRef<'a>>,
+    /// Key/value lines of the header not recognized by the parser, see
+    /// [`TextGrid::unknown_fields`].
+    pub unknown_fields: Vec<(&'a str, &'a str)>,
+    /// Problems the parser recovered from, see [`TextGrid::warnings`].
+    pub warnings: Vec<String>,
+}
+
+impl Default for TierRef<'_> {
+    fn default() -> Self {
+        TierRef {
+            name: Cow::Borrowed(""),
+            size: 0,
+            items: Vec::new(),
+            interval_tier: true,
+            tmin: 0.0,
+            tmax: 0.0,
+            unknown_fields: Vec::new(),
+        }
+    }
+}
+
+/// Copies borrowed key/value pairs.
+fn owned_fields(fields: Vec<(&str, &str)>) -> Vec<(String, String)> {
+    fields
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+impl ItemRef<'_> {
+    /// Converts the item into an owned [`Item`], copying its label.
+    pub fn into_owned(self) -> Item {
+        Item {
+            tmin: self.tmin,
+            tmax: self.tmax,
+            label: self.label.into_owned(),
+        }
+    }
+}
+
+impl TierRef<'_> {
+    /// Converts the tier into an owned [`Tier`], copying its name and labels.
+    pub fn into_owned(self) -> Tier {
+        Tier {
+            name: self.name.into_owned(),
+            size: self.size,
+            items: self.items.into_iter().map(ItemRef::into_owned).collect(),
+            interval_tier: self.interval_tier,
+            tmin: self.tmin,
+            tmax: self.tmax,
+            unknown_fields: owned_fields(self.unknown_fields),
+        }
+    }
+
+    /// Validates the tier with the same checks as [`Tier::assert_valid`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier is invalid.
+    pub fn assert_valid(&self) -> Result<()> {
+        let mut report = ValidationReport::default();
+        self.check(&mut report, None, ZeroLengthPolicy::Reject);
+        report.into_result()
+    }
+
+    /// Adds the issues of the tier to a report.
+    fn check(
+        &self,
+        report: &mut ValidationReport,
+        tier_index: Option<usize>,
+        zero_length: ZeroLengthPolicy,
+    ) {
+        report.check_tier(
+            tier_index,
+            &self.name,
+            self.size,
+            self.interval_tier,
+            (self.tmin, self.tmax),
+            &self.items,
+            |item| (item.tmin, item.tmax),
+            zero_length,
+        );
+    }
+}
+
+impl<'a> TextGridRef<'a> {
+    /// Returns the tier with the given name, if any.
+    pub fn get_tier(&self, name: &str) -> Option<&TierRef<'a>> {
+        self.tiers.iter().find(|tier| tier.name == name)
+    }
+
+    /// Converts the TextGrid into an owned [`TextGrid`] with an empty name, copying its tier
+    /// names and labels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{FileType, read_from_str_ref};
+    ///
+    /// let content = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1\n<exists>\n1\n\"IntervalTier\"\n\"words\"\n0\n1\n1\n0\n1\n\"hello\"\n";
+    /// let tg = read_from_str_ref(content, true, FileType::Auto).unwrap().into_owned();
+    /// assert_eq!(tg.tiers[0].items[0].label, "hello");
+    /// ```
+    pub fn into_owned(self) -> TextGrid {
+        TextGrid {
+            tmin: self.tmin,
+            tmax: self.tmax,
+            size: self.size,
+            name: String::new(),
+            tiers: self.tiers.into_iter().map(TierRef::into_owned).collect(),
+            unknown_fields: owned_fields(self.unknown_fields),
+            warnings: self.warnings,
+        }
+    }
+
+    /// Compares the declared sizes of the TextGrid and its tiers with the number of tiers and
+    /// items read.
+    ///
+    /// A mismatch is an error if `strict` is true; otherwise it is recorded as a warning and the
+    /// size is set to the number read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error for the first mismatch if `strict` is true.
+    pub(crate) fn check_sizes(&mut self, strict: bool) -> Result<()> {
+        let mut mismatches = Vec::new();
+        if self.size != self.tiers.len() {
+            mismatches.push(format!(
+                "The TextGrid declares {} tiers but has {}",
+                self.size,
+                self.tiers.len()
+            ));
+            self.size = self.tiers.len();
+        }
+        for tier in self.tiers.iter_mut() {
+            if tier.size != tier.items.len() {
+                let kind = if tier.interval_tier {
+                    "intervals"
+                } else {
+                    "points"
+                };
+                mismatches.push(format!(
+                    "Tier {} declares {} {} but has {}",
+                    tier.name,
+                    tier.size,
+                    kind,
+                    tier.items.len()
+                ));
+                tier.size = tier.items.len();
+            }
+        }
+        if strict && let Some(mismatch) = mismatches.first() {
+            return Err(data_error(mismatch));
+        }
+        self.warnings.extend(mismatches);
+        Ok(())
+    }
+
+    /// Clamps tiers to the bounds of the TextGrid and items to the bounds of their tier,
+    /// recording a warning for each change, see [`ParseOptions::clamp_to_bounds`].
+    ///
+    /// Intervals and points lying entirely outside the bounds are removed.
+    ///
+    /// [`ParseOptions::clamp_to_bounds`]: crate::ParseOptions::clamp_to_bounds
+    pub(crate) fn clamp_to_bounds(&mut self) {
+        let (tg_tmin, tg_tmax) = (self.tmin, self.tmax);
+        for tier in self.tiers.iter_mut() {
+            if tier.tmin < tg_tmin || tier.tmax > tg_tmax {
+                let (tmin, tmax) = (tier.tmin.max(tg_tmin), tier.tmax.min(tg_tmax));
+                self.warnings.push(format!(
+                    "Tier {} [{}, {}] clamped to [{}, {}]",
+                    tier.name, tier.tmin, tier.tmax, tmin, tmax
+                ));
+                (tier.tmin, tier.tmax) = (tmin, tmax);
+            }
+            let (tier_tmin, tier_tmax) = (tier.tmin, tier.tmax);
+            let count = tier.items.len();
+            let mut index = 0;
+            tier.items.retain_mut(|item| {
+                index += 1;
+                if item.tmin >= tier_tmin && item.tmax <= tier_tmax {
+                    return true;
+                }
+                let (tmin, tmax) = (item.tmin.max(tier_tmin), item.tmax.min(tier_tmax));
+                let kept = if tier.interval_tier {
+                    tmax - tmin > TIME_EPSILON
+                } else {
+                    tmin == tmax
+                };
+                let change = if kept {
+                    format!("clamped to [{}, {}]", tmin, tmax)
+                } else {
+                    String::from("removed")
+                };
+                self.warnings.push(format!(
+                    "Item {} [{}, {}] of tier {} lies outside [{}, {}] and was {}",
+                    index - 1,
+                    item.tmin,
+                    item.tmax,
+                    tier.name,
+                    tier_tmin,
+                    tier_tmax,
+                    change
+                ));
+                (item.tmin, item.tmax) = (tmin, tmax);
+                kept
+            });
+            tier.size -= count - tier.items.len();
+        }
+    }
+
+    /// Applies a zero-length policy to the intervals of the TextGrid, recording a warning for
+    /// each zero-length interval unless the policy is [`ZeroLengthPolicy::Reject`], see
+    /// [`ParseOptions::zero_length`].
+    ///
+    /// [`ParseOptions::zero_length`]: crate::ParseOptions::zero_length
+    pub(crate) fn apply_zero_length_policy(&mut self, policy: ZeroLengthPolicy) {
+        if policy == ZeroLengthPolicy::Reject {
+            return;
+        }
+        let is_zero = |item: &ItemRef| (item.tmax - item.tmin).abs() <= TIME_EPSILON;
+        for tier in self.tiers.iter_mut().filter(|tier| tier.interval_tier) {
+            if !tier.items.iter().any(is_zero) {
+                continue;
+            }
+            let count = tier.items.len();
+            let has_target = tier.items.iter().any(|item| !is_zero(item));
+            let mut kept: Vec<ItemRef<'a>> = Vec::with_capacity(count);
+            // Zero-length intervals before the first kept one, merged into it once found
+            let mut pending: Vec<ItemRef<'a>> = Vec::new();
+            for (index, item) in std::mem::take(&mut tier.items).into_iter().enumerate() {
+                if !is_zero(&item) {
+                    kept.push(item);
+                    let target = kept.last_mut().unwrap();
+                    // Prepended last first, so that the labels keep their order
+                    for item in pending.drain(..).rev() {
+                        merge_item(target, item, false);
+                    }
+                    continue;
+                }
+                let change = match (policy, kept.last_mut()) {
+                    (ZeroLengthPolicy::Warn, _) => "was kept",
+                    (ZeroLengthPolicy::Merge, Some(previous)) => {
+                        merge_item(previous, item.clone(), true);
+                        "was merged into the previous interval"
+                    }
+                    (ZeroLengthPolicy::Merge, None) if has_target => {
+                        pending.push(item.clone());
+                        "was merged into the next interval"
+                    }
+                    _ => "was removed",
+                };
+                self.warnings.push(format!(
+                    "Interval {} [{}, {}] of tier {} has zero length and {}",
+                    index, item.tmin, item.tmax, tier.name, change
+                ));
+                if policy == ZeroLengthPolicy::Warn {
+                    kept.push(item);
+                }
+            }
+            tier.size -= count - kept.len();
+            tier.items = kept;
+        }
+    }
+
+    /// Validates the TextGrid with the same checks as [`TextGrid::assert_valid`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TextGrid or one of its tiers is invalid.
+    pub fn assert_valid(&self) -> Result<()> {
+        self.assert_valid_with(ZeroLengthPolicy::Reject)
+    }
+
+    /// Validates the TextGrid like [`TextGridRef::assert_valid`], reporting zero-length
+    /// intervals according to `zero_length`.
+    pub(crate) fn assert_valid_with(&self, zero_length: ZeroLengthPolicy) -> Result<()> {
+        let mut report = ValidationReport::default();
+        report.check_textgrid(self.size, self.tiers.len(), (self.tmin, self.tmax));
+        for (tier_index, tier) in self.tiers.iter().enumerate() {
+            tier.check(&mut report, Some(tier_index), zero_length);
+            report.check_tier_within(
+                tier_index,
+                &tier.name,
+                (tier.tmin, tier.tmax),
+                (self.tmin, self.tmax),
+            );
+        }
+        report.into_result()
+    }
+}
+
+/// Merges a zero-length interval into a neighbouring interval, widening its bounds and joining
+/// the labels with a space, the neighbour's first if `after` is true.
+fn merge_item<'a>(target: &mut ItemRef<'a>, item: ItemRef<'a>, after: bool) {
+    target.tmin = target.tmin.min(item.tmin);
+    target.tmax = target.tmax.max(item.tmax);
+    if item.label.is_empty() {
+        return;
+    }
+    target.label = if target.label.is_empty() {
+        item.label
+    } else if after {
+        Cow::Owned(format!("{} {}", target.label, item.label))
+    } else {
+        Cow::Owned(format!("{} {}", item.label, target.label))
+    };
+}
@@ -0,0 +1,302 @@
+//! Fluent construction of TextGrids.
+
+use crate::textgrid::{Item, TIME_EPSILON, TextGrid, Tier, data_error, input_error};
+use std::io::Result;
+
+/// Builder of a [`TextGrid`], created with [`TextGrid::builder`].
+///
+/// The builder computes `size`, `tmin` and `tmax` of the TextGrid and its tiers, and validates
+/// the result in [`build`](TextGridBuilder::build).
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::TextGrid;
+///
+/// let tg = TextGrid::builder()
+///     .name("example")
+///     .tmax(2.0)
+///     .interval_tier("words", |t| t.interval(0.0, 1.0, "hi").interval(1.0, 2.0, "there"))
+///     .point_tier("beats", |t| t.point(0.5, "b1"))
+///     .build()
+///     .unwrap();
+/// assert_eq!(tg.size, 2);
+/// assert_eq!(tg.tiers[1].tmax, 2.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TextGridBuilder {
+    name: String,
+    tmin: Option<f64>,
+    tmax: Option<f64>,
+    tiers: Vec<TierBuilder>,
+}
+
+/// Builder of a tier, passed to [`TextGridBuilder::interval_tier`] and
+/// [`TextGridBuilder::point_tier`] or used on its own.
+///
+/// Items can be added in any order and are sorted at build time. Building fails if two items
+/// overlap, an interval has zero width, or an item lies outside the tier bounds, so built tiers
+/// are always valid for Praat.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::TierBuilder;
+///
+/// let tier = TierBuilder::intervals("words")
+///     .bounds(0.0, 2.0)
+///     .interval(1.2, 1.8, "world")
+///     .interval(0.3, 1.0, "hello")
+///     .fill_gaps("")
+///     .build()
+///     .unwrap();
+/// let labels: Vec<&str> = tier.items.iter().map(|i| i.label.as_str()).collect();
+/// assert_eq!(labels, vec!["", "hello", "", "world", ""]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TierBuilder {
+    name: String,
+    interval_tier: bool,
+    items: Vec<Item>,
+    bounds: Option<(f64, f64)>,
+    gap_label: Option<String>,
+}
+
+impl TierBuilder {
+    /// Creates a builder of an interval tier.
+    pub fn intervals(name: &str) -> Self {
+        Self::new(name, true)
+    }
+
+    /// Creates a builder of a point tier.
+    pub fn points(name: &str) -> Self {
+        Self::new(name, false)
+    }
+
+    fn new(name: &str, interval_tier: bool) -> Self {
+        TierBuilder {
+            name: name.to_string(),
+            interval_tier,
+            items: Vec::new(),
+            bounds: None,
+            gap_label: None,
+        }
+    }
+
+    /// Adds an interval `[tmin, tmax]` with a label.
+    pub fn interval(mut self, tmin: f64, tmax: f64, label: &str) -> Self {
+        self.items.push(Item::interval(tmin, tmax, label));
+        self
+    }
+
+    /// Adds a point at `time` with a label.
+    pub fn point(mut self, time: f64, label: &str) -> Self {
+        self.items.push(Item::point(time, label));
+        self
+    }
+
+    /// Sets the bounds of the tier. When built on its own, a tier spans by default from 0 (or its
+    /// first item, if earlier) to the end of its last item, like a [`TextGridBuilder`] without
+    /// explicit bounds.
+    ///
+    /// Within a [`TextGridBuilder`], tiers span the bounds of the TextGrid instead.
+    pub fn bounds(mut self, tmin: f64, tmax: f64) -> Self {
+        self.bounds = Some((tmin, tmax));
+        self
+    }
+
+    /// Fills the gaps between intervals, and between the tier bounds and the first and last
+    /// intervals, with intervals labelled `label`. Ignored for point tiers.
+    pub fn fill_gaps(mut self, label: &str) -> Self {
+        self.gap_label = Some(label.to_string());
+        self
+    }
+
+    /// Builds the tier.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if two items overlap, an interval has zero width, an item lies outside
+    /// the tier bounds, or the tier is invalid (see [`Tier::assert_valid`]). Without
+    /// [`bounds`](TierBuilder::bounds), building also fails if the default bounds would be empty,
+    /// e.g. for a tier without items or with a single point at 0.
+    pub fn build(self) -> Result<Tier> {
+        let (tmin, tmax) = match (self.bounds, self.extent()) {
+            (Some(bounds), _) => bounds,
+            (None, Some((first, last))) if last - first.min(0.0) > TIME_EPSILON => {
+                (first.min(0.0), last)
+            }
+            (None, _) => {
+                return Err(input_error(&format!(
+                    "Cannot infer the bounds of tier {}; set them with bounds()",
+                    self.name
+                )));
+            }
+        };
+        let tier = self.build_within(tmin, tmax)?;
+        tier.assert_valid()?;
+        Ok(tier)
+    }
+
+    /// Returns the smallest and largest times of the items, if any.
+    fn extent(&self) -> Option<(f64, f64)> {
+        let tmin = self.items.iter().map(|item| item.tmin).reduce(f64::min)?;
+        let tmax = self.items.iter().map(|item| item.tmax).reduce(f64::max)?;
+        Some((tmin, tmax))
+    }
+
+    /// Sorts and checks the items, fills gaps if requested, and builds the tier with the given
+    /// bounds.
+    fn build_within(mut self, tmin: f64, tmax: f64) -> Result<Tier> {
+        self.items
+            .sort_by(|a, b| a.tmin.total_cmp(&b.tmin).then(a.tmax.total_cmp(&b.tmax)));
+        for (index, item) in self.items.iter().enumerate() {
+            if self.interval_tier && item.tmax - item.tmin <= TIME_EPSILON {
+                return Err(data_error(&format!(
+                    "Interval [{}, {}] \"{}\" has zero width in tier {}",
+                    item.tmin, item.tmax, item.label, self.name
+                )));
+            }
+            if tmin - item.tmin > TIME_EPSILON || item.tmax - tmax > TIME_EPSILON {
+                return Err(data_error(&format!(
+                    "Item [{}, {}] \"{}\" lies outside [{}, {}] in tier {}",
+                    item.tmin, item.tmax, item.label, tmin, tmax, self.name
+                )));
+            }
+            if let Some(previous) = index.checked_sub(1).map(|i| &self.items[i]) {
+                let overlap = if self.interval_tier {
+                    previous.tmax - item.tmin > TIME_EPSILON
+                } else {
+                    item.tmin - previous.tmin <= TIME_EPSILON
+                };
+                if overlap {
+                    return Err(data_error(&format!(
+                        "Items \"{}\" and \"{}\" overlap at {} in tier {}",
+                        previous.label, item.label, item.tmin, self.name
+                    )));
+                }
+            }
+        }
+        let mut tier = Tier {
+            name: self.name,
+            size: self.items.len(),
+            items: self.items,
+            interval_tier: self.interval_tier,
+            tmin,
+            tmax,
+            unknown_fields: Vec::new(),
+        };
+        if let (Some(label), true) = (&self.gap_label, tier.interval_tier) {
+            tier.fill_gaps(label)?;
+        }
+        Ok(tier)
+    }
+}
+
+/// Builds an interval tier from `(tmin, tmax, label)` spans that may overlap slightly, as
+/// timestamps of speech recognizers do.
+///
+/// Each span starts no earlier than the end of the previous one; spans left empty are dropped
+/// and gaps are filled with empty intervals.
+pub(crate) fn clipped_tier(name: &str, mut spans: Vec<(f64, f64, String)>) -> TierBuilder {
+    spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut tier = TierBuilder::intervals(name).fill_gaps("");
+    let mut previous_end = f64::NEG_INFINITY;
+    for (tmin, tmax, label) in spans {
+        let tmin = tmin.max(previous_end);
+        if tmax - tmin > TIME_EPSILON {
+            tier = tier.interval(tmin, tmax, &label);
+            previous_end = tmax;
+        }
+    }
+    tier
+}
+
+impl TextGridBuilder {
+    /// Creates an empty builder; see [`TextGrid::builder`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the TextGrid, empty by default.
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Sets the start time of the TextGrid, 0 by default.
+    pub fn tmin(mut self, tmin: f64) -> Self {
+        self.tmin = Some(tmin);
+        self
+    }
+
+    /// Sets the end time of the TextGrid, by default the end of its last item.
+    pub fn tmax(mut self, tmax: f64) -> Self {
+        self.tmax = Some(tmax);
+        self
+    }
+
+    /// Adds an interval tier whose items are added by `items`.
+    pub fn interval_tier<F>(self, name: &str, items: F) -> Self
+    where
+        F: FnOnce(TierBuilder) -> TierBuilder,
+    {
+        self.tier(items(TierBuilder::intervals(name)))
+    }
+
+    /// Adds a point tier whose items are added by `items`.
+    pub fn point_tier<F>(self, name: &str, items: F) -> Self
+    where
+        F: FnOnce(TierBuilder) -> TierBuilder,
+    {
+        self.tier(items(TierBuilder::points(name)))
+    }
+
+    /// Adds a tier built separately.
+    pub fn tier(mut self, tier: TierBuilder) -> Self {
+        self.tiers.push(tier);
+        self
+    }
+
+    /// Builds the TextGrid.
+    ///
+    /// Every tier spans the bounds of the TextGrid, and the `size` fields count the tiers and
+    /// items.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tier cannot be built (see [`TierBuilder::build`]) or the TextGrid is
+    /// invalid (see [`TextGrid::assert_valid`]).
+    pub fn build(self) -> Result<TextGrid> {
+        let extents: Vec<(f64, f64)> = self.tiers.iter().filter_map(TierBuilder::extent).collect();
+        let tmin = self
+            .tmin
+            .unwrap_or_else(|| extents.iter().map(|e| e.0).fold(0.0, f64::min));
+        let tmax = self
+            .tmax
+            .unwrap_or_else(|| extents.iter().map(|e| e.1).fold(tmin, f64::max));
+        let tiers = self
+            .tiers
+            .into_iter()
+            .map(|tier| tier.build_within(tmin, tmax))
+            .collect::<Result<Vec<Tier>>>()?;
+        let tg = TextGrid {
+            tmin,
+            tmax,
+            size: tiers.len(),
+            name: self.name,
+            tiers,
+            unknown_fields: Vec::new(),
+            warnings: Vec::new(),
+        };
+        tg.assert_valid()?;
+        Ok(tg)
+    }
+}
+
+impl TextGrid {
+    /// Returns a builder to construct a TextGrid fluently, see [`TextGridBuilder`].
+    pub fn builder() -> TextGridBuilder {
+        TextGridBuilder::new()
+    }
+}
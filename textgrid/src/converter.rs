@@ -1,7 +1,45 @@
-use crate::textgrid::{Item, TextGrid, Tier};
+use crate::textgrid::{Item, TIME_EPSILON, TextGrid, Tier, input_error};
 use crate::utils::{fast_map, fast_move_map};
 use std::io::{Error, ErrorKind, Result};
 
+/// An item as `(tmin, tmax, label)`.
+pub type ItemData = (f64, f64, String);
+
+/// A tier as `(name, is_interval, items)`.
+pub type TierData = (String, bool, Vec<ItemData>);
+
+/// A TextGrid as `(tmin, tmax, tiers)`.
+pub type TextGridData = (f64, f64, Vec<TierData>);
+
+/// Flat per-item vectors as `(tmins, tmaxs, labels, tier_names, is_intervals)`.
+pub type TextGridVectors = (Vec<f64>, Vec<f64>, Vec<String>, Vec<String>, Vec<bool>);
+
+/// Flat per-item vectors like [`TextGridVectors`], with labels and tier names borrowed from the
+/// TextGrid.
+pub type TextGridVectorsRef<'a> = (Vec<f64>, Vec<f64>, Vec<&'a str>, Vec<&'a str>, Vec<bool>);
+
+/// Which time of each interval becomes a point in [`Tier::to_point_tier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointFrom {
+    /// The start of each interval.
+    Starts,
+    /// The end of each interval.
+    Ends,
+    /// The midpoint of each interval.
+    Midpoints,
+}
+
+/// How points become intervals in [`Tier::to_interval_tier`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntervalFrom {
+    /// Each point becomes an interval of the given width centered on it. Intervals are
+    /// truncated at the tier bounds and halfway between neighboring points, so they never overlap.
+    Window(f64),
+    /// Consecutive points are paired up, each pair spanning one interval labeled with the label
+    /// of its first point. A trailing unpaired point is dropped.
+    PairUp,
+}
+
 /// Finds the extreme value (minimum or maximum) in a collection of items.
 ///
 /// # Arguments
@@ -15,25 +53,18 @@ use std::io::{Error, ErrorKind, Result};
 /// Returns `Some(f64)` with the extreme value if the collection is not empty,
 /// otherwise returns `None`.
 #[inline]
-fn get_extreme<T, K>(items: &Vec<T>, key: K, find_max: bool) -> Option<f64>
+pub(crate) fn get_extreme<T, K>(items: &[T], key: K, find_max: bool) -> Option<f64>
 where
     K: Fn(&T) -> f64,
 {
     if items.is_empty() {
         return None;
     }
-    let ext: f64;
-    if find_max {
-        ext = items
-            .iter()
-            .map(|item| key(item))
-            .fold(f64::MIN, |a, b| a.max(b));
+    let ext = if find_max {
+        items.iter().map(key).fold(f64::MIN, |a, b| a.max(b))
     } else {
-        ext = items
-            .iter()
-            .map(|item| key(item))
-            .fold(f64::MAX, |a, b| a.min(b));
-    }
+        items.iter().map(key).fold(f64::MAX, |a, b| a.min(b))
+    };
     Some(ext)
 }
 
@@ -54,7 +85,7 @@ where
 /// Used when constructing tiers or TextGrids where time bounds may be explicitly provided
 /// or should be computed from the data.
 #[inline]
-fn get_optional_extreme<T, F>(default: Option<f64>, items: &Vec<T>, key: F, find_max: bool) -> f64
+fn get_optional_extreme<T, F>(default: Option<f64>, items: &[T], key: F, find_max: bool) -> f64
 where
     F: Fn(&T) -> f64,
 {
@@ -94,6 +125,7 @@ fn make_tier(
         tmax: get_optional_extreme(tmax, &items, |item| item.tmax, true),
         size: items.len(),
         items,
+        unknown_fields: Vec::new(),
     }
 }
 
@@ -131,11 +163,160 @@ fn make_textgrid(
         tmax: get_optional_extreme(tmax, &tiers, |tier| tier.tmax, true),
         size: tiers.len(),
         tiers,
+        unknown_fields: Vec::new(),
+        warnings: Vec::new(),
     };
     tgt.assert_valid()?;
     Ok(tgt)
 }
 
+impl Tier {
+    /// Converts an interval tier to a point tier.
+    ///
+    /// Each interval becomes one point carrying its label. The new tier keeps the name and
+    /// bounds of this tier.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - Which time of each interval becomes a point
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier is a point tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{PointFrom, TextGrid};
+    ///
+    /// let data = vec![(String::from("words"), true, vec![(0.0, 0.5, String::from("hello"))])];
+    /// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// let points = tg.tiers[0].to_point_tier(PointFrom::Midpoints).unwrap();
+    /// assert!(!points.interval_tier);
+    /// assert_eq!(points.items[0].tmin, 0.25);
+    /// ```
+    pub fn to_point_tier(&self, from: PointFrom) -> Result<Tier> {
+        if !self.interval_tier {
+            return Err(input_error(&format!(
+                "Tier {} is already a point tier",
+                self.name
+            )));
+        }
+        let items: Vec<Item> = self
+            .items
+            .iter()
+            .map(|item| {
+                let time = match from {
+                    PointFrom::Starts => item.tmin,
+                    PointFrom::Ends => item.tmax,
+                    PointFrom::Midpoints => (item.tmin + item.tmax) / 2.0,
+                };
+                Item {
+                    tmin: time,
+                    tmax: time,
+                    label: item.label.clone(),
+                }
+            })
+            .collect();
+        Ok(make_tier(
+            items,
+            self.name.clone(),
+            false,
+            Some(self.tmin),
+            Some(self.tmax),
+        ))
+    }
+
+    /// Converts a point tier to an interval tier.
+    ///
+    /// The new tier keeps the name and bounds of this tier.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - How points become intervals
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier is an interval tier, or the window width is not positive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{IntervalFrom, TextGrid};
+    ///
+    /// let data = vec![(
+    ///     String::from("beats"),
+    ///     false,
+    ///     vec![(0.2, 0.2, String::from("a")), (0.3, 0.3, String::from("b"))],
+    /// )];
+    /// let tg = TextGrid::from_data(data, None, Some(0.0), Some(1.0)).unwrap();
+    ///
+    /// let windows = tg.tiers[0].to_interval_tier(IntervalFrom::Window(0.2)).unwrap();
+    /// assert_eq!(windows.items[0].tmin, 0.1);
+    /// assert_eq!(windows.items[0].tmax, 0.25);
+    ///
+    /// let pairs = tg.tiers[0].to_interval_tier(IntervalFrom::PairUp).unwrap();
+    /// assert_eq!(pairs.items.len(), 1);
+    /// assert_eq!(pairs.items[0].label, "a");
+    /// ```
+    pub fn to_interval_tier(&self, from: IntervalFrom) -> Result<Tier> {
+        if self.interval_tier {
+            return Err(input_error(&format!(
+                "Tier {} is already an interval tier",
+                self.name
+            )));
+        }
+        let items: Vec<Item> = match from {
+            IntervalFrom::Window(width) => {
+                if width <= TIME_EPSILON {
+                    return Err(input_error(&format!(
+                        "Window width should be positive, got {}",
+                        width
+                    )));
+                }
+                let times: Vec<f64> = self.items.iter().map(|item| item.tmin).collect();
+                self.items
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, item)| {
+                        let mut tmin = (item.tmin - width / 2.0).max(self.tmin);
+                        let mut tmax = (item.tmin + width / 2.0).min(self.tmax);
+                        if index > 0 {
+                            tmin = tmin.max((times[index - 1] + item.tmin) / 2.0);
+                        }
+                        if index + 1 < times.len() {
+                            tmax = tmax.min((item.tmin + times[index + 1]) / 2.0);
+                        }
+                        (tmax - tmin > TIME_EPSILON).then(|| Item {
+                            tmin,
+                            tmax,
+                            label: item.label.clone(),
+                        })
+                    })
+                    .collect()
+            }
+            IntervalFrom::PairUp => self
+                .items
+                .chunks_exact(2)
+                .filter(|pair| pair[1].tmin - pair[0].tmin > TIME_EPSILON)
+                .map(|pair| Item {
+                    tmin: pair[0].tmin,
+                    tmax: pair[1].tmin,
+                    label: pair[0].label.clone(),
+                })
+                .collect(),
+        };
+        Ok(make_tier(
+            items,
+            self.name.clone(),
+            true,
+            Some(self.tmin),
+            Some(self.tmax),
+        ))
+    }
+}
+
 impl TextGrid {
     /// Converts the TextGrid to a nested data structure.
     ///
@@ -152,29 +333,63 @@ impl TextGrid {
     /// # Examples
     ///
     /// ```no_run
-    /// use textgrid::read_from_file;
+    /// use textgrid::{FileType, read_from_str};
     ///
-    /// let tg = read_from_file("example.TextGrid", false, "auto").unwrap();
+    /// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+    /// let tg = read_from_str(&content, false, FileType::Auto).unwrap();
     /// let (tmin, tmax, tiers) = tg.to_data();
     /// println!("TextGrid spans {:.2} to {:.2} seconds", tmin, tmax);
     /// for (tier_name, is_interval, items) in tiers {
     ///     println!("Tier '{}' has {} items", tier_name, items.len());
     /// }
     /// ```
-    pub fn to_data(&self) -> (f64, f64, Vec<(String, bool, Vec<(f64, f64, String)>)>) {
+    pub fn to_data(&self) -> TextGridData {
         let mut data = Vec::new();
         let map_fun = |item: &Item| (item.tmin, item.tmax, item.label.clone());
         for tier in self.tiers.iter() {
             let tier_data = (
                 tier.name.clone(),
                 tier.interval_tier,
-                fast_map(&tier.items, map_fun, 20),
+                fast_map(&tier.items, map_fun),
             );
             data.push(tier_data);
         }
         (self.tmin, self.tmax, data)
     }
 
+    /// Converts the TextGrid to a nested data structure like [`TextGrid::to_data`], moving the
+    /// tier names and labels out instead of cloning them.
+    ///
+    /// Prefer it when the TextGrid is no longer needed, e.g. right after reading it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.5, "hello"))
+    ///     .build()
+    ///     .unwrap();
+    /// let expected = tg.to_data();
+    /// assert_eq!(tg.into_data(), expected);
+    /// ```
+    pub fn into_data(self) -> TextGridData {
+        let map_fun = |item: Item| (item.tmin, item.tmax, item.label);
+        let data = self
+            .tiers
+            .into_iter()
+            .map(|tier| {
+                (
+                    tier.name,
+                    tier.interval_tier,
+                    fast_move_map(tier.items, map_fun),
+                )
+            })
+            .collect();
+        (self.tmin, self.tmax, data)
+    }
+
     /// Creates a TextGrid from a nested data structure.
     ///
     /// # Arguments
@@ -191,9 +406,13 @@ impl TextGrid {
     ///
     /// Returns a `Result` containing the `TextGrid` if successful, otherwise returns an error.
     ///
+    /// Tiers without items are kept, with the provided bounds or, if not provided, the bounds
+    /// computed from the other tiers. Use [`TextGrid::from_data_with`] to drop them instead.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the TextGrid validation fails.
+    /// Returns an error if the TextGrid validation fails, e.g. if a tier has no items and no
+    /// bounds can be computed for it.
     ///
     /// # Examples
     ///
@@ -209,6 +428,7 @@ impl TextGrid {
     ///             (0.5, 1.0, String::from("world")),
     ///         ],
     ///     ),
+    ///     (String::from("notes"), false, vec![]),
     /// ];
     ///
     /// let tg = TextGrid::from_data(
@@ -218,30 +438,58 @@ impl TextGrid {
     ///     Some(1.0),
     /// ).unwrap();
     ///
-    /// assert_eq!(tg.tiers.len(), 1);
+    /// assert_eq!(tg.tiers.len(), 2);
     /// assert_eq!(tg.tiers[0].items.len(), 2);
+    /// assert_eq!((tg.tiers[1].tmin, tg.tiers[1].tmax), (0.0, 1.0));
     /// ```
     pub fn from_data(
-        data: Vec<(String, bool, Vec<(f64, f64, String)>)>,
+        data: Vec<TierData>,
         name: Option<String>,
         tmin: Option<f64>,
         tmax: Option<f64>,
+    ) -> Result<TextGrid> {
+        TextGrid::from_data_with(data, name, tmin, tmax, false)
+    }
+
+    /// Creates a TextGrid from a nested data structure like [`TextGrid::from_data`], dropping
+    /// the tiers without items if `drop_empty_tiers` is true.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TextGrid validation fails.
+    pub fn from_data_with(
+        data: Vec<TierData>,
+        name: Option<String>,
+        tmin: Option<f64>,
+        tmax: Option<f64>,
+        drop_empty_tiers: bool,
     ) -> Result<TextGrid> {
         let mut tiers = Vec::new();
-        for (tier_name, is_interval, items_data) in data.into_iter() {
-            let map_fun = |item_data: (f64, f64, String)| Item {
+        // Positions and names of the tiers without items, added once the bounds are known
+        let mut empty_tiers = Vec::new();
+        for (index, (tier_name, is_interval, items_data)) in data.into_iter().enumerate() {
+            let map_fun = |item_data: ItemData| Item {
                 tmin: item_data.0,
                 tmax: item_data.1,
                 label: item_data.2,
             };
-            let items = fast_move_map(items_data, map_fun, 20);
+            let items = fast_move_map(items_data, map_fun);
             if items.is_empty() {
+                if !drop_empty_tiers {
+                    empty_tiers.push((index, tier_name, is_interval));
+                }
                 continue;
             }
             let tier = make_tier(items, tier_name, is_interval, tmin, tmax);
             tiers.push(tier);
         }
-        let tgt = make_textgrid(tiers, name, tmin, tmax)?;
+        let tmin = get_optional_extreme(tmin, &tiers, |tier| tier.tmin, false);
+        let tmax = get_optional_extreme(tmax, &tiers, |tier| tier.tmax, true);
+        for (index, tier_name, is_interval) in empty_tiers {
+            let tier = make_tier(Vec::new(), tier_name, is_interval, Some(tmin), Some(tmax));
+            tiers.insert(index.min(tiers.len()), tier);
+        }
+        let tgt = make_textgrid(tiers, name, Some(tmin), Some(tmax))?;
         Ok(tgt)
     }
 
@@ -261,9 +509,10 @@ impl TextGrid {
     /// # Examples
     ///
     /// ```no_run
-    /// use textgrid::read_from_file;
+    /// use textgrid::{FileType, read_from_str};
     ///
-    /// let tg = read_from_file("example.TextGrid", false, "auto").unwrap();
+    /// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+    /// let tg = read_from_str(&content, false, FileType::Auto).unwrap();
     /// let (tmins, tmaxs, labels, tier_names, is_intervals) = tg.to_vectors();
     ///
     /// for i in 0..tmins.len() {
@@ -271,7 +520,7 @@ impl TextGrid {
     ///              tmins[i], tmaxs[i], labels[i], tier_names[i]);
     /// }
     /// ```
-    pub fn to_vectors(&self) -> (Vec<f64>, Vec<f64>, Vec<String>, Vec<String>, Vec<bool>) {
+    pub fn to_vectors(&self) -> TextGridVectors {
         let mut tmins = Vec::new();
         let mut tmaxs = Vec::new();
         let mut labels = Vec::new();
@@ -289,6 +538,43 @@ impl TextGrid {
         (tmins, tmaxs, labels, tier_names, is_intervals)
     }
 
+    /// Converts the TextGrid to flat vectors of data like [`TextGrid::to_vectors`], borrowing
+    /// the labels and tier names instead of cloning them, which avoids one allocation per item
+    /// and per column on large TextGrids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.5, "hello").interval(0.5, 1.0, "world"))
+    ///     .build()
+    ///     .unwrap();
+    /// let (tmins, _, labels, tier_names, _) = tg.to_vectors_ref();
+    /// assert_eq!(tmins, [0.0, 0.5]);
+    /// assert_eq!(labels, ["hello", "world"]);
+    /// assert_eq!(tier_names, ["words", "words"]);
+    /// ```
+    pub fn to_vectors_ref(&self) -> TextGridVectorsRef<'_> {
+        let nitems = self.tiers.iter().map(|tier| tier.items.len()).sum();
+        let mut tmins = Vec::with_capacity(nitems);
+        let mut tmaxs = Vec::with_capacity(nitems);
+        let mut labels = Vec::with_capacity(nitems);
+        let mut tier_names = Vec::with_capacity(nitems);
+        let mut is_intervals = Vec::with_capacity(nitems);
+        for tier in self.tiers.iter() {
+            for item in tier.items.iter() {
+                tmins.push(item.tmin);
+                tmaxs.push(item.tmax);
+                labels.push(item.label.as_str());
+                is_intervals.push(tier.interval_tier);
+                tier_names.push(tier.name.as_str());
+            }
+        }
+        (tmins, tmaxs, labels, tier_names, is_intervals)
+    }
+
     /// Creates a TextGrid from flat vectors of data.
     ///
     /// # Arguments
@@ -336,6 +622,7 @@ impl TextGrid {
     /// assert_eq!(tg.tiers.len(), 1);
     /// assert_eq!(tg.tiers[0].items.len(), 2);
     /// ```
+    #[allow(clippy::too_many_arguments)]
     pub fn from_vectors(
         tmins: Vec<f64>,
         tmaxs: Vec<f64>,
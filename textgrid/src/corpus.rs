@@ -0,0 +1,287 @@
+//! Reading whole corpora of TextGrid files from a directory or a glob pattern.
+
+use crate::file_type::{FileType, IntoFileType};
+use crate::textgrid::{TextGrid, data_error, input_error};
+use crate::utils::fast_map;
+use crate::writer::{CsvOptions, TextGridFormat, WriteOptions};
+use crate::{file_error, read_batch_file};
+use std::collections::BTreeMap;
+use std::io::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// The TextGrids of a corpus, as returned by [`read_corpus`].
+///
+/// Both maps are keyed by the path of each file relative to the corpus directory, or to the
+/// part of a glob pattern before its first wildcard.
+#[derive(Debug, Default)]
+pub struct CorpusReadResult {
+    /// The TextGrids read successfully.
+    pub textgrids: BTreeMap<PathBuf, TextGrid>,
+    /// The errors of the files that could not be read or parsed.
+    pub failures: BTreeMap<PathBuf, Error>,
+}
+
+/// The output format of [`convert_files`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertTarget {
+    /// Long TextGrid format, written to `.TextGrid` files.
+    Long,
+    /// Short TextGrid format, written to `.TextGrid` files.
+    Short,
+    /// CSV table of items, written to `.csv` files.
+    Csv,
+    /// JSON in the `(tmin, tmax, tiers)` layout of [`TextGrid::to_data`], written to `.json`
+    /// files.
+    Json,
+}
+
+impl ConvertTarget {
+    /// Returns the extension of the files written in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConvertTarget::Long | ConvertTarget::Short => "TextGrid",
+            ConvertTarget::Csv => "csv",
+            ConvertTarget::Json => "json",
+        }
+    }
+}
+
+/// Options of [`convert_files`].
+#[derive(Debug, Clone, Default)]
+pub struct ConvertOptions {
+    /// Whether to perform strict validation when reading the files.
+    pub strict: bool,
+    /// A function applied to each TextGrid before it is written, e.g. to repair it. A file whose
+    /// TextGrid it fails on is not written.
+    pub repair: Option<fn(&mut TextGrid) -> Result<()>>,
+    /// Options of the TextGrid formats.
+    pub write_options: WriteOptions,
+    /// Options of the CSV format.
+    pub csv_options: CsvOptions,
+}
+
+/// Returns `true` if a path has a `.TextGrid` extension, in any case.
+fn is_textgrid(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("TextGrid"))
+}
+
+/// Collects the TextGrid files of a directory, and of its subdirectories if `recursive`.
+fn walk(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if recursive {
+                walk(&path, recursive, files)?;
+            }
+        } else if is_textgrid(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the directory part of a glob pattern before its first wildcard.
+fn glob_base(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+        .collect()
+}
+
+/// Finds the files of a corpus and the directory their keys are relative to.
+pub(crate) fn find_files(
+    path_or_pattern: &str,
+    recursive: bool,
+) -> Result<(PathBuf, Vec<PathBuf>)> {
+    let path = Path::new(path_or_pattern);
+    let mut files = Vec::new();
+    let base = if path.is_dir() {
+        walk(path, recursive, &mut files)?;
+        path.to_path_buf()
+    } else {
+        let paths = glob::glob(path_or_pattern)
+            .map_err(|e| input_error(&format!("Invalid pattern {}: {}", path_or_pattern, e)))?;
+        for entry in paths {
+            let entry = entry.map_err(Error::from)?;
+            if entry.is_file() {
+                files.push(entry);
+            }
+        }
+        glob_base(path_or_pattern)
+    };
+    files.sort();
+    Ok((base, files))
+}
+
+/// Reads all TextGrid files of a directory or matching a glob pattern in parallel.
+///
+/// If `path_or_pattern` is a directory, every file with a `.TextGrid` extension (in any case) in
+/// it is read, including in subdirectories if `recursive` is true. Otherwise, it is a glob pattern
+/// such as `corpus/**/*.TextGrid`, and `recursive` is ignored. The format of each file is detected
+/// automatically, and each TextGrid is named after its file.
+///
+/// # Arguments
+///
+/// * `path_or_pattern` - A directory or a glob pattern
+/// * `recursive` - Whether to read the subdirectories of a directory
+/// * `strict` - Whether to perform strict validation on the parsed data
+///
+/// # Returns
+///
+/// Returns the TextGrids read successfully and the errors of the other files, keyed by relative
+/// path.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be listed or the pattern is invalid. Errors of
+/// individual files are reported in [`CorpusReadResult::failures`] instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// use textgrid::read_corpus;
+///
+/// let corpus = read_corpus("corpus", true, false).unwrap();
+/// for (path, tg) in &corpus.textgrids {
+///     println!("{}: {} tiers", path.display(), tg.tiers.len());
+/// }
+/// for (path, error) in &corpus.failures {
+///     eprintln!("Could not read {}: {}", path.display(), error);
+/// }
+/// ```
+pub fn read_corpus(
+    path_or_pattern: &str,
+    recursive: bool,
+    strict: bool,
+) -> Result<CorpusReadResult> {
+    let (base, files) = find_files(path_or_pattern, recursive)?;
+    let map_fun = |path: &PathBuf| match path.to_str() {
+        Some(fname) => {
+            read_batch_file(fname, strict, FileType::Auto).map_err(|e| file_error(fname, e))
+        }
+        None => Err(input_error(&format!(
+            "{}: path is not valid UTF-8",
+            path.display()
+        ))),
+    };
+    let results = fast_map(&files, map_fun);
+
+    let mut corpus = CorpusReadResult::default();
+    for (path, result) in files.into_iter().zip(results) {
+        let key = path
+            .strip_prefix(&base)
+            .map(Path::to_path_buf)
+            .unwrap_or(path);
+        match result {
+            Ok(tg) => {
+                corpus.textgrids.insert(key, tg);
+            }
+            Err(e) => {
+                corpus.failures.insert(key, e);
+            }
+        }
+    }
+    Ok(corpus)
+}
+
+/// Reads, converts and writes one file of [`convert_files`].
+fn convert_file(
+    input: &Path,
+    output: &Path,
+    from: FileType,
+    to: ConvertTarget,
+    options: &ConvertOptions,
+) -> Result<()> {
+    let fname = input
+        .to_str()
+        .ok_or_else(|| input_error("path is not valid UTF-8"))?;
+    let mut tg = read_batch_file(fname, options.strict, from)?;
+    if let Some(repair) = options.repair {
+        repair(&mut tg)?;
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let output = output
+        .to_str()
+        .ok_or_else(|| input_error("output path is not valid UTF-8"))?;
+    match to {
+        ConvertTarget::Long => {
+            tg.save_textgrid(output, TextGridFormat::Long, &options.write_options)
+        }
+        ConvertTarget::Short => {
+            tg.save_textgrid(output, TextGridFormat::Short, &options.write_options)
+        }
+        ConvertTarget::Csv => tg.save_csv(output, &options.csv_options),
+        ConvertTarget::Json => {
+            let json =
+                serde_json::to_string(&tg.to_data()).map_err(|e| data_error(&e.to_string()))?;
+            std::fs::write(output, json)
+        }
+    }
+}
+
+/// Converts all TextGrid files of a directory or matching a glob pattern in parallel, writing
+/// them to `output_dir` in another format.
+///
+/// Files are found as in [`read_corpus`], including subdirectories, and written to the same
+/// relative path under `output_dir`, with the extension of the target format. Subdirectories of
+/// `output_dir` are created as needed.
+///
+/// # Arguments
+///
+/// * `inputs` - A directory or a glob pattern
+/// * `output_dir` - The directory to write the converted files to
+/// * `from` - The format of the input files, see [`FileType`]
+/// * `to` - The output format
+/// * `options` - Validation, repair and write options, see [`ConvertOptions`]
+///
+/// # Returns
+///
+/// Returns, for each input file keyed by relative path, the path of the written file or the error
+/// that prevented it from being converted.
+///
+/// # Errors
+///
+/// Returns an error if `from` names no known file type, or if the input directory cannot be
+/// listed or the pattern is invalid. Errors of individual files are reported in the returned
+/// map instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// use textgrid::{ConvertOptions, ConvertTarget, FileType, convert_files};
+///
+/// let results = convert_files("corpus", "corpus_short", FileType::Auto, ConvertTarget::Short, &ConvertOptions::default())
+///     .unwrap();
+/// for (path, result) in &results {
+///     if let Err(e) = result {
+///         eprintln!("Could not convert {}: {}", path.display(), e);
+///     }
+/// }
+/// ```
+pub fn convert_files(
+    inputs: &str,
+    output_dir: &str,
+    from: impl IntoFileType,
+    to: ConvertTarget,
+    options: &ConvertOptions,
+) -> Result<BTreeMap<PathBuf, Result<PathBuf>>> {
+    let from = from.into_file_type()?;
+    let (base, files) = find_files(inputs, true)?;
+    let map_fun = |input: &PathBuf| {
+        let relative = input.strip_prefix(&base).unwrap_or(input);
+        let output = Path::new(output_dir)
+            .join(relative)
+            .with_extension(to.extension());
+        match convert_file(input, &output, from, to, options) {
+            Ok(()) => (relative.to_path_buf(), Ok(output)),
+            Err(e) => {
+                let e = file_error(&input.display().to_string(), e);
+                (relative.to_path_buf(), Err(e))
+            }
+        }
+    };
+    Ok(fast_map(&files, map_fun).into_iter().collect())
+}
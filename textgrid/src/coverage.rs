@@ -0,0 +1,214 @@
+//! Coverage checks of interval tiers: gaps, overlaps, and overlaps across tiers.
+
+use crate::edit::require_interval_tier;
+use crate::textgrid::{Item, TIME_EPSILON, TextGrid, Tier, input_error};
+use std::io::Result;
+
+/// A time span `[tmin, tmax]`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+    /// Start of the span.
+    pub tmin: f64,
+    /// End of the span.
+    pub tmax: f64,
+}
+
+impl Span {
+    /// Returns the duration of the span.
+    pub fn duration(&self) -> f64 {
+        self.tmax - self.tmin
+    }
+}
+
+/// Gaps and overlaps found in one interval tier.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierCoverage {
+    /// Name of the tier.
+    pub tier_name: String,
+    /// Spans of the tier not covered by any interval.
+    pub gaps: Vec<Span>,
+    /// Spans covered by two consecutive intervals.
+    pub overlaps: Vec<Span>,
+}
+
+/// Overlaps between the labelled intervals of two tiers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierPairOverlap {
+    /// Name of the first tier.
+    pub first: String,
+    /// Name of the second tier.
+    pub second: String,
+    /// Spans during which both tiers have a non-empty label.
+    pub overlaps: Vec<Span>,
+}
+
+/// Coverage of the interval tiers of a TextGrid, as returned by [`TextGrid::coverage_report`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CoverageReport {
+    /// Gaps and overlaps of every interval tier, in tier order.
+    pub tiers: Vec<TierCoverage>,
+    /// Overlaps of the requested tier pairs, in request order.
+    pub pair_overlaps: Vec<TierPairOverlap>,
+}
+
+impl TierCoverage {
+    /// Returns the total duration of the gaps.
+    pub fn gap_duration(&self) -> f64 {
+        self.gaps.iter().map(Span::duration).sum()
+    }
+
+    /// Returns the total duration of the overlaps.
+    pub fn overlap_duration(&self) -> f64 {
+        self.overlaps.iter().map(Span::duration).sum()
+    }
+}
+
+impl TierPairOverlap {
+    /// Returns the total duration of the overlaps.
+    pub fn overlap_duration(&self) -> f64 {
+        self.overlaps.iter().map(Span::duration).sum()
+    }
+}
+
+impl CoverageReport {
+    /// Returns `true` if no tier has gaps or overlaps and no tier pair overlaps.
+    pub fn is_clean(&self) -> bool {
+        self.tiers
+            .iter()
+            .all(|tier| tier.gaps.is_empty() && tier.overlaps.is_empty())
+            && self
+                .pair_overlaps
+                .iter()
+                .all(|pair| pair.overlaps.is_empty())
+    }
+}
+
+/// Collects the gaps and overlaps of the items of an interval tier within `[tmin, tmax]`.
+fn tier_coverage(items: &[Item], tmin: f64, tmax: f64) -> (Vec<Span>, Vec<Span>) {
+    let (mut gaps, mut overlaps) = (Vec::new(), Vec::new());
+    let mut covered_until = tmin;
+    for item in items {
+        if item.tmin - covered_until > TIME_EPSILON {
+            gaps.push(Span {
+                tmin: covered_until,
+                tmax: item.tmin,
+            });
+        } else if covered_until - item.tmin > TIME_EPSILON {
+            overlaps.push(Span {
+                tmin: item.tmin,
+                tmax: covered_until.min(item.tmax),
+            });
+        }
+        covered_until = covered_until.max(item.tmax);
+    }
+    if tmax - covered_until > TIME_EPSILON {
+        gaps.push(Span {
+            tmin: covered_until,
+            tmax,
+        });
+    }
+    (gaps, overlaps)
+}
+
+/// Collects the spans during which both lists of intervals have a non-empty label.
+fn labelled_overlaps(first: &[Item], second: &[Item]) -> Vec<Span> {
+    let labelled = |items: &[Item]| -> Vec<Span> {
+        items
+            .iter()
+            .filter(|item| !item.label.is_empty())
+            .map(|item| Span {
+                tmin: item.tmin,
+                tmax: item.tmax,
+            })
+            .collect()
+    };
+    let (first, second) = (labelled(first), labelled(second));
+    let mut overlaps = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < first.len() && j < second.len() {
+        let tmin = first[i].tmin.max(second[j].tmin);
+        let tmax = first[i].tmax.min(second[j].tmax);
+        if tmax - tmin > TIME_EPSILON {
+            overlaps.push(Span { tmin, tmax });
+        }
+        if first[i].tmax < second[j].tmax {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    overlaps
+}
+
+impl TextGrid {
+    /// Reports the gaps and overlaps of every interval tier, and optionally the overlaps between
+    /// pairs of tiers.
+    ///
+    /// Gaps are spans of a tier, between its bounds, that no interval covers. Overlaps are spans
+    /// covered by an interval and a previous one. For tier pairs, overlaps are the spans during
+    /// which both tiers have an interval with a non-empty label, e.g. overlapping speech on two
+    /// speaker tiers. Point tiers are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `tier_pairs` - Names of interval tiers to check for overlaps against each other
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tier of `tier_pairs` does not exist or is a point tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![
+    ///     (String::from("A"), true, vec![(0.0, 0.6, String::from("hi")), (0.7, 1.0, String::from(""))]),
+    ///     (String::from("B"), true, vec![(0.0, 0.5, String::from("")), (0.5, 1.0, String::from("hey"))]),
+    /// ];
+    /// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// let report = tg.coverage_report(&[("A", "B")]).unwrap();
+    /// assert_eq!(report.tiers[0].gaps.len(), 1);
+    /// assert!((report.tiers[0].gap_duration() - 0.1).abs() < 1e-9);
+    /// assert!((report.pair_overlaps[0].overlap_duration() - 0.1).abs() < 1e-9);
+    /// assert!(!report.is_clean());
+    /// ```
+    pub fn coverage_report(&self, tier_pairs: &[(&str, &str)]) -> Result<CoverageReport> {
+        let tiers = self
+            .tiers
+            .iter()
+            .filter(|tier| tier.interval_tier)
+            .map(|tier| {
+                let (gaps, overlaps) = tier_coverage(&tier.items, tier.tmin, tier.tmax);
+                TierCoverage {
+                    tier_name: tier.name.clone(),
+                    gaps,
+                    overlaps,
+                }
+            })
+            .collect();
+        let find = |name: &str| -> Result<&Tier> {
+            let tier = self
+                .get_tier(name)
+                .ok_or_else(|| input_error(&format!("No tier \"{}\" in TextGrid", name)))?;
+            require_interval_tier(tier, "Coverage report")?;
+            Ok(tier)
+        };
+        let pair_overlaps = tier_pairs
+            .iter()
+            .map(|&(first, second)| {
+                let (first, second) = (find(first)?, find(second)?);
+                Ok(TierPairOverlap {
+                    first: first.name.clone(),
+                    second: second.name.clone(),
+                    overlaps: labelled_overlaps(&first.items, &second.items),
+                })
+            })
+            .collect::<Result<Vec<TierPairOverlap>>>()?;
+        Ok(CoverageReport {
+            tiers,
+            pair_overlaps,
+        })
+    }
+}
@@ -0,0 +1,456 @@
+//! Differences between two revisions of a TextGrid.
+
+use crate::textgrid::{Item, TextGrid, Tier};
+use std::fmt;
+
+/// A change to a single item between two revisions of a tier.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ItemChange {
+    /// An item only present in the new revision.
+    Added {
+        /// Index of the item in the new tier.
+        index: usize,
+        /// Start time of the item.
+        tmin: f64,
+        /// End time of the item.
+        tmax: f64,
+        /// Label of the item.
+        label: String,
+    },
+    /// An item only present in the old revision.
+    Removed {
+        /// Index of the item in the old tier.
+        index: usize,
+        /// Start time of the item.
+        tmin: f64,
+        /// End time of the item.
+        tmax: f64,
+        /// Label of the item.
+        label: String,
+    },
+    /// An item whose boundaries moved by more than the tolerance.
+    Moved {
+        /// Index of the item in the old tier.
+        old_index: usize,
+        /// Index of the item in the new tier.
+        new_index: usize,
+        /// `(tmin, tmax)` in the old tier.
+        old_span: (f64, f64),
+        /// `(tmin, tmax)` in the new tier.
+        new_span: (f64, f64),
+    },
+    /// An item whose label changed.
+    Relabeled {
+        /// Index of the item in the old tier.
+        old_index: usize,
+        /// Index of the item in the new tier.
+        new_index: usize,
+        /// Label in the old tier.
+        old_label: String,
+        /// Label in the new tier.
+        new_label: String,
+    },
+}
+
+/// A change to the time range of a TextGrid or tier between two revisions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundsChange {
+    /// `(tmin, tmax)` in the old revision.
+    pub old: (f64, f64),
+    /// `(tmin, tmax)` in the new revision.
+    pub new: (f64, f64),
+}
+
+/// The changes within one tier present in both revisions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierDiff {
+    /// Name of the tier in the new revision.
+    pub tier_name: String,
+    /// Change to the tier's bounds, if they moved by more than the tolerance.
+    pub bounds: Option<BoundsChange>,
+    /// Item changes, in time order.
+    pub changes: Vec<ItemChange>,
+}
+
+/// Differences between two revisions of a TextGrid, as returned by [`TextGrid::diff`].
+///
+/// The [`Display`](fmt::Display) implementation renders a human-readable report.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextGridDiff {
+    /// Change to the TextGrid's bounds, if they moved by more than the tolerance.
+    pub bounds: Option<BoundsChange>,
+    /// Names of tiers only present in the new revision.
+    pub added_tiers: Vec<String>,
+    /// Names of tiers only present in the old revision.
+    pub removed_tiers: Vec<String>,
+    /// `(old_name, new_name)` of tiers that were renamed.
+    pub renamed_tiers: Vec<(String, String)>,
+    /// Item changes of tiers present in both revisions; tiers without changes are omitted.
+    pub tier_diffs: Vec<TierDiff>,
+}
+
+impl TextGridDiff {
+    /// Returns `true` if both revisions are equivalent within the tolerance.
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_none()
+            && self.added_tiers.is_empty()
+            && self.removed_tiers.is_empty()
+            && self.renamed_tiers.is_empty()
+            && self.tier_diffs.is_empty()
+    }
+}
+
+/// Compares the items of two revisions of a tier.
+///
+/// Items are swept in time order: items with the same span (within `tolerance`) are compared by
+/// label, overlapping items with different spans are reported as moved, and items without
+/// a counterpart are reported as added or removed.
+fn diff_items(old: &[Item], new: &[Item], tolerance: f64) -> Vec<ItemChange> {
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        let (a, b) = (&old[i], &new[j]);
+        let same_span =
+            (a.tmin - b.tmin).abs() <= tolerance && (a.tmax - b.tmax).abs() <= tolerance;
+        if !same_span && a.tmax <= b.tmin + tolerance {
+            changes.push(removed(i, a));
+            i += 1;
+        } else if !same_span && b.tmax <= a.tmin + tolerance {
+            changes.push(added(j, b));
+            j += 1;
+        } else {
+            if !same_span {
+                changes.push(ItemChange::Moved {
+                    old_index: i,
+                    new_index: j,
+                    old_span: (a.tmin, a.tmax),
+                    new_span: (b.tmin, b.tmax),
+                });
+            }
+            if a.label != b.label {
+                changes.push(ItemChange::Relabeled {
+                    old_index: i,
+                    new_index: j,
+                    old_label: a.label.clone(),
+                    new_label: b.label.clone(),
+                });
+            }
+            i += 1;
+            j += 1;
+        }
+    }
+    changes.extend(old[i..].iter().enumerate().map(|(k, a)| removed(i + k, a)));
+    changes.extend(new[j..].iter().enumerate().map(|(k, b)| added(j + k, b)));
+    changes
+}
+
+#[inline]
+fn added(index: usize, item: &Item) -> ItemChange {
+    ItemChange::Added {
+        index,
+        tmin: item.tmin,
+        tmax: item.tmax,
+        label: item.label.clone(),
+    }
+}
+
+#[inline]
+fn removed(index: usize, item: &Item) -> ItemChange {
+    ItemChange::Removed {
+        index,
+        tmin: item.tmin,
+        tmax: item.tmax,
+        label: item.label.clone(),
+    }
+}
+
+/// Compares two `(tmin, tmax)` ranges, returning the change if either end moved by more than
+/// `tolerance`.
+fn diff_bounds(old: (f64, f64), new: (f64, f64), tolerance: f64) -> Option<BoundsChange> {
+    ((old.0 - new.0).abs() > tolerance || (old.1 - new.1).abs() > tolerance)
+        .then_some(BoundsChange { old, new })
+}
+
+/// Compares two revisions of a tier, returning `None` if they are equivalent.
+fn diff_tier(old: &Tier, new: &Tier, tolerance: f64) -> Option<TierDiff> {
+    let bounds = diff_bounds((old.tmin, old.tmax), (new.tmin, new.tmax), tolerance);
+    let changes = diff_items(&old.items, &new.items, tolerance);
+    (bounds.is_some() || !changes.is_empty()).then(|| TierDiff {
+        tier_name: new.name.clone(),
+        bounds,
+        changes,
+    })
+}
+
+/// Returns `true` if two tiers can be compared item by item.
+#[inline]
+fn comparable(old: &Tier, new: &Tier) -> bool {
+    old.interval_tier == new.interval_tier
+}
+
+impl TextGrid {
+    /// Compares this TextGrid (the old revision) with another one (the new revision).
+    ///
+    /// The bounds of the TextGrid and of matched tiers are compared with the same tolerance as
+    /// item times. Tiers are matched by name. An unmatched tier is considered renamed if a new tier of the
+    /// same kind has the same items, or failing that sits at the same position; any other
+    /// unmatched tier is reported as added or removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The new revision
+    /// * `tolerance` - Boundaries moving by at most this many seconds are considered unchanged
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let words = |tmax: f64, label: &str| {
+    ///     vec![(
+    ///         String::from("words"),
+    ///         true,
+    ///         vec![(0.0, tmax, String::from("hello")), (tmax, 1.0, String::from(label))],
+    ///     )]
+    /// };
+    /// let old = TextGrid::from_data(words(0.5, "world"), None, None, None).unwrap();
+    /// let new = TextGrid::from_data(words(0.6, "word"), None, None, None).unwrap();
+    ///
+    /// let diff = old.diff(&new, 0.01);
+    /// assert_eq!(diff.tier_diffs[0].changes.len(), 3);
+    /// println!("{}", diff);
+    /// assert!(old.diff(&new, 0.2).tier_diffs[0].changes.len() == 1);
+    /// ```
+    pub fn diff(&self, other: &TextGrid, tolerance: f64) -> TextGridDiff {
+        let mut diff = TextGridDiff {
+            bounds: diff_bounds((self.tmin, self.tmax), (other.tmin, other.tmax), tolerance),
+            ..TextGridDiff::default()
+        };
+        let mut matched_new = vec![false; other.tiers.len()];
+        let mut unmatched_old = Vec::new();
+        for (old_index, old_tier) in self.tiers.iter().enumerate() {
+            match other.tier_index(&old_tier.name) {
+                Some(new_index) if comparable(old_tier, &other.tiers[new_index]) => {
+                    matched_new[new_index] = true;
+                    diff.tier_diffs
+                        .extend(diff_tier(old_tier, &other.tiers[new_index], tolerance));
+                }
+                _ => unmatched_old.push(old_index),
+            }
+        }
+        for old_index in unmatched_old {
+            let old_tier = &self.tiers[old_index];
+            let is_candidate = |new_index: usize| {
+                let new_tier = &other.tiers[new_index];
+                !matched_new[new_index]
+                    && comparable(old_tier, new_tier)
+                    && self.tier_index(&new_tier.name).is_none()
+            };
+            let same_items = (0..other.tiers.len()).find(|&new_index| {
+                is_candidate(new_index)
+                    && diff_items(&old_tier.items, &other.tiers[new_index].items, tolerance)
+                        .is_empty()
+            });
+            let same_position =
+                (old_index < other.tiers.len() && is_candidate(old_index)).then_some(old_index);
+            match same_items.or(same_position) {
+                Some(new_index) => {
+                    matched_new[new_index] = true;
+                    let new_tier = &other.tiers[new_index];
+                    diff.renamed_tiers
+                        .push((old_tier.name.clone(), new_tier.name.clone()));
+                    diff.tier_diffs
+                        .extend(diff_tier(old_tier, new_tier, tolerance));
+                }
+                None => diff.removed_tiers.push(old_tier.name.clone()),
+            }
+        }
+        diff.added_tiers = other
+            .tiers
+            .iter()
+            .zip(matched_new)
+            .filter(|(_, matched)| !matched)
+            .map(|(tier, _)| tier.name.clone())
+            .collect();
+        diff
+    }
+
+    /// Checks that two TextGrids have the same structure and the same times up to a tolerance,
+    /// e.g. to compare the output of an annotation pipeline with a reference despite float
+    /// noise.
+    ///
+    /// Tiers and items are compared by position, so unlike [`TextGrid::diff`] nothing is
+    /// matched or realigned. The names of the TextGrids, which usually come from their files,
+    /// are not compared.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The TextGrid to compare with
+    /// * `time_tol` - The largest difference, in seconds, allowed between two times
+    /// * `compare_labels` - Whether labels must be equal too
+    ///
+    /// # Returns
+    ///
+    /// Returns `None` if the TextGrids match, or a description of the first difference found,
+    /// so that `assert_eq!(actual.approx_eq(&expected, tol, true), None)` shows it on failure.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let words = |tmax: f64, label: &str| {
+    ///     vec![(
+    ///         String::from("words"),
+    ///         true,
+    ///         vec![(0.0, tmax, String::from("hello")), (tmax, 1.0, String::from(label))],
+    ///     )]
+    /// };
+    /// let expected = TextGrid::from_data(words(0.5, "world"), None, None, None).unwrap();
+    /// let actual = TextGrid::from_data(words(0.5 + 1e-9, "word"), None, None, None).unwrap();
+    ///
+    /// assert_eq!(actual.approx_eq(&expected, 1e-6, false), None);
+    /// assert_eq!(
+    ///     actual.approx_eq(&expected, 1e-6, true).unwrap(),
+    ///     "tier 0 (words), item 1: label \"word\" differs from \"world\""
+    /// );
+    /// ```
+    pub fn approx_eq(
+        &self,
+        other: &TextGrid,
+        time_tol: f64,
+        compare_labels: bool,
+    ) -> Option<String> {
+        let differs = |a: f64, b: f64| (a - b).abs() > time_tol;
+        if differs(self.tmin, other.tmin) || differs(self.tmax, other.tmax) {
+            return Some(format!(
+                "bounds [{}, {}] differ from [{}, {}]",
+                self.tmin, self.tmax, other.tmin, other.tmax
+            ));
+        }
+        if self.tiers.len() != other.tiers.len() {
+            return Some(format!(
+                "{} tiers differ from {} tiers",
+                self.tiers.len(),
+                other.tiers.len()
+            ));
+        }
+        for (tier_index, (a, b)) in self.tiers.iter().zip(&other.tiers).enumerate() {
+            let tier = format!("tier {} ({})", tier_index, a.name);
+            if a.name != b.name {
+                return Some(format!("{}: name differs from \"{}\"", tier, b.name));
+            }
+            if a.interval_tier != b.interval_tier {
+                return Some(format!("{}: kind differs", tier));
+            }
+            if differs(a.tmin, b.tmin) || differs(a.tmax, b.tmax) {
+                return Some(format!(
+                    "{}: bounds [{}, {}] differ from [{}, {}]",
+                    tier, a.tmin, a.tmax, b.tmin, b.tmax
+                ));
+            }
+            if a.items.len() != b.items.len() {
+                return Some(format!(
+                    "{}: {} items differ from {} items",
+                    tier,
+                    a.items.len(),
+                    b.items.len()
+                ));
+            }
+            for (item_index, (x, y)) in a.items.iter().zip(&b.items).enumerate() {
+                if differs(x.tmin, y.tmin) || differs(x.tmax, y.tmax) {
+                    return Some(format!(
+                        "{}, item {}: [{}, {}] differs from [{}, {}]",
+                        tier, item_index, x.tmin, x.tmax, y.tmin, y.tmax
+                    ));
+                }
+                if compare_labels && x.label != y.label {
+                    return Some(format!(
+                        "{}, item {}: label \"{}\" differs from \"{}\"",
+                        tier, item_index, x.label, y.label
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl fmt::Display for ItemChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemChange::Added {
+                index,
+                tmin,
+                tmax,
+                label,
+            } => write!(f, "+ item {}: [{}, {}] {:?}", index, tmin, tmax, label),
+            ItemChange::Removed {
+                index,
+                tmin,
+                tmax,
+                label,
+            } => write!(f, "- item {}: [{}, {}] {:?}", index, tmin, tmax, label),
+            ItemChange::Moved {
+                old_index,
+                new_index,
+                old_span,
+                new_span,
+            } => write!(
+                f,
+                "~ item {} -> {}: [{}, {}] -> [{}, {}]",
+                old_index, new_index, old_span.0, old_span.1, new_span.0, new_span.1
+            ),
+            ItemChange::Relabeled {
+                old_index,
+                new_index,
+                old_label,
+                new_label,
+            } => write!(
+                f,
+                "~ item {} -> {}: {:?} -> {:?}",
+                old_index, new_index, old_label, new_label
+            ),
+        }
+    }
+}
+
+impl fmt::Display for BoundsChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "~ bounds [{}, {}] -> [{}, {}]",
+            self.old.0, self.old.1, self.new.0, self.new.1
+        )
+    }
+}
+
+impl fmt::Display for TextGridDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        if let Some(bounds) = &self.bounds {
+            writeln!(f, "{}", bounds)?;
+        }
+        for name in &self.added_tiers {
+            writeln!(f, "+ tier {:?}", name)?;
+        }
+        for name in &self.removed_tiers {
+            writeln!(f, "- tier {:?}", name)?;
+        }
+        for (old_name, new_name) in &self.renamed_tiers {
+            writeln!(f, "~ tier {:?} -> {:?}", old_name, new_name)?;
+        }
+        for tier_diff in &self.tier_diffs {
+            writeln!(f, "tier {:?}:", tier_diff.tier_name)?;
+            if let Some(bounds) = &tier_diff.bounds {
+                writeln!(f, "    {}", bounds)?;
+            }
+            for change in &tier_diff.changes {
+                writeln!(f, "    {}", change)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,640 @@
+//! Editing operations on TextGrids and tiers.
+
+use crate::structure::TierKey;
+use crate::textgrid::{Item, TIME_EPSILON, TextGrid, Tier, input_error};
+use crate::utils::fast_map;
+use regex::Regex;
+use std::io::Result;
+
+/// What to do with existing intervals that overlap an inserted interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// Refuse the insertion and return an error.
+    Error,
+    /// Truncate overlapping intervals so that only their parts outside the new interval remain.
+    Split,
+    /// Remove every interval that overlaps the new interval.
+    Overwrite,
+}
+
+/// How to handle the gap left by a removed interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapPolicy {
+    /// Leave the gap empty.
+    Leave,
+    /// Extend the previous interval over the gap.
+    MergeLeft,
+    /// Extend the next interval over the gap.
+    MergeRight,
+    /// Fill the gap with an interval with an empty label.
+    FillEmpty,
+}
+
+/// Which label the interval resulting from a boundary removal receives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeLabelPolicy {
+    /// Keep the label of the interval before the boundary.
+    Left,
+    /// Keep the label of the interval after the boundary.
+    Right,
+    /// Concatenate both labels, as Praat does.
+    Concat,
+}
+
+/// Ensures that an editing operation targets an interval tier.
+#[inline]
+pub(crate) fn require_interval_tier(tier: &Tier, operation: &str) -> Result<()> {
+    if tier.interval_tier {
+        Ok(())
+    } else {
+        Err(input_error(&format!(
+            "{} requires an interval tier, but {} is a point tier",
+            operation, tier.name
+        )))
+    }
+}
+
+impl Tier {
+    /// Replaces every label in the tier with the result of a function.
+    ///
+    /// Labels are mapped in parallel for large tiers.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - A function receiving the current label and returning the new one
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(
+    ///     String::from("words"),
+    ///     true,
+    ///     vec![(0.0, 0.5, String::from("Hello")), (0.5, 1.0, String::from("World"))],
+    /// )];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// tg.tiers[0].map_labels(|label| label.to_lowercase());
+    /// assert_eq!(tg.tiers[0].items[1].label, "world");
+    /// ```
+    pub fn map_labels<F>(&mut self, func: F)
+    where
+        F: Fn(&str) -> String + Sync + Send,
+    {
+        let labels = fast_map(&self.items, |item| func(&item.label));
+        for (item, label) in self.items.iter_mut().zip(labels) {
+            item.label = label;
+        }
+    }
+
+    /// Splits the interval containing a time point into two intervals.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the new boundary; it must lie strictly inside an interval
+    /// * `left_label` - The label of the interval before the new boundary
+    /// * `right_label` - The label of the interval after the new boundary
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The tier is a point tier
+    /// * No interval strictly contains `time` (including when `time` is already a boundary)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(String::from("words"), true, vec![(0.0, 1.0, String::from("helloworld"))])];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// tg.tiers[0].split_at(0.5, "hello", "world").unwrap();
+    /// assert_eq!(tg.tiers[0].items.len(), 2);
+    /// assert_eq!(tg.tiers[0].items[1].tmin, 0.5);
+    /// assert_eq!(tg.tiers[0].items[1].label, "world");
+    /// assert!(tg.tiers[0].split_at(0.5, "a", "b").is_err());
+    /// ```
+    pub fn split_at(&mut self, time: f64, left_label: &str, right_label: &str) -> Result<()> {
+        require_interval_tier(self, "Splitting")?;
+        let index = self
+            .items
+            .iter()
+            .position(|item| item.tmin + TIME_EPSILON < time && time < item.tmax - TIME_EPSILON)
+            .ok_or_else(|| {
+                input_error(&format!(
+                    "No interval strictly contains time {} in tier {}",
+                    time, self.name
+                ))
+            })?;
+        let right = Item {
+            tmin: time,
+            tmax: self.items[index].tmax,
+            label: right_label.to_string(),
+        };
+        let left = &mut self.items[index];
+        left.tmax = time;
+        left.label = left_label.to_string();
+        self.items.insert(index + 1, right);
+        self.size = self.items.len();
+        Ok(())
+    }
+
+    /// Merges runs of adjacent intervals into single intervals.
+    ///
+    /// Two consecutive intervals are adjacent when the first ends where the second starts.
+    /// A merged interval spans the whole run and keeps the label of its first interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `should_merge` - A function receiving the current (possibly already merged) interval
+    ///   and the next one, returning `true` if they should be merged
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier is a point tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(
+    ///     String::from("phones"),
+    ///     true,
+    ///     vec![
+    ///         (0.0, 0.1, String::from("")),
+    ///         (0.1, 0.2, String::from("sil")),
+    ///         (0.2, 0.5, String::from("a")),
+    ///     ],
+    /// )];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// // Collapse silences, whether they are labeled or not
+    /// let is_silence = |label: &str| label.is_empty() || label == "sil";
+    /// tg.tiers[0]
+    ///     .merge_adjacent(|a, b| is_silence(&a.label) && is_silence(&b.label))
+    ///     .unwrap();
+    /// assert_eq!(tg.tiers[0].items.len(), 2);
+    /// assert_eq!(tg.tiers[0].items[0].tmax, 0.2);
+    /// ```
+    pub fn merge_adjacent<F>(&mut self, should_merge: F) -> Result<()>
+    where
+        F: Fn(&Item, &Item) -> bool,
+    {
+        require_interval_tier(self, "Merging")?;
+        let mut merged: Vec<Item> = Vec::with_capacity(self.items.len());
+        for item in self.items.drain(..) {
+            match merged.last_mut() {
+                Some(last)
+                    if (item.tmin - last.tmax).abs() <= TIME_EPSILON
+                        && should_merge(last, &item) =>
+                {
+                    last.tmax = item.tmax;
+                }
+                _ => merged.push(item),
+            }
+        }
+        self.items = merged;
+        self.size = self.items.len();
+        Ok(())
+    }
+
+    /// Merges runs of adjacent intervals sharing the same label.
+    ///
+    /// Shorthand for [`Tier::merge_adjacent`] with a label equality test.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier is a point tier.
+    pub fn merge_equal_labels(&mut self) -> Result<()> {
+        self.merge_adjacent(|a, b| a.label == b.label)
+    }
+
+    /// Inserts an interval, keeping the tier sorted and free of overlaps.
+    ///
+    /// The tier bounds are extended if the new interval lies outside them.
+    ///
+    /// # Arguments
+    ///
+    /// * `tmin` - Start time of the new interval
+    /// * `tmax` - End time of the new interval
+    /// * `label` - Label of the new interval
+    /// * `policy` - What to do with existing intervals overlapping the new one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The tier is a point tier
+    /// * `tmin` is not less than `tmax`
+    /// * The new interval overlaps an existing one and `policy` is [`OverlapPolicy::Error`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{OverlapPolicy, TextGrid};
+    ///
+    /// let data = vec![(String::from("words"), true, vec![(0.0, 1.0, String::from(""))])];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// let tier = &mut tg.tiers[0];
+    /// assert!(tier.insert_interval(0.2, 0.4, "hi", OverlapPolicy::Error).is_err());
+    /// tier.insert_interval(0.2, 0.4, "hi", OverlapPolicy::Split).unwrap();
+    /// let labels: Vec<&str> = tier.items.iter().map(|item| item.label.as_str()).collect();
+    /// assert_eq!(labels, vec!["", "hi", ""]);
+    /// ```
+    pub fn insert_interval(
+        &mut self,
+        tmin: f64,
+        tmax: f64,
+        label: &str,
+        policy: OverlapPolicy,
+    ) -> Result<()> {
+        require_interval_tier(self, "Inserting an interval")?;
+        if tmax - tmin <= TIME_EPSILON {
+            return Err(input_error(&format!(
+                "Cannot insert interval [{}, {}] in tier {}: tmin should be less than tmax",
+                tmin, tmax, self.name
+            )));
+        }
+        let overlaps =
+            |item: &Item| item.tmin < tmax - TIME_EPSILON && item.tmax > tmin + TIME_EPSILON;
+        match policy {
+            OverlapPolicy::Error => {
+                if let Some(index) = self.items.iter().position(overlaps) {
+                    return Err(input_error(&format!(
+                        "Interval [{}, {}] overlaps item {} in tier {}",
+                        tmin, tmax, index, self.name
+                    )));
+                }
+            }
+            OverlapPolicy::Overwrite => self.items.retain(|item| !overlaps(item)),
+            OverlapPolicy::Split => {
+                let mut kept = Vec::with_capacity(self.items.len() + 1);
+                for item in self.items.drain(..) {
+                    if !overlaps(&item) {
+                        kept.push(item);
+                        continue;
+                    }
+                    if item.tmin < tmin - TIME_EPSILON {
+                        kept.push(Item {
+                            tmin: item.tmin,
+                            tmax: tmin,
+                            label: item.label.clone(),
+                        });
+                    }
+                    if item.tmax > tmax + TIME_EPSILON {
+                        kept.push(Item {
+                            tmin: tmax,
+                            tmax: item.tmax,
+                            label: item.label,
+                        });
+                    }
+                }
+                self.items = kept;
+            }
+        }
+        let index = self.items.partition_point(|item| item.tmin < tmin);
+        self.items.insert(
+            index,
+            Item {
+                tmin,
+                tmax,
+                label: label.to_string(),
+            },
+        );
+        self.size = self.items.len();
+        self.tmin = self.tmin.min(tmin);
+        self.tmax = self.tmax.max(tmax);
+        Ok(())
+    }
+
+    /// Removes an item from the tier.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The index of the item to remove
+    /// * `fill` - How to handle the gap left by the removed interval; ignored for point tiers
+    ///
+    /// # Returns
+    ///
+    /// Returns the removed item.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * `index` is out of range
+    /// * `fill` merges into a neighbor that does not exist
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{GapPolicy, TextGrid};
+    ///
+    /// let data = vec![(
+    ///     String::from("words"),
+    ///     true,
+    ///     vec![(0.0, 0.5, String::from("hello")), (0.5, 1.0, String::from("world"))],
+    /// )];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// let removed = tg.tiers[0].remove_item(1, GapPolicy::MergeLeft).unwrap();
+    /// assert_eq!(removed.label, "world");
+    /// assert_eq!(tg.tiers[0].items[0].tmax, 1.0);
+    /// ```
+    pub fn remove_item(&mut self, index: usize, fill: GapPolicy) -> Result<Item> {
+        if index >= self.items.len() {
+            return Err(input_error(&format!(
+                "Item index {} is out of range for tier {} with {} items",
+                index,
+                self.name,
+                self.items.len()
+            )));
+        }
+        if !self.interval_tier {
+            let removed = self.items.remove(index);
+            self.size = self.items.len();
+            return Ok(removed);
+        }
+        let removed = match fill {
+            GapPolicy::Leave => self.items.remove(index),
+            GapPolicy::MergeLeft => {
+                if index == 0 {
+                    return Err(input_error(&format!(
+                        "Item 0 in tier {} has no left neighbor to merge into",
+                        self.name
+                    )));
+                }
+                let removed = self.items.remove(index);
+                self.items[index - 1].tmax = removed.tmax;
+                removed
+            }
+            GapPolicy::MergeRight => {
+                if index + 1 == self.items.len() {
+                    return Err(input_error(&format!(
+                        "Item {} in tier {} has no right neighbor to merge into",
+                        index, self.name
+                    )));
+                }
+                let removed = self.items.remove(index);
+                self.items[index].tmin = removed.tmin;
+                removed
+            }
+            GapPolicy::FillEmpty => {
+                let item = &mut self.items[index];
+                Item {
+                    tmin: item.tmin,
+                    tmax: item.tmax,
+                    label: std::mem::take(&mut item.label),
+                }
+            }
+        };
+        self.size = self.items.len();
+        Ok(removed)
+    }
+
+    /// Inserts a boundary at a time point, as in Praat's "Add boundary".
+    ///
+    /// The interval containing `time` is split in two: the left part keeps the label
+    /// and the right part gets an empty label.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier is a point tier or no interval strictly contains `time`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{MergeLabelPolicy, TextGrid};
+    ///
+    /// let data = vec![(String::from("words"), true, vec![(0.0, 1.0, String::from("hello"))])];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// tg.tiers[0].insert_boundary(0.5).unwrap();
+    /// assert_eq!(tg.tiers[0].items[1].label, "");
+    /// tg.tiers[0].remove_boundary_at(0.5, MergeLabelPolicy::Concat).unwrap();
+    /// assert_eq!(tg.tiers[0].items.len(), 1);
+    /// assert_eq!(tg.tiers[0].items[0].label, "hello");
+    /// ```
+    pub fn insert_boundary(&mut self, time: f64) -> Result<()> {
+        require_interval_tier(self, "Inserting a boundary")?;
+        let label = self
+            .item_at(time)
+            .map(|(_, item)| item.label.clone())
+            .unwrap_or_default();
+        self.split_at(time, &label, "")
+    }
+
+    /// Removes the boundary at a time point, merging the two intervals it separates.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the boundary
+    /// * `policy` - Which label the merged interval receives
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier is a point tier or no boundary shared by two adjacent
+    /// intervals lies at `time`.
+    pub fn remove_boundary_at(&mut self, time: f64, policy: MergeLabelPolicy) -> Result<()> {
+        require_interval_tier(self, "Removing a boundary")?;
+        let index = self
+            .items
+            .windows(2)
+            .position(|pair| {
+                (pair[0].tmax - time).abs() <= TIME_EPSILON
+                    && (pair[1].tmin - time).abs() <= TIME_EPSILON
+            })
+            .ok_or_else(|| {
+                input_error(&format!(
+                    "No boundary between adjacent intervals at time {} in tier {}",
+                    time, self.name
+                ))
+            })?;
+        let right = self.items.remove(index + 1);
+        let left = &mut self.items[index];
+        left.tmax = right.tmax;
+        match policy {
+            MergeLabelPolicy::Left => {}
+            MergeLabelPolicy::Right => left.label = right.label,
+            MergeLabelPolicy::Concat => left.label.push_str(&right.label),
+        }
+        self.size = self.items.len();
+        Ok(())
+    }
+
+    /// Fills every gap in the tier with an interval, so that intervals tile the tier.
+    ///
+    /// Gaps before the first interval and after the last one, relative to the tier bounds,
+    /// are filled as well. Praat expects interval tiers to be gap-free.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label of the inserted intervals, usually empty
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier is a point tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(String::from("words"), true, vec![(0.2, 0.5, String::from("hello"))])];
+    /// let mut tg = TextGrid::from_data(data, None, Some(0.0), Some(1.0)).unwrap();
+    ///
+    /// tg.tiers[0].fill_gaps("").unwrap();
+    /// let spans: Vec<(f64, f64)> = tg.tiers[0].items.iter().map(|i| (i.tmin, i.tmax)).collect();
+    /// assert_eq!(spans, vec![(0.0, 0.2), (0.2, 0.5), (0.5, 1.0)]);
+    /// ```
+    pub fn fill_gaps(&mut self, label: &str) -> Result<()> {
+        require_interval_tier(self, "Filling gaps")?;
+        let mut filled = Vec::with_capacity(self.items.len() * 2 + 1);
+        let mut cursor = self.tmin;
+        for item in self.items.drain(..) {
+            if item.tmin - cursor > TIME_EPSILON {
+                filled.push(Item {
+                    tmin: cursor,
+                    tmax: item.tmin,
+                    label: label.to_string(),
+                });
+            }
+            cursor = cursor.max(item.tmax);
+            filled.push(item);
+        }
+        if self.tmax - cursor > TIME_EPSILON {
+            filled.push(Item {
+                tmin: cursor,
+                tmax: self.tmax,
+                label: label.to_string(),
+            });
+        }
+        self.items = filled;
+        self.size = self.items.len();
+        Ok(())
+    }
+}
+
+impl TextGrid {
+    /// Replaces labels across tiers with the result of a function.
+    ///
+    /// # Arguments
+    ///
+    /// * `func` - A function receiving the current label and returning the new one
+    /// * `tier_filter` - If provided, only tiers whose name matches this expression are relabeled
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{FileType, Regex, read_from_str};
+    ///
+    /// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+    /// let mut tg = read_from_str(&content, false, FileType::Auto).unwrap();
+    ///
+    /// // Strip stress markers from the phone tiers only
+    /// let stress = Regex::new(r"[012]$").unwrap();
+    /// let phone_tiers = Regex::new(r"^phones?$").unwrap();
+    /// tg.map_labels(|label| stress.replace(label, "").into_owned(), Some(&phone_tiers));
+    /// ```
+    pub fn map_labels<F>(&mut self, func: F, tier_filter: Option<&Regex>)
+    where
+        F: Fn(&str) -> String + Sync + Send,
+    {
+        for tier in self.tiers.iter_mut() {
+            if tier_filter.is_none_or(|filter| filter.is_match(&tier.name)) {
+                tier.map_labels(&func);
+            }
+        }
+    }
+
+    /// Moves the boundaries of target tiers that nearly coincide with a boundary of a
+    /// reference tier onto it, e.g. to remove the sub-millisecond noise between the word and
+    /// phone boundaries produced by an aligner.
+    ///
+    /// Every start and end time of the target items is moved to the nearest boundary of the
+    /// reference tier, if it is at most `tolerance` away. The boundaries of a point tier are
+    /// its points. Tier bounds are not changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `reference` - The index or name of the tier whose boundaries are kept
+    /// * `targets` - The indices or names of the tiers whose boundaries are moved
+    /// * `tolerance` - The largest distance, in seconds, a boundary is moved by
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a tier does not exist, or if snapping would collapse an interval of
+    /// a target tier, which may happen when `tolerance` is larger than half an interval. The
+    /// TextGrid is left unchanged in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let mut tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.5, "hello").interval(0.5, 1.0, "world"))
+    ///     .interval_tier("phones", |t| t.interval(0.0, 0.4997, "h").interval(0.4997, 1.0, "w"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// tg.snap_boundaries("words", &["phones"], 0.001).unwrap();
+    /// assert_eq!(tg["phones"].items[0].tmax, 0.5);
+    /// assert_eq!(tg["phones"].items[1].tmin, 0.5);
+    /// ```
+    pub fn snap_boundaries<K: TierKey>(
+        &mut self,
+        reference: K,
+        targets: &[K],
+        tolerance: f64,
+    ) -> Result<()> {
+        let reference = &self.tiers[self.resolve_tier(&reference)?];
+        let mut boundaries: Vec<f64> = reference
+            .items
+            .iter()
+            .flat_map(|item| [item.tmin, item.tmax])
+            .collect();
+        boundaries.sort_by(f64::total_cmp);
+        boundaries.dedup();
+        let snap = |time: f64| {
+            let next = boundaries.partition_point(|&boundary| boundary < time);
+            let nearest = [next.checked_sub(1), Some(next)]
+                .into_iter()
+                .flatten()
+                .filter_map(|index| boundaries.get(index))
+                .min_by(|a, b| (*a - time).abs().total_cmp(&(*b - time).abs()));
+            match nearest {
+                Some(&boundary) if (boundary - time).abs() <= tolerance => boundary,
+                _ => time,
+            }
+        };
+
+        let mut snapped = Vec::with_capacity(targets.len());
+        for target in targets {
+            let index = self.resolve_tier(target)?;
+            let tier = &self.tiers[index];
+            let mut items = tier.items.clone();
+            for (item_index, item) in items.iter_mut().enumerate() {
+                item.tmin = snap(item.tmin);
+                item.tmax = if tier.interval_tier {
+                    snap(item.tmax)
+                } else {
+                    item.tmin
+                };
+                if tier.interval_tier && item.tmax - item.tmin <= TIME_EPSILON {
+                    return Err(input_error(&format!(
+                        "Snapping would collapse item {} of tier {}; use a smaller tolerance",
+                        item_index, tier.name
+                    )));
+                }
+            }
+            snapped.push((index, items));
+        }
+        for (index, items) in snapped {
+            self.tiers[index].items = items;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+//! The format of TextGrid content to parse.
+
+use crate::textgrid::{data_error, input_error};
+use crate::writer::TextGridFormat;
+use std::fmt;
+use std::io::Result;
+use std::str::FromStr;
+
+/// The format of TextGrid content to parse.
+///
+/// More formats may be added in the future, so matches on it need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum FileType {
+    /// The long (full text) format, with explicit keys.
+    Long,
+    /// The short text format, with values only.
+    Short,
+    /// Detect the format from the header of the content, see [`FileType::detect`].
+    #[default]
+    Auto,
+}
+
+/// Number of bytes at the start of a file searched for the signature of a binary TextGrid.
+#[cfg(feature = "fs")]
+const SIGNATURE_LEN: usize = 16;
+
+impl FileType {
+    /// Returns the name of the file type, as accepted by [`FileType::from_str`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FileType::Long => "long",
+            FileType::Short => "short",
+            FileType::Auto => "auto",
+        }
+    }
+
+    /// Detects whether TextGrid content is in the long or the short format.
+    ///
+    /// Only the header is inspected: the `File type` and `Object class` lines, and the first
+    /// value after them, which is `xmin = ...` in the long format and a bare number in the short
+    /// format. Labels are never looked at, so a label containing `item []` cannot confuse the
+    /// detection. A leading byte order mark is ignored.
+    ///
+    /// # Returns
+    ///
+    /// Returns [`FileType::Long`] or [`FileType::Short`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error whose message starts with `Unknown TextGrid format` if the
+    /// content is not a text TextGrid, e.g. a chronological or binary TextGrid, or another Praat
+    /// object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::FileType;
+    ///
+    /// let short = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1\n<exists>\n0\n";
+    /// assert_eq!(FileType::detect(short).unwrap(), FileType::Short);
+    /// assert!(FileType::detect("\"Praat chronological TextGrid text file\"\n0 1\n").is_err());
+    /// ```
+    pub fn detect(content: &str) -> Result<FileType> {
+        let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+        let mut lines = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty());
+        let file_type = lines.next().unwrap_or_default();
+        if file_type.contains("chronological") {
+            return Err(unknown_format("chronological TextGrids are not supported"));
+        }
+        if file_type.contains("ooBinaryFile") {
+            return Err(unknown_format("binary TextGrids are not supported"));
+        }
+        if !(file_type.starts_with("File type") && file_type.contains("ooTextFile")) {
+            return Err(unknown_format(
+                "missing `File type = \"ooTextFile\"` header",
+            ));
+        }
+        let object_class = lines.next().unwrap_or_default();
+        if !(object_class.starts_with("Object class") && object_class.contains("\"TextGrid\"")) {
+            return Err(unknown_format(&format!(
+                "expected `Object class = \"TextGrid\"`, found `{}`",
+                object_class
+            )));
+        }
+        match lines.next() {
+            Some(line) if line.starts_with("xmin") => Ok(FileType::Long),
+            Some(line) if line.parse::<f64>().is_ok() => Ok(FileType::Short),
+            Some(line) => Err(unknown_format(&format!(
+                "expected the start time of the TextGrid, found `{}`",
+                line
+            ))),
+            None => Err(unknown_format("the content ends after the header")),
+        }
+    }
+}
+
+/// Creates the error returned when the format of content cannot be detected.
+fn unknown_format(reason: &str) -> std::io::Error {
+    data_error(&format!("Unknown TextGrid format: {}", reason))
+}
+
+/// Checks the raw bytes of a TextGrid file and returns them as text.
+///
+/// Binary TextGrids are recognized by their signature and rejected with an
+/// `Unknown TextGrid format` error, instead of a less helpful UTF-8 error.
+#[cfg(feature = "fs")]
+pub(crate) fn decode<'a>(fname: &str, bytes: &'a [u8]) -> Result<&'a str> {
+    let head = &bytes[..bytes.len().min(SIGNATURE_LEN)];
+    if head.windows(12).any(|window| window == b"ooBinaryFile") {
+        return Err(unknown_format(&format!(
+            "{} is a binary TextGrid, which is not supported",
+            fname
+        )));
+    }
+    std::str::from_utf8(bytes)
+        .map_err(|e| data_error(&format!("{} is not valid UTF-8: {}", fname, e)))
+}
+
+/// Parses `"long"`, `"short"` or `"auto"`.
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error for any other value.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::FileType;
+///
+/// assert_eq!("short".parse::<FileType>().unwrap(), FileType::Short);
+/// assert!("binary".parse::<FileType>().is_err());
+/// ```
+impl FromStr for FileType {
+    type Err = std::io::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "long" => Ok(FileType::Long),
+            "short" => Ok(FileType::Short),
+            "auto" => Ok(FileType::Auto),
+            _ => Err(input_error(&format!(
+                "Unknown file type: {} (expected \"long\", \"short\" or \"auto\")",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for FileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<TextGridFormat> for FileType {
+    fn from(format: TextGridFormat) -> Self {
+        match format {
+            TextGridFormat::Long => FileType::Long,
+            TextGridFormat::Short => FileType::Short,
+        }
+    }
+}
+
+/// Values accepted as the `file_type` argument of the reading functions.
+///
+/// Implemented for [`FileType`] and [`TextGridFormat`]. It is also implemented for strings
+/// (`"long"`, `"short"` or `"auto"`), so that code written before [`FileType`] existed keeps
+/// compiling and bindings can pass the names they receive. Prefer a [`FileType`] in Rust code:
+/// a misspelled string is only rejected when the reading function runs.
+pub trait IntoFileType {
+    /// Converts the value into a [`FileType`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidInput` error if the value does not name a known file type.
+    fn into_file_type(self) -> Result<FileType>;
+}
+
+impl IntoFileType for FileType {
+    fn into_file_type(self) -> Result<FileType> {
+        Ok(self)
+    }
+}
+
+impl IntoFileType for TextGridFormat {
+    fn into_file_type(self) -> Result<FileType> {
+        Ok(self.into())
+    }
+}
+
+/// Parses the name of a file type, see [`FileType::from_str`].
+impl IntoFileType for &str {
+    fn into_file_type(self) -> Result<FileType> {
+        self.parse()
+    }
+}
+
+/// Parses the name of a file type, see [`FileType::from_str`].
+impl IntoFileType for &String {
+    fn into_file_type(self) -> Result<FileType> {
+        self.parse()
+    }
+}
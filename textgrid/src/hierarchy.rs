@@ -0,0 +1,160 @@
+//! Hierarchical links between tiers, such as phones within words.
+
+use crate::edit::require_interval_tier;
+use crate::textgrid::{Item, TextGrid, Tier, input_error};
+use std::io::Result;
+use std::ops::Range;
+
+/// Links between the intervals of a parent tier and the items of a child tier they contain,
+/// as returned by [`TextGrid::link_tiers`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TierHierarchy<'a> {
+    /// The parent tier, e.g. words.
+    pub parent: &'a Tier,
+    /// The child tier, e.g. phones.
+    pub child: &'a Tier,
+    /// For each parent interval, the range of indices of its children in the child tier.
+    children: Vec<Range<usize>>,
+    /// For each child item, the index of the parent interval containing it.
+    parents: Vec<Option<usize>>,
+}
+
+impl<'a> TierHierarchy<'a> {
+    /// Returns the children of the parent interval at `parent_index`.
+    ///
+    /// Returns an empty slice if the index is out of range or the interval has no children.
+    pub fn children_of(&self, parent_index: usize) -> &'a [Item] {
+        match self.children.get(parent_index) {
+            Some(range) => &self.child.items[range.clone()],
+            None => &[],
+        }
+    }
+
+    /// Returns the range of indices in the child tier of the children of the parent interval at
+    /// `parent_index`, or `None` if the index is out of range.
+    pub fn child_range(&self, parent_index: usize) -> Option<Range<usize>> {
+        self.children.get(parent_index).cloned()
+    }
+
+    /// Returns the index of the parent interval containing the child item at `child_index`, or
+    /// `None` if no parent interval contains it.
+    pub fn parent_of(&self, child_index: usize) -> Option<usize> {
+        self.parents.get(child_index).copied().flatten()
+    }
+
+    /// Returns the indices of the child items not contained in any parent interval.
+    pub fn orphans(&self) -> Vec<usize> {
+        self.parents
+            .iter()
+            .enumerate()
+            .filter(|(_, parent)| parent.is_none())
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Iterates over the parent intervals together with their children.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a Item, &'a [Item])> + '_ {
+        self.parent
+            .items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (item, self.children_of(index)))
+    }
+}
+
+/// Returns `true` if `parent` contains `child`, allowing the child to stick out by `tolerance`.
+#[inline]
+fn contains(parent: &Item, child: &Item, tolerance: f64) -> bool {
+    child.tmin >= parent.tmin - tolerance && child.tmax <= parent.tmax + tolerance
+}
+
+impl TextGrid {
+    /// Links an interval tier to a child tier whose items it contains, such as words to phones or
+    /// utterances to words.
+    ///
+    /// Each child item belongs to the first parent interval containing it, allowing its bounds to
+    /// stick out by `tolerance` seconds. Child items contained in no parent interval are orphans.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The name of the parent interval tier
+    /// * `child` - The name of the child tier, which may be a point tier
+    /// * `tolerance` - The tolerance for containment, in seconds
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either tier does not exist or the parent tier is a point tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![
+    ///     (String::from("words"), true, vec![(0.0, 0.3, String::from("hi")), (0.3, 1.0, String::from("there"))]),
+    ///     (
+    ///         String::from("phones"),
+    ///         true,
+    ///         vec![
+    ///             (0.0, 0.1, String::from("h")),
+    ///             (0.1, 0.3, String::from("aI")),
+    ///             (0.3, 0.6, String::from("D")),
+    ///             (0.6, 1.0, String::from("E@")),
+    ///         ],
+    ///     ),
+    /// ];
+    /// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// let hierarchy = tg.link_tiers("words", "phones", 0.001).unwrap();
+    /// let phones: Vec<&str> = hierarchy.children_of(1).iter().map(|p| p.label.as_str()).collect();
+    /// assert_eq!(phones, vec!["D", "E@"]);
+    /// assert_eq!(hierarchy.parent_of(1), Some(0));
+    /// assert!(hierarchy.orphans().is_empty());
+    /// ```
+    pub fn link_tiers(
+        &self,
+        parent: &str,
+        child: &str,
+        tolerance: f64,
+    ) -> Result<TierHierarchy<'_>> {
+        let find = |name: &str| {
+            self.get_tier(name)
+                .ok_or_else(|| input_error(&format!("No tier \"{}\" in TextGrid", name)))
+        };
+        let (parent, child) = (find(parent)?, find(child)?);
+        require_interval_tier(parent, "Linking tiers")?;
+
+        let mut parents = Vec::with_capacity(child.items.len());
+        let mut candidate = 0;
+        for item in child.items.iter() {
+            while candidate < parent.items.len()
+                && parent.items[candidate].tmax + tolerance < item.tmax
+            {
+                candidate += 1;
+            }
+            parents.push(
+                (candidate < parent.items.len()
+                    && contains(&parent.items[candidate], item, tolerance))
+                .then_some(candidate),
+            );
+        }
+
+        let mut children = vec![0..0; parent.items.len()];
+        for (child_index, parent_index) in parents.iter().enumerate() {
+            if let Some(parent_index) = *parent_index {
+                let range = &mut children[parent_index];
+                if range.start == range.end {
+                    *range = child_index..child_index + 1;
+                } else {
+                    range.end = child_index + 1;
+                }
+            }
+        }
+        Ok(TierHierarchy {
+            parent,
+            child,
+            children,
+            parents,
+        })
+    }
+}
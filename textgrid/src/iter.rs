@@ -0,0 +1,138 @@
+//! Iterators over the tiers and items of a TextGrid.
+
+use crate::textgrid::{Item, TextGrid, Tier};
+use std::slice;
+
+impl Tier {
+    /// Iterates over the items of the tier.
+    pub fn iter(&self) -> slice::Iter<'_, Item> {
+        self.items.iter()
+    }
+
+    /// Iterates mutably over the items of the tier.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, Item> {
+        self.items.iter_mut()
+    }
+
+    /// Iterates over the items of the tier whose label is not empty or only whitespace, such as
+    /// the intervals that are not silences.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TierBuilder;
+    ///
+    /// let tier = TierBuilder::intervals("words")
+    ///     .bounds(0.0, 2.0)
+    ///     .interval(0.5, 1.0, "hello")
+    ///     .interval(1.0, 1.5, " ")
+    ///     .fill_gaps("")
+    ///     .build()
+    ///     .unwrap();
+    /// let labels: Vec<&str> = tier.labeled_items().map(|i| i.label.as_str()).collect();
+    /// assert_eq!(labels, vec!["hello"]);
+    /// ```
+    pub fn labeled_items(&self) -> impl Iterator<Item = &Item> + '_ {
+        self.items
+            .iter()
+            .filter(|item| !item.label.trim().is_empty())
+    }
+}
+
+impl TextGrid {
+    /// Iterates over the tiers of the TextGrid.
+    pub fn tiers(&self) -> slice::Iter<'_, Tier> {
+        self.tiers.iter()
+    }
+
+    /// Iterates mutably over the tiers of the TextGrid.
+    pub fn tiers_mut(&mut self) -> slice::IterMut<'_, Tier> {
+        self.tiers.iter_mut()
+    }
+
+    /// Iterates over the items of all tiers, in tier order, together with their tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 1.0, "hi"))
+    ///     .point_tier("beats", |t| t.point(0.5, "b1"))
+    ///     .build()
+    ///     .unwrap();
+    /// let labels: Vec<String> = tg
+    ///     .items()
+    ///     .map(|(tier, item)| format!("{}:{}", tier.name, item.label))
+    ///     .collect();
+    /// assert_eq!(labels, vec!["words:hi", "beats:b1"]);
+    /// ```
+    pub fn items(&self) -> impl Iterator<Item = (&Tier, &Item)> + '_ {
+        self.tiers
+            .iter()
+            .flat_map(|tier| tier.items.iter().map(move |item| (tier, item)))
+    }
+
+    /// Iterates over the items of all tiers whose label is not empty or only whitespace, in tier
+    /// order, together with their tier.
+    pub fn labeled_items(&self) -> impl Iterator<Item = (&Tier, &Item)> + '_ {
+        self.tiers
+            .iter()
+            .flat_map(|tier| tier.labeled_items().map(move |item| (tier, item)))
+    }
+}
+
+impl IntoIterator for Tier {
+    type Item = Item;
+    type IntoIter = std::vec::IntoIter<Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Tier {
+    type Item = &'a Item;
+    type IntoIter = slice::Iter<'a, Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Tier {
+    type Item = &'a mut Item;
+    type IntoIter = slice::IterMut<'a, Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter_mut()
+    }
+}
+
+impl IntoIterator for TextGrid {
+    type Item = Tier;
+    type IntoIter = std::vec::IntoIter<Tier>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tiers.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a TextGrid {
+    type Item = &'a Tier;
+    type IntoIter = slice::Iter<'a, Tier>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tiers.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut TextGrid {
+    type Item = &'a mut Tier;
+    type IntoIter = slice::IterMut<'a, Tier>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tiers.iter_mut()
+    }
+}
@@ -0,0 +1,170 @@
+//! Conversion of Kaldi alignments into TextGrids.
+//!
+//! Kaldi describes a corpus with plain-text tables: `segments` places each utterance within a
+//! recording, `utt2spk` names the speaker of each utterance, and a CTM file holds the words
+//! recognized or aligned in each utterance, with times relative to the start of the utterance.
+//! [`build_textgrids`] combines them into one TextGrid per recording.
+
+use crate::builder::clipped_tier;
+use crate::textgrid::{TextGrid, data_error};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Result;
+
+/// An utterance from the `segments` table.
+struct Segment<'a> {
+    recording: &'a str,
+    tmin: f64,
+    tmax: f64,
+}
+
+/// Splits a table into the fields of its lines, skipping empty lines and `;;` comments.
+///
+/// Returns an error naming the table if a line has fewer than `min_fields` fields.
+fn table<'a>(
+    content: &'a str,
+    table_name: &'a str,
+    min_fields: usize,
+) -> impl Iterator<Item = Result<(usize, Vec<&'a str>)>> + 'a {
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with(";;"))
+        .map(move |(line_number, line)| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < min_fields {
+                return Err(data_error(&format!(
+                    "Line {} of {}: expected at least {} fields, found {}",
+                    line_number,
+                    table_name,
+                    min_fields,
+                    fields.len()
+                )));
+            }
+            Ok((line_number, fields))
+        })
+}
+
+/// Parses a time field of a table.
+fn time(field: &str, line_number: usize, table_name: &str) -> Result<f64> {
+    field.parse::<f64>().map_err(|_| {
+        data_error(&format!(
+            "Line {} of {}: invalid time {}",
+            line_number, table_name, field
+        ))
+    })
+}
+
+/// Builds one TextGrid per recording from Kaldi's `segments`, `utt2spk` and CTM tables.
+///
+/// CTM times are relative to the start of their utterance and are offset by the start of the
+/// utterance in `segments`. Each TextGrid is named after its recording, starts at 0 and ends
+/// with its last utterance. For each speaker of the recording, in alphabetical order, it has a
+/// tier named after the speaker, with one interval per utterance holding its words, and a
+/// `{speaker} - words` tier with one interval per word. Overlapping words are moved to the end
+/// of the previous word, and gaps are filled with empty intervals.
+///
+/// # Arguments
+///
+/// * `ctm` - Content of the CTM file: `<utterance> <channel> <start> <duration> <word>`,
+///   optionally followed by a confidence
+/// * `segments` - Content of the `segments` file: `<utterance> <recording> <start> <end>`
+/// * `utt2spk` - Content of the `utt2spk` file: `<utterance> <speaker>`
+///
+/// # Returns
+///
+/// Returns the TextGrids keyed by recording.
+///
+/// # Errors
+///
+/// Returns an error if a line is malformed, or an utterance of the CTM file is missing from
+/// `segments`, or an utterance of `segments` is missing from `utt2spk`.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::kaldi;
+///
+/// let segments = "rec1-a rec1 0.0 2.0\nrec1-b rec1 2.5 4.0\n";
+/// let utt2spk = "rec1-a alice\nrec1-b bob\n";
+/// let ctm = "rec1-a 1 0.1 0.4 hello 0.98\nrec1-a 1 0.5 0.6 world 0.95\nrec1-b 1 0.25 0.5 hi\n";
+///
+/// let textgrids = kaldi::build_textgrids(ctm, segments, utt2spk).unwrap();
+/// let tg = &textgrids["rec1"];
+/// let names: Vec<&str> = tg.tiers.iter().map(|t| t.name.as_str()).collect();
+/// assert_eq!(names, ["alice", "alice - words", "bob", "bob - words"]);
+/// let hi = tg.tiers[3].labeled_items().next().unwrap();
+/// assert_eq!((hi.tmin, hi.tmax, hi.label.as_str()), (2.75, 3.25, "hi"));
+/// assert_eq!(tg.tiers[0].labeled_items().next().unwrap().label, "hello world");
+/// ```
+pub fn build_textgrids(
+    ctm: &str,
+    segments: &str,
+    utt2spk: &str,
+) -> Result<BTreeMap<String, TextGrid>> {
+    let mut utterances: BTreeMap<&str, Segment> = BTreeMap::new();
+    for line in table(segments, "segments", 4) {
+        let (line_number, fields) = line?;
+        utterances.insert(
+            fields[0],
+            Segment {
+                recording: fields[1],
+                tmin: time(fields[2], line_number, "segments")?,
+                tmax: time(fields[3], line_number, "segments")?,
+            },
+        );
+    }
+    let mut speakers: HashMap<&str, &str> = HashMap::new();
+    for line in table(utt2spk, "utt2spk", 2) {
+        let (_, fields) = line?;
+        speakers.insert(fields[0], fields[1]);
+    }
+    let mut words: HashMap<&str, Vec<(f64, f64, String)>> = HashMap::new();
+    for line in table(ctm, "CTM", 5) {
+        let (line_number, fields) = line?;
+        let segment = utterances.get(fields[0]).ok_or_else(|| {
+            data_error(&format!(
+                "Line {} of CTM: utterance {} is not in segments",
+                line_number, fields[0]
+            ))
+        })?;
+        let start = segment.tmin + time(fields[2], line_number, "CTM")?;
+        let duration = time(fields[3], line_number, "CTM")?;
+        words
+            .entry(fields[0])
+            .or_default()
+            .push((start, start + duration, fields[4].to_string()));
+    }
+
+    // recording -> speaker -> (utterance spans, word spans)
+    type Spans = Vec<(f64, f64, String)>;
+    let mut recordings: BTreeMap<&str, BTreeMap<&str, (Spans, Spans)>> = BTreeMap::new();
+    for (&utterance, segment) in &utterances {
+        let speaker = *speakers
+            .get(utterance)
+            .ok_or_else(|| data_error(&format!("Utterance {} is not in utt2spk", utterance)))?;
+        let mut utterance_words = words.remove(utterance).unwrap_or_default();
+        utterance_words.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let text: Vec<&str> = utterance_words.iter().map(|word| word.2.as_str()).collect();
+        let (utterance_spans, word_spans) = recordings
+            .entry(segment.recording)
+            .or_default()
+            .entry(speaker)
+            .or_default();
+        utterance_spans.push((segment.tmin, segment.tmax, text.join(" ")));
+        word_spans.extend(utterance_words);
+    }
+
+    recordings
+        .into_iter()
+        .map(|(recording, speakers)| {
+            let mut builder = TextGrid::builder().name(recording).tmin(0.0);
+            for (speaker, (utterance_spans, word_spans)) in speakers {
+                builder = builder
+                    .tier(clipped_tier(speaker, utterance_spans))
+                    .tier(clipped_tier(&format!("{} - words", speaker), word_spans));
+            }
+            Ok((recording.to_string(), builder.build()?))
+        })
+        .collect()
+}
@@ -3,75 +3,464 @@
 //! This library provides functionality to parse TextGrid files in both long and short formats,
 //! convert them to various data structures, and write them back to files.
 
+pub mod agreement;
+#[cfg(feature = "fs")]
+mod asr;
+#[cfg(feature = "audio")]
+mod audio;
+mod borrowed;
+mod builder;
 mod converter;
+#[cfg(feature = "fs")]
+mod corpus;
+mod coverage;
+mod diff;
+mod edit;
+mod file_type;
+mod hierarchy;
+mod iter;
+pub mod kaldi;
+mod parallel;
+mod parse_error;
+mod parse_options;
 mod parser_long;
 mod parser_short;
+#[cfg(feature = "pipeline")]
+mod pipeline;
+mod praat_script;
+mod query;
+mod query_builder;
+mod set_ops;
+mod structure;
 mod textgrid;
+mod tier_index;
+mod transform;
 mod utils;
+mod validation;
+mod vocabulary;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 mod writer;
 
+pub use agreement::{
+    BoundaryAlignment, ConfusionMatrix, ConfusionSampling, TierAgreement, align_boundaries,
+    confusion_matrix,
+};
+#[cfg(feature = "fs")]
+pub use asr::{AsrSchema, read_asr_json};
+#[cfg(feature = "audio")]
+pub use audio::audio_duration;
+pub use borrowed::{ItemRef, TextGridRef, TierRef};
+pub use builder::{TextGridBuilder, TierBuilder};
+pub use converter::{
+    IntervalFrom, ItemData, PointFrom, TextGridData, TextGridVectors, TextGridVectorsRef, TierData,
+};
+#[cfg(feature = "fs")]
+pub use corpus::{ConvertOptions, ConvertTarget, CorpusReadResult, convert_files, read_corpus};
+pub use coverage::{CoverageReport, Span, TierCoverage, TierPairOverlap};
+pub use diff::{BoundsChange, ItemChange, TextGridDiff, TierDiff};
+pub use edit::{GapPolicy, MergeLabelPolicy, OverlapPolicy};
+pub use file_type::{FileType, IntoFileType};
+pub use hierarchy::TierHierarchy;
+pub use parallel::{ParallelConfig, parallel_config, set_parallel_config};
+pub use parse_error::ParseError;
+pub use parse_options::ParseOptions;
+#[cfg(feature = "pipeline")]
+pub use pipeline::{
+    OutputConfig, OutputFormat, PipelineConfig, PipelineStep, RepairConfig, ValidationLevel,
+    run_pipeline,
+};
+pub use praat_script::PraatScriptMode;
+pub use query::{LabelMatch, LabelPattern, SequenceMatch};
+pub use query_builder::{Query, QueryMatch};
+pub use regex::Regex;
+pub use structure::{DuplicateNamePolicy, TierKey};
 pub use textgrid::{Item, TextGrid, Tier};
+pub use tier_index::TierIndex;
+pub use transform::Rounding;
+pub use validation::{
+    Severity, ValidationIssue, ValidationOptions, ValidationReport, ZeroLengthPolicy,
+};
+pub use vocabulary::LabelStats;
+pub use writer::{
+    CsvColumn, CsvOptions, CsvQuoting, Encoding, FloatFormat, LineEnding, TableFormat, TableLayout,
+    TextGridFormat, WriteOptions,
+};
 
-use parser_long::read_from_file_long;
-use parser_short::read_from_file_short;
+use parser_long::read_from_str_long;
+use parser_short::read_from_str_short;
 use std::io::Result;
+#[cfg(feature = "fs")]
 use utils::fast_map;
 
-/// Reads a TextGrid file from the specified path.
+/// Parses a TextGrid from the content of a TextGrid file.
 ///
 /// # Arguments
 ///
-/// * `fname` - The path to the TextGrid file
+/// * `content` - The content of the TextGrid file
 /// * `strict` - Whether to perform strict validation on the parsed data
-/// * `file_type` - The format of the file: "long", "short", or "auto" to detect automatically
+/// * `file_type` - The format of the content, see [`FileType`]
 ///
 /// # Returns
 ///
 /// Returns a `Result` containing the parsed `TextGrid` on success, or an error on failure.
+/// The name of the returned `TextGrid` is empty. A leading byte order mark is ignored.
 ///
-/// # Panics
+/// # Errors
 ///
-/// Panics if an unknown file type is provided.
+/// Returns an `InvalidInput` error if `file_type` is a string naming no known file type, and
+/// an error if the content cannot be parsed or, when `strict` is true, is invalid.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::{FileType, read_from_str};
+///
+/// let content = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1\n<exists>\n1\n\"IntervalTier\"\n\"words\"\n0\n1\n1\n0\n1\n\"hello\"\n";
+/// let tg = read_from_str(content, true, FileType::Auto).unwrap();
+/// assert_eq!(tg.tiers[0].items[0].label, "hello");
+/// ```
+pub fn read_from_str(
+    content: &str,
+    strict: bool,
+    file_type: impl IntoFileType,
+) -> Result<TextGrid> {
+    read_from_str_ref(content, strict, file_type).map(TextGridRef::into_owned)
+}
+
+/// Parses a TextGrid from the content of a TextGrid file without copying its strings.
+///
+/// Tier names and labels of the returned [`TextGridRef`] borrow from `content`, so no `String`
+/// is allocated per label unless it contains escaped quotes. Use it when labels are only
+/// inspected, and [`TextGridRef::into_owned`] to get a [`TextGrid`] when needed.
+///
+/// # Arguments
+///
+/// * `content` - The content of the TextGrid file
+/// * `strict` - Whether to perform strict validation on the parsed data
+/// * `file_type` - The format of the content, see [`FileType`]
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `TextGridRef` on success, or an error on failure.
+/// A leading byte order mark is ignored.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+/// use textgrid::{FileType, read_from_str_ref};
+///
+/// let content = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1\n<exists>\n1\n\"IntervalTier\"\n\"words\"\n0\n1\n1\n0\n1\n\"hello\"\n";
+/// let tg = read_from_str_ref(content, true, FileType::Auto).unwrap();
+/// assert!(matches!(tg.tiers[0].items[0].label, Cow::Borrowed("hello")));
+/// ```
+pub fn read_from_str_ref<'a>(
+    content: &'a str,
+    strict: bool,
+    file_type: impl IntoFileType,
+) -> Result<TextGridRef<'a>> {
+    parse(
+        content,
+        strict,
+        file_type,
+        &|_| true,
+        &ParseOptions::default(),
+    )
+}
+
+/// Parses a TextGrid from the content of a TextGrid file, normalizing labels as set in
+/// `options`.
+///
+/// # Arguments
+///
+/// * `content` - The content of the TextGrid file
+/// * `strict` - Whether to perform strict validation on the parsed data
+/// * `file_type` - The format of the content, see [`FileType`]
+/// * `options` - The normalizations applied to labels, see [`ParseOptions`]
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `TextGrid` on success, or an error on failure.
+/// The name of the returned `TextGrid` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::{FileType, ParseOptions, read_from_str_with};
+///
+/// let content = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1\n<exists>\n1\n\"IntervalTier\"\n\"words\"\n0\n1\n1\n0\n1\n\" caf\u{65}\u{301}  au  lait \"\n";
+/// let options = ParseOptions {
+///     trim_labels: true,
+///     collapse_whitespace: true,
+///     nfc_normalize: true,
+///     ..Default::default()
+/// };
+/// let tg = read_from_str_with(content, true, FileType::Auto, &options).unwrap();
+/// assert_eq!(tg.tiers[0].items[0].label, "caf\u{e9} au lait");
+/// ```
+pub fn read_from_str_with(
+    content: &str,
+    strict: bool,
+    file_type: impl IntoFileType,
+    options: &ParseOptions,
+) -> Result<TextGrid> {
+    parse(content, strict, file_type, &|_| true, options).map(TextGridRef::into_owned)
+}
+
+/// Parses a TextGrid from the content of a TextGrid file, keeping only the tiers accepted by
+/// `tier_filter`.
+///
+/// The items of other tiers are skipped without being parsed or validated, which saves time and
+/// memory when only a few tiers of large files are needed. The size of the returned `TextGrid`
+/// counts the kept tiers only.
+///
+/// # Arguments
+///
+/// * `content` - The content of the TextGrid file
+/// * `strict` - Whether to perform strict validation on the kept tiers
+/// * `file_type` - The format of the content, see [`FileType`]
+/// * `tier_filter` - Returns whether to keep a tier given its name
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `TextGrid` on success, or an error on failure.
+/// The name of the returned `TextGrid` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::{FileType, read_from_str_filtered};
+///
+/// let content = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1\n<exists>\n2\n\"IntervalTier\"\n\"words\"\n0\n1\n1\n0\n1\n\"hello\"\n\"IntervalTier\"\n\"phones\"\n0\n1\n1\n0\n1\n\"h\"\n";
+/// let tg = read_from_str_filtered(content, true, FileType::Auto, |name| ["phones"].contains(&name)).unwrap();
+/// assert_eq!(tg.size, 1);
+/// assert_eq!(tg.tiers[0].name, "phones");
+/// ```
+pub fn read_from_str_filtered(
+    content: &str,
+    strict: bool,
+    file_type: impl IntoFileType,
+    tier_filter: impl Fn(&str) -> bool,
+) -> Result<TextGrid> {
+    parse(
+        content,
+        strict,
+        file_type,
+        &tier_filter,
+        &ParseOptions::default(),
+    )
+    .map(TextGridRef::into_owned)
+}
+
+/// Parses content with the parser for `file_type`, keeping the tiers accepted by `tier_filter`
+/// and normalizing labels as set in `options`.
+fn parse<'a>(
+    content: &'a str,
+    strict: bool,
+    file_type: impl IntoFileType,
+    tier_filter: &dyn Fn(&str) -> bool,
+    options: &ParseOptions,
+) -> Result<TextGridRef<'a>> {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    match file_type.into_file_type()? {
+        FileType::Long => read_from_str_long(content, strict, tier_filter, options),
+        FileType::Short => read_from_str_short(content, strict, tier_filter, options),
+        FileType::Auto => match FileType::detect(content)? {
+            FileType::Long => read_from_str_long(content, strict, tier_filter, options),
+            _ => read_from_str_short(content, strict, tier_filter, options),
+        },
+    }
+}
+
+/// Reads a TextGrid file from the specified path.
+///
+/// # Arguments
+///
+/// * `fname` - The path to the TextGrid file
+/// * `strict` - Whether to perform strict validation on the parsed data
+/// * `file_type` - The format of the file, see [`FileType`]
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `TextGrid` on success, or an error on failure.
 ///
 /// # Examples
 ///
 /// ```no_run
-/// use textgrid::read_from_file;
+/// use textgrid::{FileType, read_from_file};
 ///
 /// // Auto-detect format
-/// let tg = read_from_file("example.TextGrid", true, "auto").unwrap();
+/// let tg = read_from_file("example.TextGrid", true, FileType::Auto).unwrap();
 /// println!("TextGrid has {} tiers", tg.tiers.len());
 ///
 /// // Explicitly specify long format
-/// let tg_long = read_from_file("example.TextGrid", false, "long").unwrap();
+/// let tg_long = read_from_file("example.TextGrid", false, FileType::Long).unwrap();
+/// ```
+#[cfg(feature = "fs")]
+pub fn read_from_file(fname: &str, strict: bool, file_type: impl IntoFileType) -> Result<TextGrid> {
+    let bytes = std::fs::read(fname)?;
+    let content = file_type::decode(fname, &bytes)?;
+    let mut tg = read_from_str(content, strict, file_type)?;
+    tg.name = file_stem(fname);
+    Ok(tg)
+}
+
+/// Reads a TextGrid file from the specified path, normalizing labels as set in `options`.
+///
+/// See [`read_from_str_with`].
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use textgrid::{FileType, ParseOptions, read_from_file_with};
+///
+/// let options = ParseOptions {
+///     nfc_normalize: true,
+///     ..Default::default()
+/// };
+/// let tg = read_from_file_with("example.TextGrid", true, FileType::Auto, &options).unwrap();
 /// ```
-pub fn read_from_file(fname: &str, strict: bool, file_type: &str) -> Result<TextGrid> {
+#[cfg(feature = "fs")]
+pub fn read_from_file_with(
+    fname: &str,
+    strict: bool,
+    file_type: impl IntoFileType,
+    options: &ParseOptions,
+) -> Result<TextGrid> {
+    let bytes = std::fs::read(fname)?;
+    let content = file_type::decode(fname, &bytes)?;
+    let mut tg = read_from_str_with(content, strict, file_type, options)?;
+    tg.name = file_stem(fname);
+    Ok(tg)
+}
+
+/// Reads a TextGrid file from the specified path, keeping only the tiers accepted by
+/// `tier_filter`.
+///
+/// See [`read_from_str_filtered`] for how other tiers are skipped.
+///
+/// # Arguments
+///
+/// * `fname` - The path to the TextGrid file
+/// * `strict` - Whether to perform strict validation on the kept tiers
+/// * `file_type` - The format of the file, see [`FileType`]
+/// * `tier_filter` - Returns whether to keep a tier given its name
+///
+/// # Returns
+///
+/// Returns a `Result` containing the parsed `TextGrid` on success, or an error on failure.
+///
+/// # Examples
+///
+/// ```no_run
+/// use textgrid::{FileType, read_from_file_filtered};
+///
+/// let tg = read_from_file_filtered("example.TextGrid", true, FileType::Auto, |name| name == "phones").unwrap();
+/// println!("Kept {} tiers", tg.tiers.len());
+/// ```
+#[cfg(feature = "fs")]
+pub fn read_from_file_filtered(
+    fname: &str,
+    strict: bool,
+    file_type: impl IntoFileType,
+    tier_filter: impl Fn(&str) -> bool,
+) -> Result<TextGrid> {
+    let bytes = std::fs::read(fname)?;
+    let content = file_type::decode(fname, &bytes)?;
+    let mut tg = read_from_str_filtered(content, strict, file_type, tier_filter)?;
+    tg.name = file_stem(fname);
+    Ok(tg)
+}
+
+/// Reads a TextGrid file through a memory map, parsing directly from the mapped bytes.
+///
+/// Behaves like [`read_from_file`], but skips copying the file into a `String` first, which
+/// lowers peak memory when reading many large files. With the `mmap` feature enabled,
+/// [`files_to_data`] and [`files_to_vectors`] read files this way.
+///
+/// The file must not be modified while it is being read; otherwise parsing may see
+/// inconsistent content.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or mapped, is not valid UTF-8, or cannot be
+/// parsed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use textgrid::{FileType, read_from_file_mmap};
+///
+/// let tg = read_from_file_mmap("example.TextGrid", true, FileType::Auto).unwrap();
+/// println!("TextGrid has {} tiers", tg.tiers.len());
+/// ```
+#[cfg(feature = "mmap")]
+pub fn read_from_file_mmap(
+    fname: &str,
+    strict: bool,
+    file_type: impl IntoFileType,
+) -> Result<TextGrid> {
+    let file = std::fs::File::open(fname)?;
+    // SAFETY: the map is only read during parsing, and callers are told not to modify the
+    // file meanwhile; the content is validated as UTF-8 before use.
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    let content = file_type::decode(fname, &mmap)?;
+    let mut tg = read_from_str(content, strict, file_type)?;
+    tg.name = file_stem(fname);
+    Ok(tg)
+}
+
+/// Returns the file name of a path without its extension, used as the name of a TextGrid.
+#[cfg(feature = "fs")]
+fn file_stem(fname: &str) -> String {
+    std::path::Path::new(fname)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Reads one file of a batch, through a memory map if the `mmap` feature is enabled.
+#[cfg(feature = "fs")]
+#[inline]
+fn read_batch_file(fname: &str, strict: bool, file_type: FileType) -> Result<TextGrid> {
+    #[cfg(feature = "mmap")]
+    return read_from_file_mmap(fname, strict, file_type);
+    #[cfg(not(feature = "mmap"))]
+    return read_from_file(fname, strict, file_type);
+}
+
+/// Copies the file type resolved once for a batch, or its error, for one file of the batch.
+#[cfg(feature = "fs")]
+fn resolved(file_type: &Result<FileType>) -> Result<FileType> {
     match file_type {
-        "long" => read_from_file_long(fname, strict),
-        "short" => read_from_file_short(fname, strict),
-        "auto" => {
-            let content = std::fs::read_to_string(fname)?;
-            if content.contains("item []") {
-                read_from_file_long(fname, strict)
-            } else {
-                read_from_file_short(fname, strict)
-            }
-        }
-        _ => panic!("Unknown file type: {}", file_type),
+        Ok(file_type) => Ok(*file_type),
+        Err(e) => Err(std::io::Error::new(e.kind(), e.to_string())),
     }
 }
 
+/// Adds the name of the file that caused an error to its message.
+#[cfg(feature = "fs")]
+fn file_error(fname: &str, e: std::io::Error) -> std::io::Error {
+    std::io::Error::new(e.kind(), format!("{}: {}", fname, e))
+}
+
 /// Reads multiple TextGrid files and converts them to data format in parallel.
 ///
 /// # Arguments
 ///
-/// * `fnames` - A vector of file paths to TextGrid files
+/// * `fnames` - A slice of file paths to TextGrid files
 /// * `strict` - Whether to perform strict validation on the parsed data
-/// * `file_type` - The format of the files: "long", "short", or "auto"
+/// * `file_type` - The format of the files, see [`FileType`]
 ///
 /// # Returns
 ///
-/// Returns a vector of tuples, where each tuple contains:
+/// Returns one `Result` per file, in the order of `fnames`. On success, it contains a tuple of:
 /// * `tmin` - The minimum time of the TextGrid
 /// * `tmax` - The maximum time of the TextGrid
 /// * A vector of tier data, where each tier contains:
@@ -79,82 +468,209 @@ pub fn read_from_file(fname: &str, strict: bool, file_type: &str) -> Result<Text
 ///   - Whether it's an interval tier (bool)
 ///   - A vector of items (tmin, tmax, label)
 ///
-/// Files that fail to parse return `(0.0, 0.0, Vec::new())`.
+/// # Errors
+///
+/// Each file that cannot be read or parsed gets an error whose message starts with its path.
 ///
 /// # Examples
 ///
 /// ```no_run
-/// use textgrid::files_to_data;
+/// use textgrid::{FileType, files_to_data};
 ///
 /// let files = vec![
 ///     String::from("file1.TextGrid"),
 ///     String::from("file2.TextGrid"),
 /// ];
-/// let data = files_to_data(&files, true, "auto");
-/// for (tmin, tmax, tiers) in data {
-///     println!("TextGrid: {:.2} - {:.2}, {} tiers", tmin, tmax, tiers.len());
+/// for result in files_to_data(&files, true, FileType::Auto) {
+///     match result {
+///         Ok((tmin, tmax, tiers)) => {
+///             println!("TextGrid: {:.2} - {:.2}, {} tiers", tmin, tmax, tiers.len())
+///         }
+///         Err(e) => eprintln!("Skipping {}", e),
+///     }
 /// }
 /// ```
+#[cfg(feature = "fs")]
 pub fn files_to_data(
-    fnames: &Vec<String>,
+    fnames: &[String],
     strict: bool,
-    file_type: &str,
-) -> Vec<(f64, f64, Vec<(String, bool, Vec<(f64, f64, String)>)>)> {
+    file_type: impl IntoFileType,
+) -> Vec<Result<TextGridData>> {
+    let file_type = file_type.into_file_type();
     let map_fun = |tgt_fname: &String| {
-        let tgt_result = read_from_file(tgt_fname, strict, file_type);
-        match tgt_result {
-            Ok(tgt) => tgt.to_data(),
-            Err(_) => (0.0, 0.0, Vec::new()),
-        }
+        resolved(&file_type)
+            .and_then(|file_type| read_batch_file(tgt_fname, strict, file_type))
+            .map(TextGrid::into_data)
+            .map_err(|e| file_error(tgt_fname, e))
     };
-    let datas: Vec<(f64, f64, Vec<(String, bool, Vec<(f64, f64, String)>)>)> =
-        fast_map(fnames, map_fun, 20);
-    datas
+    fast_map(fnames, map_fun)
 }
 
 /// Reads multiple TextGrid files and converts them to vector format in parallel.
 ///
 /// # Arguments
 ///
-/// * `fnames` - A vector of file paths to TextGrid files
+/// * `fnames` - A slice of file paths to TextGrid files
 /// * `strict` - Whether to perform strict validation on the parsed data
-/// * `file_type` - The format of the files: "long", "short", or "auto"
+/// * `file_type` - The format of the files, see [`FileType`]
 ///
 /// # Returns
 ///
-/// Returns a vector of tuples, where each tuple contains:
+/// Returns one `Result` per file, in the order of `fnames`. On success, it contains a tuple of:
 /// * A vector of tmin values (`Vec<f64>`)
 /// * A vector of tmax values (`Vec<f64>`)
 /// * A vector of labels (`Vec<String>`)
 /// * A vector of tier names (`Vec<String>`)
 /// * A vector of interval tier flags (`Vec<bool>`)
 ///
-/// Files that fail to parse return empty vectors.
+/// # Errors
+///
+/// Each file that cannot be read or parsed gets an error whose message starts with its path.
 ///
 /// # Examples
 ///
 /// ```no_run
-/// use textgrid::files_to_vectors;
+/// use textgrid::{FileType, files_to_vectors};
 ///
 /// let files = vec![String::from("example.TextGrid")];
-/// let vectors = files_to_vectors(&files, false, "auto");
-/// for (tmins, tmaxs, labels, tier_names, is_intervals) in vectors {
+/// let vectors = files_to_vectors(&files, false, FileType::Auto);
+/// for (tmins, tmaxs, labels, tier_names, is_intervals) in vectors.into_iter().flatten() {
 ///     println!("Found {} items", tmins.len());
 /// }
 /// ```
+#[cfg(feature = "fs")]
 pub fn files_to_vectors(
-    fnames: &Vec<String>,
+    fnames: &[String],
     strict: bool,
-    file_type: &str,
-) -> Vec<(Vec<f64>, Vec<f64>, Vec<String>, Vec<String>, Vec<bool>)> {
+    file_type: impl IntoFileType,
+) -> Vec<Result<TextGridVectors>> {
+    let file_type = file_type.into_file_type();
     let map_fun = |tgt_fname: &String| {
-        let tgt_result = read_from_file(tgt_fname, strict, file_type);
-        match tgt_result {
-            Ok(tgt) => tgt.to_vectors(),
-            Err(_) => (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new()),
-        }
+        resolved(&file_type)
+            .and_then(|file_type| read_batch_file(tgt_fname, strict, file_type))
+            .map(|tgt| tgt.to_vectors())
+            .map_err(|e| file_error(tgt_fname, e))
+    };
+    fast_map(fnames, map_fun)
+}
+
+/// Writes multiple TextGrids to files in parallel.
+///
+/// # Arguments
+///
+/// * `grids` - Pairs of the output path and the TextGrid to write to it
+/// * `format` - The TextGrid format to write
+/// * `options` - The write options, see [`WriteOptions`]
+///
+/// # Returns
+///
+/// Returns one `Result` per TextGrid, in the order of `grids`.
+///
+/// # Errors
+///
+/// Each file that cannot be written gets an error whose message starts with its path.
+///
+/// # Examples
+///
+/// ```no_run
+/// use textgrid::{FileType, TextGridFormat, WriteOptions, read_from_file, write_many};
+///
+/// let tg = read_from_file("input.TextGrid", false, FileType::Auto).unwrap();
+/// let grids = vec![(String::from("copy1.TextGrid"), &tg), (String::from("copy2.TextGrid"), &tg)];
+/// for result in write_many(&grids, TextGridFormat::Short, &WriteOptions::default()) {
+///     if let Err(e) = result {
+///         eprintln!("Could not write {}", e);
+///     }
+/// }
+/// ```
+#[cfg(feature = "fs")]
+pub fn write_many(
+    grids: &[(String, &TextGrid)],
+    format: TextGridFormat,
+    options: &WriteOptions,
+) -> Vec<Result<()>> {
+    let map_fun = |(fname, tg): &(String, &TextGrid)| {
+        tg.save_textgrid(fname, format, options)
+            .map_err(|e| file_error(fname, e))
+    };
+    fast_map(grids, map_fun)
+}
+
+/// Number of files read in parallel at a time by [`files_to_csv`].
+#[cfg(feature = "fs")]
+const CSV_BATCH_LEN: usize = 256;
+
+/// Reads multiple TextGrid files and writes all their items to a single CSV file.
+///
+/// Files are read in parallel in small batches and their items written as soon as a batch is
+/// read, so memory use does not grow with the number of files. The first column is always the
+/// `filename` column, holding the path of each file as given in `fnames`, followed by the columns
+/// of `options`; set [`CsvOptions::delimiter`] to `b'\t'` for TSV. Files that cannot be read or
+/// parsed are skipped.
+///
+/// # Arguments
+///
+/// * `fnames` - A slice of file paths to TextGrid files
+/// * `out_path` - The path where the CSV file will be saved
+/// * `strict` - Whether to perform strict validation on the parsed data
+/// * `file_type` - The format of the files, see [`FileType`]
+/// * `options` - The CSV options, see [`CsvOptions`]
+///
+/// # Returns
+///
+/// Returns one `Result` per file, in the order of `fnames`, telling whether its items were
+/// written.
+///
+/// # Errors
+///
+/// Returns an error if the CSV file cannot be created or written. Each file that cannot be read
+/// or parsed gets an error whose message starts with its path.
+///
+/// # Examples
+///
+/// ```no_run
+/// use textgrid::{CsvOptions, FileType, files_to_csv};
+///
+/// let files = vec![String::from("file1.TextGrid"), String::from("file2.TextGrid")];
+/// let results = files_to_csv(&files, "corpus.tsv", false, FileType::Auto, &CsvOptions {
+///     delimiter: b'\t',
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// for e in results.into_iter().filter_map(Result::err) {
+///     eprintln!("Skipped {}", e);
+/// }
+/// ```
+#[cfg(feature = "fs")]
+pub fn files_to_csv(
+    fnames: &[String],
+    out_path: &str,
+    strict: bool,
+    file_type: impl IntoFileType,
+    options: &CsvOptions,
+) -> Result<Vec<Result<()>>> {
+    let file_type = file_type.into_file_type()?;
+    let options = CsvOptions {
+        include_filename: true,
+        ..options.clone()
     };
-    let vectors: Vec<(Vec<f64>, Vec<f64>, Vec<String>, Vec<String>, Vec<bool>)> =
-        fast_map(fnames, map_fun, 20);
-    vectors
+    let file = std::io::BufWriter::new(std::fs::File::create(out_path)?);
+    let mut wtr = options.csv_writer(file)?;
+    let mut results = Vec::with_capacity(fnames.len());
+    for batch in fnames.chunks(CSV_BATCH_LEN) {
+        let read = |fname: &String| {
+            read_batch_file(fname, strict, file_type).map_err(|e| file_error(fname, e))
+        };
+        for (fname, tgt) in batch.iter().zip(fast_map(batch, read)) {
+            match tgt {
+                Ok(tgt) => {
+                    tgt.write_csv_records(&mut wtr, &options, fname)?;
+                    results.push(Ok(()));
+                }
+                Err(e) => results.push(Err(e)),
+            }
+        }
+    }
+    wtr.flush()?;
+    Ok(results)
 }
@@ -0,0 +1,149 @@
+//! Crate-wide configuration of parallel processing.
+//!
+//! Large tiers and file batches are mapped in parallel with rayon. By default, work runs on the
+//! global rayon pool once a collection is large enough; [`set_parallel_config`] changes the
+//! threshold, runs work on a dedicated pool, or disables parallelism entirely, e.g. when the
+//! crate is embedded in an application that manages its own threads.
+
+use std::io::Result;
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
+use std::sync::RwLock;
+
+/// Configuration of parallel processing, set with [`set_parallel_config`].
+///
+/// Without the `parallel` feature, work always runs sequentially and this configuration has no
+/// effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelConfig {
+    /// Whether to process large collections in parallel at all.
+    pub enabled: bool,
+    /// Number of threads of a dedicated pool, or `None` to use the global rayon pool.
+    pub num_threads: Option<usize>,
+    /// Minimum number of items processed by each parallel task. A collection is only processed
+    /// in parallel if it holds at least `min_len` items per two threads.
+    pub min_len: usize,
+}
+
+impl ParallelConfig {
+    const DEFAULT: ParallelConfig = ParallelConfig {
+        enabled: true,
+        num_threads: None,
+        min_len: 20,
+    };
+}
+
+impl Default for ParallelConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The current configuration, with the dedicated pool built for it if any.
+struct ParallelState {
+    config: ParallelConfig,
+    #[cfg(feature = "parallel")]
+    pool: Option<Arc<rayon::ThreadPool>>,
+}
+
+static STATE: RwLock<ParallelState> = RwLock::new(ParallelState {
+    config: ParallelConfig::DEFAULT,
+    #[cfg(feature = "parallel")]
+    pool: None,
+});
+
+/// Sets the configuration of parallel processing for the whole crate.
+///
+/// Work already running keeps its previous configuration.
+///
+/// # Arguments
+///
+/// * `config` - The new configuration
+///
+/// # Errors
+///
+/// Returns an error if `num_threads` is `Some(0)` or the dedicated thread pool cannot be built.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::{ParallelConfig, parallel_config, set_parallel_config};
+///
+/// // Leave threading to the embedding application
+/// set_parallel_config(ParallelConfig {
+///     enabled: false,
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// assert!(!parallel_config().enabled);
+/// # set_parallel_config(ParallelConfig::default()).unwrap();
+/// ```
+pub fn set_parallel_config(config: ParallelConfig) -> Result<()> {
+    if config.num_threads == Some(0) {
+        return Err(crate::textgrid::input_error(
+            "Number of threads should be positive",
+        ));
+    }
+    #[cfg(feature = "parallel")]
+    let pool = match config.num_threads {
+        Some(num_threads) if config.enabled => Some(Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .map_err(std::io::Error::other)?,
+        )),
+        _ => None,
+    };
+    let mut state = STATE.write().unwrap_or_else(|e| e.into_inner());
+    *state = ParallelState {
+        config,
+        #[cfg(feature = "parallel")]
+        pool,
+    };
+    Ok(())
+}
+
+/// Returns the current configuration of parallel processing.
+pub fn parallel_config() -> ParallelConfig {
+    STATE.read().unwrap_or_else(|e| e.into_inner()).config
+}
+
+/// How to process a collection in parallel, as decided by [`plan`].
+#[cfg(feature = "parallel")]
+pub(crate) struct Plan {
+    /// Minimum number of items per parallel task.
+    pub(crate) min_len: usize,
+    pool: Option<Arc<rayon::ThreadPool>>,
+}
+
+#[cfg(feature = "parallel")]
+impl Plan {
+    /// Runs `op` on the configured pool.
+    #[inline]
+    pub(crate) fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+}
+
+/// Decides whether a collection of `len` items is worth processing in parallel, returning how
+/// to do it if so.
+#[cfg(feature = "parallel")]
+#[inline]
+pub(crate) fn plan(len: usize) -> Option<Plan> {
+    let state = STATE.read().unwrap_or_else(|e| e.into_inner());
+    if !state.config.enabled {
+        return None;
+    }
+    let num_threads = match &state.pool {
+        Some(pool) => pool.current_num_threads(),
+        None => num_cpus::get(),
+    };
+    let min_len = state.config.min_len.max(1);
+    (len >= num_threads * min_len / 2).then(|| Plan {
+        min_len,
+        pool: state.pool.clone(),
+    })
+}
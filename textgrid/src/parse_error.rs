@@ -0,0 +1,73 @@
+//! Typed errors of the TextGrid parsers.
+
+use std::fmt;
+use std::io::{Error, ErrorKind};
+
+/// A structural problem found while parsing a TextGrid.
+///
+/// The parsers return `std::io::Error`s like the rest of the crate; for these problems the
+/// error wraps a `ParseError`, which can be recovered with [`ParseError::from_io`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The file ends before the TextGrid is complete, e.g. because it was truncated while
+    /// being copied.
+    UnexpectedEof {
+        /// What the parser expected to read next, e.g. `interval 3 of tier 2`.
+        expected: String,
+        /// The 1-based number of the line the file ends on.
+        at_line: usize,
+    },
+}
+
+impl ParseError {
+    /// Returns the `ParseError` wrapped in an error returned by a parser, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{FileType, ParseError, read_from_str};
+    ///
+    /// let truncated = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1\n<exists>\n1\n";
+    /// let err = read_from_str(truncated, false, FileType::Short).unwrap_err();
+    /// assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    /// assert!(matches!(
+    ///     ParseError::from_io(&err),
+    ///     Some(ParseError::UnexpectedEof { at_line: 8, .. })
+    /// ));
+    /// ```
+    pub fn from_io(error: &Error) -> Option<&ParseError> {
+        error.get_ref()?.downcast_ref::<ParseError>()
+    }
+
+    /// Creates an `UnexpectedEof` error for content ending on its last line.
+    pub(crate) fn eof(content: &str, expected: String) -> ParseError {
+        ParseError::UnexpectedEof {
+            expected,
+            at_line: content.matches('\n').count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedEof { expected, at_line } => write!(
+                f,
+                "Line {}: unexpected end of file, expected {}",
+                at_line, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        let kind = match error {
+            ParseError::UnexpectedEof { .. } => ErrorKind::UnexpectedEof,
+        };
+        Error::new(kind, error)
+    }
+}
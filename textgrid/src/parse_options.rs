@@ -0,0 +1,83 @@
+//! Options applied while parsing.
+
+use crate::validation::ZeroLengthPolicy;
+use std::borrow::Cow;
+use unicode_normalization::{IsNormalized, UnicodeNormalization, is_nfc_quick};
+
+/// Normalizations applied to every label while parsing, and what else to keep from the file,
+/// see [`read_from_str_with`].
+///
+/// All are disabled by default, so labels are read exactly as written.
+///
+/// [`read_from_str_with`]: crate::read_from_str_with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    /// Whether to remove leading and trailing whitespace.
+    pub trim_labels: bool,
+    /// Whether to replace every run of whitespace with a single space.
+    pub collapse_whitespace: bool,
+    /// Whether to convert labels to Unicode Normalization Form C, so that precomposed and
+    /// decomposed characters, such as "é" and "e\u{301}", compare equal.
+    pub nfc_normalize: bool,
+    /// Whether to keep the key/value lines of the header and of tiers that the long-format
+    /// parser does not recognize, in [`TextGrid::unknown_fields`] and [`Tier::unknown_fields`],
+    /// so that writing the TextGrid in the long format re-emits them. Unknown keys of items, and
+    /// anything in the short format, are still dropped.
+    ///
+    /// [`TextGrid::unknown_fields`]: crate::TextGrid::unknown_fields
+    /// [`Tier::unknown_fields`]: crate::Tier::unknown_fields
+    pub preserve_unknown_fields: bool,
+    /// Whether to read a comma in a time as a decimal point, e.g. `1,234` as 1.234, for files
+    /// exported with a European locale. Commas are never read as thousands separators, which
+    /// Praat does not write.
+    pub accept_decimal_comma: bool,
+    /// Whether to truncate tiers extending beyond the TextGrid, and items extending beyond
+    /// their tier, to those bounds instead of leaving them for validation to reject. Items
+    /// left empty are removed, and each change is recorded in [`TextGrid::warnings`].
+    ///
+    /// [`TextGrid::warnings`]: crate::TextGrid::warnings
+    pub clamp_to_bounds: bool,
+    /// What to do with intervals of zero length. Under [`ZeroLengthPolicy::Warn`] they are kept
+    /// and pass strict validation; under [`ZeroLengthPolicy::Drop`] and
+    /// [`ZeroLengthPolicy::Merge`] they are removed. Each is recorded in
+    /// [`TextGrid::warnings`] unless rejected, the default.
+    ///
+    /// [`TextGrid::warnings`]: crate::TextGrid::warnings
+    pub zero_length: ZeroLengthPolicy,
+}
+
+impl ParseOptions {
+    /// Applies the enabled normalizations to a label, allocating only if it changes.
+    pub(crate) fn normalize_label<'a>(&self, label: Cow<'a, str>) -> Cow<'a, str> {
+        let mut label = label;
+        if self.trim_labels {
+            label = match label {
+                Cow::Borrowed(s) => Cow::Borrowed(s.trim()),
+                Cow::Owned(s) if s.trim().len() == s.len() => Cow::Owned(s),
+                Cow::Owned(s) => Cow::Owned(s.trim().to_string()),
+            };
+        }
+        if self.collapse_whitespace {
+            let mut collapsed = String::with_capacity(label.len());
+            let mut in_whitespace = false;
+            for c in label.chars() {
+                if !c.is_whitespace() {
+                    collapsed.push(c);
+                } else if !in_whitespace {
+                    collapsed.push(' ');
+                }
+                in_whitespace = c.is_whitespace();
+            }
+            if collapsed != *label {
+                label = Cow::Owned(collapsed);
+            }
+        }
+        if self.nfc_normalize && is_nfc_quick(label.chars()) != IsNormalized::Yes {
+            let normalized: String = label.nfc().collect();
+            if normalized != *label {
+                label = Cow::Owned(normalized);
+            }
+        }
+        label
+    }
+}
@@ -3,8 +3,11 @@
 //! This module provides functionality to parse TextGrid files in the long format,
 //! which uses explicit key-value pairs with equals signs.
 
-use crate::textgrid::*;
-use crate::utils::{parse_float, parse_str, parse_uint};
+use crate::borrowed::{ItemRef, TextGridRef, TierRef};
+use crate::parse_error::ParseError;
+use crate::parse_options::ParseOptions;
+use crate::textgrid::data_error;
+use crate::utils::{logical_lines, parse_float, parse_str, parse_uint};
 use std::io::Result;
 
 /// Represents the current parsing state when reading a long-format TextGrid file.
@@ -17,6 +20,8 @@ enum State {
     Tier,
     /// Parsing the tier list declaration
     TierList,
+    /// Skipping a tier rejected by the tier filter
+    SkippedTier,
 }
 
 /// Parses a key-value pair from a line.
@@ -31,144 +36,298 @@ enum State {
 /// otherwise returns `None`.
 #[inline]
 fn parse_kv(line: &str) -> Option<(&str, &str)> {
-    let parts: Vec<&str> = line.splitn(2, '=').collect();
-    if parts.len() == 2 {
-        Some((parts[0].trim(), parts[1].trim()))
-    } else {
-        None
-    }
+    line.split_once('=')
+        .map(|(key, value)| (key.trim(), value.trim()))
 }
 
-/// Parses a key-value pair and updates an Item accordingly.
+/// Updates an Item with a key-value pair.
 ///
 /// # Arguments
 ///
-/// * `line` - The line to parse
+/// * `(key, value)` - The key-value pair of the line
+/// * `line_number` - The 1-based number of the line, for error messages
 /// * `item` - The item to update with parsed values
+/// * `options` - The normalizations applied to the label, and whether to accept decimal
+///   commas
+///
+/// # Errors
+///
+/// Returns an error if a time is not a number.
 #[inline]
-fn parse_item_kv(line: &str, item: &mut Item) {
-    if let Some((key, value)) = parse_kv(line) {
-        match key {
-            "xmin" => item.tmin = parse_float(value),
-            "xmax" => item.tmax = parse_float(value),
-            "text" => item.label = parse_str(value),
-            "number" => {
-                item.tmin = parse_float(value);
-                item.tmax = item.tmin;
-            }
-            "mark" => item.label = parse_str(value),
-            _ => {}
+fn parse_item_kv<'a>(
+    (key, value): (&'a str, &'a str),
+    line_number: usize,
+    item: &mut ItemRef<'a>,
+    options: &ParseOptions,
+) -> Result<()> {
+    match key {
+        "xmin" => item.tmin = parse_float(value, line_number, options.accept_decimal_comma)?,
+        "xmax" => item.tmax = parse_float(value, line_number, options.accept_decimal_comma)?,
+        "text" => item.label = options.normalize_label(parse_str(value)),
+        "number" => {
+            item.tmin = parse_float(value, line_number, options.accept_decimal_comma)?;
+            item.tmax = item.tmin;
         }
+        "mark" => item.label = options.normalize_label(parse_str(value)),
+        _ => {}
     }
+    Ok(())
 }
 
-/// Parses a key-value pair and updates a Tier accordingly.
+/// Updates a Tier with a key-value pair.
 ///
 /// # Arguments
 ///
-/// * `line` - The line to parse
+/// * `(key, value)` - The key-value pair of the line
+/// * `line_number` - The 1-based number of the line, for error messages
 /// * `tier` - The tier to update with parsed values
+/// * `options` - Whether to keep unrecognized keys and accept decimal commas
+///
+/// # Returns
+///
+/// Returns `true` if the pair set the name of the tier.
+///
+/// # Errors
+///
+/// Returns an error if a time or the size is not a number, or the class is unknown.
 #[inline]
-fn parse_tier_kv(line: &str, tier: &mut Tier) {
-    if let Some((key, value)) = parse_kv(line) {
-        match key {
-            "class" => match value.trim_matches('"') {
-                "IntervalTier" => tier.interval_tier = true,
-                "TextTier" => tier.interval_tier = false,
-                _ => {
-                    panic!("Unknown tier class: {}", value);
-                }
-            },
-            "name" => tier.name = parse_str(value),
-            "intervals: size" => tier.size = parse_uint(value),
-            "points: size" => tier.size = parse_uint(value),
-            "xmin" => tier.tmin = parse_float(value),
-            "xmax" => tier.tmax = parse_float(value),
-            _ => {}
+fn parse_tier_kv<'a>(
+    (key, value): (&'a str, &'a str),
+    line_number: usize,
+    tier: &mut TierRef<'a>,
+    options: &ParseOptions,
+) -> Result<bool> {
+    match key {
+        "class" => match value.trim_matches('"') {
+            "IntervalTier" => tier.interval_tier = true,
+            "TextTier" => tier.interval_tier = false,
+            _ => {
+                return Err(data_error(&format!(
+                    "Line {}: unknown tier class {}",
+                    line_number, value
+                )));
+            }
+        },
+        "name" => {
+            tier.name = parse_str(value);
+            return Ok(true);
         }
+        "intervals: size" => tier.size = parse_uint(value, line_number)?,
+        "points: size" => tier.size = parse_uint(value, line_number)?,
+        "xmin" => tier.tmin = parse_float(value, line_number, options.accept_decimal_comma)?,
+        "xmax" => tier.tmax = parse_float(value, line_number, options.accept_decimal_comma)?,
+        _ if options.preserve_unknown_fields => tier.unknown_fields.push((key, value)),
+        _ => {}
     }
+    Ok(false)
 }
 
-/// Parses a key-value pair and updates a TextGrid accordingly.
+/// Updates a TextGrid with a key-value pair.
 ///
 /// # Arguments
 ///
-/// * `line` - The line to parse
+/// * `(key, value)` - The key-value pair of the line
+/// * `line_number` - The 1-based number of the line, for error messages
 /// * `tg` - The TextGrid to update with parsed values
+/// * `options` - Whether to keep unrecognized keys and accept decimal commas
+///
+/// # Errors
+///
+/// Returns an error if a time or the size is not a number.
 #[inline]
-fn parse_tg_kv(line: &str, tg: &mut TextGrid) {
-    if let Some((key, value)) = parse_kv(line) {
-        match key {
-            "xmin" => tg.tmin = parse_float(value),
-            "xmax" => tg.tmax = parse_float(value),
-            "size" => tg.size = parse_uint(value),
-            _ => {}
+fn parse_tg_kv<'a>(
+    (key, value): (&'a str, &'a str),
+    line_number: usize,
+    tg: &mut TextGridRef<'a>,
+    options: &ParseOptions,
+) -> Result<()> {
+    match key {
+        "xmin" => tg.tmin = parse_float(value, line_number, options.accept_decimal_comma)?,
+        "xmax" => tg.tmax = parse_float(value, line_number, options.accept_decimal_comma)?,
+        "size" => tg.size = parse_uint(value, line_number)?,
+        "File type" | "Object class" => {}
+        _ if options.preserve_unknown_fields => tg.unknown_fields.push((key, value)),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Returns what a file ending in the given state is missing, if anything.
+///
+/// # Arguments
+///
+/// * `tg` - The TextGrid parsed so far
+/// * `state` - The state of the parser at the end of the file
+/// * `seen` - The keys read in the current header, tier or item
+/// * `skipped_tiers` - The number of tiers rejected by the tier filter
+fn missing_at_eof(
+    tg: &TextGridRef,
+    state: &State,
+    seen: &[&str],
+    skipped_tiers: usize,
+) -> Option<String> {
+    let missing = |keys: &[&'static str]| keys.iter().find(|key| !seen.contains(key)).copied();
+    let tier_number = tg.tiers.len() + skipped_tiers;
+    let tier = tg.tiers.last();
+    let item_kind = |tier: &TierRef| {
+        if tier.interval_tier {
+            "interval"
+        } else {
+            "point"
+        }
+    };
+    match (state, tier) {
+        (State::Header, _) => {
+            if let Some(key) = missing(&["xmin", "xmax"]) {
+                return Some(format!("`{}` of the TextGrid", key));
+            }
+        }
+        (State::Tier, Some(tier)) => {
+            let size_key = if tier.interval_tier {
+                "intervals: size"
+            } else {
+                "points: size"
+            };
+            if let Some(key) = missing(&["class", "name", "xmin", "xmax", size_key]) {
+                return Some(format!("`{}` of tier {}", key, tier_number));
+            }
         }
+        (State::Item, Some(tier)) => {
+            let keys: &[&str] = if tier.interval_tier {
+                &["xmin", "xmax", "text"]
+            } else {
+                &["number", "mark"]
+            };
+            if let Some(key) = missing(keys) {
+                return Some(format!(
+                    "`{}` of {} {} of tier {}",
+                    key,
+                    item_kind(tier),
+                    tier.items.len(),
+                    tier_number
+                ));
+            }
+        }
+        _ => {}
+    }
+    if let (State::Tier | State::Item, Some(tier)) = (state, tier)
+        && tier.items.len() < tier.size
+    {
+        return Some(format!(
+            "{} {} of tier {}",
+            item_kind(tier),
+            tier.items.len() + 1,
+            tier_number
+        ));
     }
+    (tier_number < tg.size).then(|| format!("tier {}", tier_number + 1))
 }
 
-/// Reads and parses a TextGrid file in long format.
+/// Parses the content of a TextGrid file in long format.
 ///
 /// # Arguments
 ///
-/// * `fname` - The path to the TextGrid file
+/// * `content` - The content of the TextGrid file
 /// * `strict` - Whether to perform strict validation on the parsed data
+/// * `tier_filter` - Returns whether to keep a tier given its name; the items of other tiers
+///   are skipped without being parsed, and the size of the TextGrid only counts kept tiers
+/// * `options` - The normalizations applied to labels, and whether to keep unknown keys
 ///
 /// # Returns
 ///
-/// Returns a `Result` containing the parsed `TextGrid` on success, or an error on failure.
+/// Returns a `Result` containing the parsed `TextGridRef`, whose strings borrow from
+/// `content`, on success, or an error on failure.
 ///
 /// # Errors
 ///
 /// Returns an error if:
-/// * The file cannot be read
-/// * The file content is invalid
+/// * The file content is invalid, e.g. a time is not a number, a tier class is unknown or an
+///   interval comes before any tier
+/// * The file ends before the TextGrid is complete, see [`ParseError::UnexpectedEof`]
+/// * A declared number of tiers or items differs from the number read (when `strict` is
+///   true; otherwise a warning is recorded)
 /// * Validation fails (when `strict` is true)
 ///
 /// # Examples
 ///
 /// ```no_run
-/// use textgrid::read_from_file;
+/// use textgrid::{FileType, read_from_str};
 ///
-/// // Read using explicit "long" format specifier
-/// let tg = read_from_file("example.TextGrid", true, "long").unwrap();
+/// // Parse the long format explicitly
+/// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+/// let tg = read_from_str(&content, true, FileType::Long).unwrap();
 /// println!("Loaded TextGrid with {} tiers", tg.tiers.len());
 /// ```
-pub(crate) fn read_from_file_long(fname: &str, strict: bool) -> Result<TextGrid> {
-    let content = std::fs::read_to_string(fname).unwrap();
-    let mut tg = TextGrid::new();
-    tg.name = std::path::Path::new(fname)
-        .file_stem()
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .to_string();
+pub(crate) fn read_from_str_long<'a>(
+    content: &'a str,
+    strict: bool,
+    tier_filter: &dyn Fn(&str) -> bool,
+    options: &ParseOptions,
+) -> Result<TextGridRef<'a>> {
+    let mut tg = TextGridRef::default();
     let mut state = State::Header;
-    for line in content.lines().map(|l| l.trim()) {
+    let mut skipped_tiers = 0;
+    // Keys read in the current header, tier or item, to name what a truncated file misses
+    let mut seen: Vec<&str> = Vec::new();
+    for (line_number, line) in logical_lines(content) {
         if line.starts_with("item []") {
             state = State::TierList;
         } else if line.starts_with("item [") {
             state = State::Tier;
-            tg.add_empty_tier();
+            tg.tiers.push(TierRef::default());
+            seen.clear();
+        } else if let State::SkippedTier = state {
+            continue;
         } else if line.starts_with("intervals [") || line.starts_with("points [") {
+            let Some(tier) = tg.tiers.last_mut() else {
+                return Err(data_error(&format!(
+                    "Line {}: {} before the first tier",
+                    line_number,
+                    line.trim_end_matches(':')
+                )));
+            };
             state = State::Item;
-            tg.tiers.last_mut().unwrap().add_empty_item();
-        } else {
-            // parse key-value pairs
+            tier.items.push(ItemRef::default());
+            seen.clear();
+        } else if let Some(kv @ (key, value)) = parse_kv(line) {
+            if value.starts_with('"') && value.bytes().filter(|&b| b == b'"').count() % 2 == 1 {
+                let expected = format!(
+                    "the closing quote of the string starting on line {}",
+                    line_number
+                );
+                return Err(ParseError::eof(content, expected).into());
+            }
+            seen.push(key);
             match state {
-                State::Header => parse_tg_kv(line, &mut tg),
-                State::Tier => parse_tier_kv(line, &mut tg.tiers.last_mut().unwrap()),
-                State::Item => parse_item_kv(
-                    line,
-                    &mut tg.tiers.last_mut().unwrap().items.last_mut().unwrap(),
-                ),
+                State::Header => parse_tg_kv(kv, line_number, &mut tg, options)?,
+                State::Tier => {
+                    let tier = tg.tiers.last_mut().unwrap();
+                    if parse_tier_kv(kv, line_number, tier, options)? && !tier_filter(&tier.name) {
+                        tg.tiers.pop();
+                        skipped_tiers += 1;
+                        state = State::SkippedTier;
+                    }
+                }
+                State::Item => {
+                    let item = tg.tiers.last_mut().unwrap().items.last_mut().unwrap();
+                    parse_item_kv(kv, line_number, item, options)?
+                }
                 // TierList has no key-value pairs
-                State::TierList => (),
+                State::TierList | State::SkippedTier => (),
             }
         }
     }
+    if let Some(expected) = missing_at_eof(&tg, &state, &seen, skipped_tiers) {
+        return Err(ParseError::eof(content, expected).into());
+    }
+    tg.size = tg.size.saturating_sub(skipped_tiers);
+    tg.check_sizes(strict)?;
+    if options.clamp_to_bounds {
+        tg.clamp_to_bounds();
+    }
+    tg.apply_zero_length_policy(options.zero_length);
     if strict {
-        tg.assert_valid()?;
+        tg.assert_valid_with(options.zero_length)?;
     }
     Ok(tg)
 }
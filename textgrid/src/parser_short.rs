@@ -1,118 +1,300 @@
 //! Parser for short-format TextGrid files.
 //!
 //! This module provides functionality to parse TextGrid files in the short format,
-//! which uses a more compact representation without explicit key-value pairs.
+//! which uses a more compact representation without explicit key-value pairs. Values are read
+//! one after the other whatever the lines they are on, as Praat does.
 
-use crate::textgrid::*;
+use crate::borrowed::{ItemRef, TextGridRef, TierRef};
+use crate::parse_error::ParseError;
+use crate::parse_options::ParseOptions;
+use crate::textgrid::data_error;
 use crate::utils::{parse_float, parse_str, parse_uint};
 use std::io::Result;
 
-/// Parses a single tier from the short format lines.
+/// The values of a short-format file, separated by any whitespace.
+///
+/// Praat reads text files value by value rather than line by line, so blank lines, trailing
+/// whitespace and values sharing a line are all accepted.
+#[derive(Clone)]
+struct Tokens<'a> {
+    /// The content after the last value read.
+    rest: &'a str,
+    /// The 1-based number of the line `rest` starts on.
+    line_number: usize,
+    /// Whether to accept decimal commas in numbers.
+    decimal_comma: bool,
+}
+
+impl<'a> Tokens<'a> {
+    /// Starts reading the content after its `File type` and `Object class` header lines.
+    fn new(content: &'a str, options: &ParseOptions) -> Self {
+        let mut tokens = Tokens {
+            rest: content.strip_prefix('\u{feff}').unwrap_or(content),
+            line_number: 1,
+            decimal_comma: options.accept_decimal_comma,
+        };
+        loop {
+            tokens.skip_whitespace();
+            if !(tokens.rest.starts_with("File type") || tokens.rest.starts_with("Object class")) {
+                return tokens;
+            }
+            let end = tokens.rest.find('\n').unwrap_or(tokens.rest.len());
+            tokens.rest = &tokens.rest[end..];
+        }
+    }
+
+    /// Advances past whitespace, counting the lines skipped.
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest.trim_start();
+        let skipped = &self.rest[..self.rest.len() - trimmed.len()];
+        self.line_number += skipped.matches('\n').count();
+        self.rest = trimmed;
+    }
+
+    /// Reads the next value with the number of the line it starts on.
+    ///
+    /// A quoted string is returned with its quotes, up to the first quote that is not doubled,
+    /// so it may contain whitespace and span several lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError::UnexpectedEof`] if the file ends before the value, or within a
+    /// quoted string.
+    fn next(&mut self, expected: &str) -> Result<(usize, &'a str)> {
+        self.skip_whitespace();
+        if self.rest.is_empty() {
+            return Err(ParseError::UnexpectedEof {
+                expected: expected.to_string(),
+                at_line: self.line_number,
+            }
+            .into());
+        }
+        let end = if self.rest.starts_with('"') {
+            let bytes = self.rest.as_bytes();
+            let mut from = 1;
+            loop {
+                match bytes[from..].iter().position(|&b| b == b'"') {
+                    Some(offset) if bytes.get(from + offset + 1) == Some(&b'"') => {
+                        from += offset + 2
+                    }
+                    Some(offset) => break from + offset + 1,
+                    None => {
+                        return Err(ParseError::UnexpectedEof {
+                            expected: format!(
+                                "the closing quote of the string starting on line {}",
+                                self.line_number
+                            ),
+                            at_line: self.line_number + self.rest.matches('\n').count(),
+                        }
+                        .into());
+                    }
+                }
+            }
+        } else {
+            self.rest
+                .find(char::is_whitespace)
+                .unwrap_or(self.rest.len())
+        };
+        let (token, rest) = self.rest.split_at(end);
+        let line_number = self.line_number;
+        self.line_number += token.matches('\n').count();
+        self.rest = rest;
+        Ok((line_number, token))
+    }
+
+    /// Returns whether the next value is a number, which starts an item.
+    fn at_number(&self) -> bool {
+        self.clone().number("").is_ok()
+    }
+
+    /// Returns whether the next value is the class of a tier, which starts a new tier.
+    fn at_tier_class(&mut self) -> bool {
+        self.skip_whitespace();
+        self.rest.starts_with("\"IntervalTier\"") || self.rest.starts_with("\"TextTier\"")
+    }
+
+    /// Reads a finite number.
+    fn number(&mut self, expected: &str) -> Result<f64> {
+        let (line_number, token) = self.next(expected)?;
+        parse_float(token, line_number, self.decimal_comma)
+    }
+
+    /// Reads an unsigned integer.
+    fn count(&mut self, expected: &str) -> Result<usize> {
+        let (line_number, token) = self.next(expected)?;
+        parse_uint(token, line_number)
+    }
+
+    /// Reads a string, with its quotes.
+    fn string(&mut self, expected: &str) -> Result<&'a str> {
+        Ok(self.next(expected)?.1)
+    }
+}
+
+/// Parses a single tier from the values of a short-format file.
 ///
 /// # Arguments
 ///
-/// * `lines` - A slice of all lines in the file
-/// * `start_index` - The index where the tier data starts
+/// * `tokens` - The values of the file, positioned at the class of the tier
+/// * `tier_number` - The 1-based number of the tier in the file, for error messages
+/// * `tier_filter` - Returns whether to keep the tier given its name
+/// * `options` - The normalizations applied to labels
 ///
 /// # Returns
 ///
-/// Returns a tuple containing:
-/// * The parsed `Tier`
-/// * The index of the next line after this tier's data
+/// Returns the parsed `TierRef`, or `None` if the tier filter rejected it. The values of the
+/// tier are consumed either way.
 ///
-/// # Panics
+/// Items are read until the next tier or the last number rather than by the declared size,
+/// which is kept in the tier for [`TextGridRef::check_sizes`] to compare with the items read.
+/// A file ending before the declared number of items is still an error.
+///
+/// # Errors
 ///
-/// Panics if an unknown tier class is encountered.
+/// Returns an error if a time or the size is not a number or the class is unknown, or a
+/// [`ParseError::UnexpectedEof`] if the file ends within the tier.
 #[inline]
-fn parse_tier(lines: &[&str], start_index: usize) -> (Tier, usize) {
-    let mut tier = Tier::new();
-    tier.interval_tier = match lines[start_index].trim_matches('"') {
+fn parse_tier<'a>(
+    tokens: &mut Tokens<'a>,
+    tier_number: usize,
+    tier_filter: &dyn Fn(&str) -> bool,
+    options: &ParseOptions,
+) -> Result<Option<TierRef<'a>>> {
+    let (line_number, class) = tokens.next(&format!("tier {}", tier_number))?;
+    let interval_tier = match class.trim_matches('"') {
         "IntervalTier" => true,
         "TextTier" => false,
         _ => {
-            panic!("Unknown tier class: {}", lines[start_index]);
+            return Err(data_error(&format!(
+                "Line {}: unknown tier class {}",
+                line_number, class
+            )));
         }
     };
-    tier.name = parse_str(lines[start_index + 1]);
-    tier.tmin = parse_float(lines[start_index + 2]);
-    tier.tmax = parse_float(lines[start_index + 3]);
-    tier.size = parse_uint(lines[start_index + 4]);
-    let mut cursor = start_index + 5;
-    for _ in 0..tier.size {
-        let item: Item;
-        if tier.interval_tier {
-            item = Item {
-                tmin: parse_float(lines[cursor]),
-                tmax: parse_float(lines[cursor + 1]),
-                label: parse_str(lines[cursor + 2]),
-            };
-            cursor += 3;
+    let of_tier = |what: &str| format!("{} of tier {}", what, tier_number);
+    let name = parse_str(tokens.string(&of_tier("the name"))?);
+    let tmin = tokens.number(&of_tier("the start time"))?;
+    let tmax = tokens.number(&of_tier("the end time"))?;
+    let size = tokens.count(&of_tier("the size"))?;
+    let keep = tier_filter(&name);
+    let mut tier = TierRef {
+        name,
+        size,
+        items: Vec::new(),
+        interval_tier,
+        tmin,
+        tmax,
+        unknown_fields: Vec::new(),
+    };
+    let mut index = 0;
+    loop {
+        index += 1;
+        if tokens.at_tier_class() || (index > size && !tokens.at_number()) {
+            break;
+        }
+        let of_item = |what: &str, kind: &str| {
+            format!("{} of {} {} of tier {}", what, kind, index, tier_number)
+        };
+        let item = if interval_tier {
+            ItemRef {
+                tmin: tokens.number(&of_item("the start time", "interval"))?,
+                tmax: tokens.number(&of_item("the end time", "interval"))?,
+                label: parse_str(tokens.string(&of_item("the text", "interval"))?),
+            }
         } else {
-            let number = parse_float(lines[cursor]);
-            item = Item {
-                tmin: number,
-                tmax: number,
-                label: parse_str(lines[cursor + 1]),
-            };
-            cursor += 2;
+            let time = tokens.number(&of_item("the time", "point"))?;
+            ItemRef {
+                tmin: time,
+                tmax: time,
+                label: parse_str(tokens.string(&of_item("the mark", "point"))?),
+            }
+        };
+        if keep {
+            tier.items.push(ItemRef {
+                label: options.normalize_label(item.label),
+                ..item
+            });
         }
-        tier.items.push(item);
     }
-    (tier, cursor)
+    Ok(keep.then_some(tier))
 }
 
-/// Reads and parses a TextGrid file in short format.
+/// Parses the content of a TextGrid file in short format.
 ///
 /// # Arguments
 ///
-/// * `fname` - The path to the TextGrid file
+/// * `content` - The content of the TextGrid file
 /// * `strict` - Whether to perform strict validation on the parsed data
+/// * `tier_filter` - Returns whether to keep a tier given its name; the items of other tiers
+///   are read but not kept, and the size of the TextGrid only counts kept tiers
+/// * `options` - The normalizations applied to labels, and whether to accept decimal commas
 ///
 /// # Returns
 ///
-/// Returns a `Result` containing the parsed `TextGrid` on success, or an error on failure.
+/// Returns a `Result` containing the parsed `TextGridRef`, whose strings borrow from
+/// `content`, on success, or an error on failure.
 ///
 /// # Errors
 ///
 /// Returns an error if:
-/// * The file cannot be read
-/// * The file content is invalid
+/// * The file content is invalid, e.g. a time is not a number or a tier class is unknown
+/// * The file ends before the TextGrid is complete, see [`ParseError::UnexpectedEof`]
+/// * A declared number of tiers or items differs from the number read (when `strict` is
+///   true; otherwise a warning is recorded)
 /// * Validation fails (when `strict` is true)
 ///
 /// # Examples
 ///
 /// ```no_run
-/// use textgrid::read_from_file;
+/// use textgrid::{FileType, read_from_str};
 ///
-/// // Read using explicit "short" format specifier
-/// let tg = read_from_file("example.TextGrid", false, "short").unwrap();
+/// // Parse the short format explicitly
+/// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+/// let tg = read_from_str(&content, false, FileType::Short).unwrap();
 /// println!("Loaded TextGrid with {} tiers", tg.tiers.len());
 /// ```
-pub(crate) fn read_from_file_short(fname: &str, strict: bool) -> Result<TextGrid> {
-    let content = std::fs::read_to_string(fname).unwrap();
-    let mut tg = TextGrid::new();
-    tg.name = std::path::Path::new(fname)
-        .file_stem()
-        .unwrap()
-        .to_str()
-        .unwrap()
-        .to_string();
+pub(crate) fn read_from_str_short<'a>(
+    content: &'a str,
+    strict: bool,
+    tier_filter: &dyn Fn(&str) -> bool,
+    options: &ParseOptions,
+) -> Result<TextGridRef<'a>> {
+    let mut tg = TextGridRef::default();
+    let mut tokens = Tokens::new(content, options);
 
-    let lines: Vec<&str> = content.lines().map(|l| l.trim()).collect();
-
-    tg.tmin = parse_float(lines[3]);
-    tg.tmax = parse_float(lines[4]);
-    tg.size = parse_uint(lines[6]);
+    tg.tmin = tokens.number("the start time of the TextGrid")?;
+    tg.tmax = tokens.number("the end time of the TextGrid")?;
+    let (line_number, tiers) = tokens.next("<exists> or <absent>")?;
+    tg.size = match tiers {
+        "<exists>" => tokens.count("the number of tiers")?,
+        "<absent>" => 0,
+        _ => {
+            return Err(data_error(&format!(
+                "Line {}: expected <exists> or <absent>, found {}",
+                line_number, tiers
+            )));
+        }
+    };
 
-    let mut cursor = 7;
-    for _ in 0..tg.size {
-        let (tier, next_cursor) = parse_tier(&lines, cursor);
-        tg.tiers.push(tier);
-        cursor = next_cursor;
+    let declared = tg.size;
+    let mut skipped_tiers = 0;
+    let mut tier_number = 0;
+    while tier_number < declared || tokens.at_tier_class() {
+        tier_number += 1;
+        match parse_tier(&mut tokens, tier_number, tier_filter, options)? {
+            Some(tier) => tg.tiers.push(tier),
+            None => skipped_tiers += 1,
+        }
     }
+    tg.size = declared.saturating_sub(skipped_tiers);
 
+    tg.check_sizes(strict)?;
+    if options.clamp_to_bounds {
+        tg.clamp_to_bounds();
+    }
+    tg.apply_zero_length_policy(options.zero_length);
     if strict {
-        tg.assert_valid()?;
+        tg.assert_valid_with(options.zero_length)?;
     }
     Ok(tg)
 }
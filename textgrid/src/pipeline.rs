@@ -0,0 +1,350 @@
+//! Batch processing of corpora described by TOML or YAML pipeline files.
+//!
+//! A pipeline reads every TextGrid of a corpus, repairs and validates it, applies a list of
+//! steps and writes the result to an output directory, so that a recurring corpus-preparation
+//! job is kept in a file next to the corpus instead of in a script.
+
+use crate::corpus::find_files;
+use crate::file_type::FileType;
+use crate::parse_options::ParseOptions;
+use crate::textgrid::{TextGrid, data_error, input_error};
+use crate::utils::fast_map;
+use crate::validation::ValidationOptions;
+use crate::writer::{TextGridFormat, WriteOptions};
+use crate::{file_error, read_from_file_with};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+
+/// A batch job read by [`PipelineConfig::from_toml`] or [`PipelineConfig::from_yaml`] and run
+/// by [`run_pipeline`].
+///
+/// # Examples
+///
+/// ```toml
+/// inputs = "corpus/**/*.TextGrid"
+/// validation = "pedantic"
+///
+/// [repair]
+/// trim_labels = true
+/// normalize = true
+///
+/// [[steps]]
+/// op = "shift"
+/// offset = -0.5
+/// clamp = true
+///
+/// [[steps]]
+/// op = "relabel"
+/// tiers = ["phones"]
+/// pattern = "ʦ"
+/// replace = "ts"
+///
+/// [[steps]]
+/// op = "fill_gaps"
+///
+/// [output]
+/// dir = "prepared"
+/// format = "short"
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfig {
+    /// A directory, read with its subdirectories, or a glob pattern, as in
+    /// [`read_corpus`](crate::read_corpus).
+    pub inputs: String,
+    /// Whether to perform strict validation when reading the files.
+    #[serde(default)]
+    pub strict: bool,
+    /// Repairs applied to each TextGrid while and after reading it.
+    #[serde(default)]
+    pub repair: RepairConfig,
+    /// The steps applied to each TextGrid, in order.
+    #[serde(default)]
+    pub steps: Vec<PipelineStep>,
+    /// Validation of each TextGrid after its steps; a file with errors is not written.
+    #[serde(default)]
+    pub validation: ValidationLevel,
+    /// Where and how the TextGrids are written.
+    pub output: OutputConfig,
+}
+
+/// Repairs of a [`PipelineConfig`], all disabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RepairConfig {
+    /// Whether to remove leading and trailing whitespace from labels, see [`ParseOptions`].
+    pub trim_labels: bool,
+    /// Whether to replace every run of whitespace in labels with a single space.
+    pub collapse_whitespace: bool,
+    /// Whether to convert labels to Unicode Normalization Form C.
+    pub nfc_normalize: bool,
+    /// Whether to read a comma in a time as a decimal point.
+    pub accept_decimal_comma: bool,
+    /// Whether to truncate items extending beyond their tier to its bounds.
+    pub clamp_to_bounds: bool,
+    /// Whether to sort items and widen bounds with [`TextGrid::normalize`].
+    pub normalize: bool,
+}
+
+/// A transformation applied by a [`PipelineConfig`], tagged by `op` in the file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum PipelineStep {
+    /// Shifts all times by an offset, see [`TextGrid::shift`].
+    Shift {
+        /// The offset in seconds.
+        offset: f64,
+        /// Whether to clamp negative times to zero.
+        #[serde(default)]
+        clamp: bool,
+    },
+    /// Replaces a regular expression in the labels, see [`TextGrid::map_labels`].
+    Relabel {
+        /// The names of the tiers to relabel; all tiers if empty.
+        #[serde(default)]
+        tiers: Vec<String>,
+        /// The regular expression to search for.
+        pattern: String,
+        /// The replacement, which may refer to capture groups as `$1` or `${name}`.
+        replace: String,
+    },
+    /// Fills the gaps between intervals with new intervals, see [`Tier::fill_gaps`].
+    ///
+    /// [`Tier::fill_gaps`]: crate::Tier::fill_gaps
+    FillGaps {
+        /// The names of the tiers to fill; all interval tiers if empty.
+        #[serde(default)]
+        tiers: Vec<String>,
+        /// The label of the new intervals.
+        #[serde(default)]
+        label: String,
+    },
+}
+
+/// How strictly a [`PipelineConfig`] validates its output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationLevel {
+    /// No validation.
+    None,
+    /// The checks of [`TextGrid::validate`].
+    #[default]
+    Default,
+    /// Also require interval tiers to cover their bounds without gaps, see
+    /// [`ValidationOptions::pedantic`].
+    Pedantic,
+}
+
+/// The output of a [`PipelineConfig`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputConfig {
+    /// The directory the TextGrids are written to, at their path relative to the inputs.
+    pub dir: String,
+    /// The format of the written TextGrids.
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// The format of the TextGrids written by a [`PipelineConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputFormat {
+    /// Long TextGrid format.
+    #[default]
+    Long,
+    /// Short TextGrid format.
+    Short,
+}
+
+impl PipelineConfig {
+    /// Parses a pipeline from TOML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error if the content is not a valid pipeline.
+    pub fn from_toml(content: &str) -> Result<PipelineConfig> {
+        toml::from_str(content).map_err(|e| data_error(&format!("Invalid pipeline: {}", e)))
+    }
+
+    /// Parses a pipeline from YAML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error if the content is not a valid pipeline.
+    pub fn from_yaml(content: &str) -> Result<PipelineConfig> {
+        serde_yaml::from_str(content).map_err(|e| data_error(&format!("Invalid pipeline: {}", e)))
+    }
+
+    /// Reads a pipeline file, in YAML if its extension is `.yaml` or `.yml` and in TOML
+    /// otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a valid pipeline.
+    pub fn from_file(path: &str) -> Result<PipelineConfig> {
+        let content = std::fs::read_to_string(path)?;
+        let is_yaml = Path::new(path)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+        if is_yaml {
+            PipelineConfig::from_yaml(&content)
+        } else {
+            PipelineConfig::from_toml(&content)
+        }
+        .map_err(|e| file_error(path, e))
+    }
+}
+
+/// A step with its regular expressions compiled.
+enum Step<'a> {
+    Shift(f64, bool),
+    Relabel(Option<Regex>, Regex, &'a str),
+    FillGaps(Option<Regex>, &'a str),
+}
+
+/// Compiles a filter matching the exact names of tiers, or `None` to match all tiers.
+fn tier_filter(tiers: &[String]) -> Option<Regex> {
+    if tiers.is_empty() {
+        return None;
+    }
+    let names: Vec<String> = tiers.iter().map(|name| regex::escape(name)).collect();
+    Regex::new(&format!("^(?:{})$", names.join("|"))).ok()
+}
+
+impl<'a> Step<'a> {
+    fn compile(step: &'a PipelineStep) -> Result<Step<'a>> {
+        Ok(match step {
+            PipelineStep::Shift { offset, clamp } => Step::Shift(*offset, *clamp),
+            PipelineStep::Relabel {
+                tiers,
+                pattern,
+                replace,
+            } => {
+                let pattern = Regex::new(pattern)
+                    .map_err(|e| input_error(&format!("Invalid relabel pattern: {}", e)))?;
+                Step::Relabel(tier_filter(tiers), pattern, replace)
+            }
+            PipelineStep::FillGaps { tiers, label } => Step::FillGaps(tier_filter(tiers), label),
+        })
+    }
+
+    fn apply(&self, tg: &mut TextGrid) -> Result<()> {
+        match self {
+            Step::Shift(offset, clamp) => tg.shift(*offset, *clamp)?,
+            Step::Relabel(tiers, pattern, replace) => tg.map_labels(
+                |label| pattern.replace_all(label, *replace).into_owned(),
+                tiers.as_ref(),
+            ),
+            Step::FillGaps(tiers, label) => {
+                for tier in tg.tiers.iter_mut() {
+                    match tiers {
+                        Some(filter) if filter.is_match(&tier.name) => tier.fill_gaps(label)?,
+                        None if tier.interval_tier => tier.fill_gaps(label)?,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads, processes, validates and writes one file of [`run_pipeline`].
+fn run_file(input: &Path, output: &Path, config: &PipelineConfig, steps: &[Step]) -> Result<()> {
+    let fname = input
+        .to_str()
+        .ok_or_else(|| input_error("path is not valid UTF-8"))?;
+    let repair = &config.repair;
+    let parse_options = ParseOptions {
+        trim_labels: repair.trim_labels,
+        collapse_whitespace: repair.collapse_whitespace,
+        nfc_normalize: repair.nfc_normalize,
+        accept_decimal_comma: repair.accept_decimal_comma,
+        clamp_to_bounds: repair.clamp_to_bounds,
+        ..Default::default()
+    };
+    let mut tg = read_from_file_with(fname, config.strict, FileType::Auto, &parse_options)?;
+    if repair.normalize {
+        tg.normalize();
+    }
+    for step in steps {
+        step.apply(&mut tg)?;
+    }
+    let validation = match config.validation {
+        ValidationLevel::None => None,
+        ValidationLevel::Default => Some(ValidationOptions::default()),
+        ValidationLevel::Pedantic => Some(ValidationOptions {
+            pedantic: true,
+            ..Default::default()
+        }),
+    };
+    if let Some(options) = validation {
+        tg.validate_with(&options).into_result()?;
+    }
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let output = output
+        .to_str()
+        .ok_or_else(|| input_error("output path is not valid UTF-8"))?;
+    let format = match config.output.format {
+        OutputFormat::Long => TextGridFormat::Long,
+        OutputFormat::Short => TextGridFormat::Short,
+    };
+    tg.save_textgrid(output, format, &WriteOptions::default())
+}
+
+/// Runs a pipeline over all TextGrid files of its inputs in parallel.
+///
+/// Each file is read with the label repairs of the pipeline, normalized if requested, then
+/// transformed by its steps in order, validated at its validation level and written to the
+/// same relative path under the output directory. A file that fails to be read, transformed
+/// or validated is not written.
+///
+/// # Returns
+///
+/// Returns, for each input file keyed by relative path, the path of the written file or the
+/// error that prevented it from being written.
+///
+/// # Errors
+///
+/// Returns an error if a relabel pattern is invalid, or if the input directory cannot be
+/// listed or the pattern is invalid. Errors of individual files are reported in the returned
+/// map instead.
+///
+/// # Examples
+///
+/// ```no_run
+/// use textgrid::{PipelineConfig, run_pipeline};
+///
+/// let config = PipelineConfig::from_file("pipeline.toml").unwrap();
+/// for (path, result) in run_pipeline(&config).unwrap() {
+///     if let Err(e) = result {
+///         eprintln!("{}: {}", path.display(), e);
+///     }
+/// }
+/// ```
+pub fn run_pipeline(config: &PipelineConfig) -> Result<BTreeMap<PathBuf, Result<PathBuf>>> {
+    let steps = config
+        .steps
+        .iter()
+        .map(Step::compile)
+        .collect::<Result<Vec<_>>>()?;
+    let (base, files) = find_files(&config.inputs, true)?;
+    let map_fun = |input: &PathBuf| {
+        let relative = input.strip_prefix(&base).unwrap_or(input);
+        let output = Path::new(&config.output.dir).join(relative);
+        match run_file(input, &output, config, &steps) {
+            Ok(()) => (relative.to_path_buf(), Ok(output)),
+            Err(e) => {
+                let e = file_error(&input.display().to_string(), e);
+                (relative.to_path_buf(), Err(e))
+            }
+        }
+    };
+    Ok(fast_map(&files, map_fun).into_iter().collect())
+}
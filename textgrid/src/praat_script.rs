@@ -0,0 +1,161 @@
+//! Generation of Praat scripts that rebuild a TextGrid inside Praat.
+
+use crate::textgrid::{TIME_EPSILON, TextGrid, Tier, input_error};
+use std::fmt::Write;
+use std::io::Result;
+
+/// What a script generated by [`TextGrid::to_praat_script`] does in Praat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PraatScriptMode {
+    /// Create a new TextGrid object with the same domain, tiers and items, named after the
+    /// TextGrid.
+    Create,
+    /// Append the tiers to the TextGrid object selected when the script runs, e.g. one created
+    /// from a Sound with `To TextGrid...`. The selected TextGrid should have the same domain.
+    Modify,
+}
+
+/// Formats a string as a Praat string expression: quotes are doubled and line breaks are
+/// spliced in with `newline$`.
+fn praat_string(text: &str) -> String {
+    text.split('\n')
+        .map(|line| format!("\"{}\"", line.trim_end_matches('\r').replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" + newline$ + ")
+}
+
+/// Appends the commands filling the tier at index `tier` of the script's TextGrid.
+fn push_items(script: &mut String, tier: &str, source: &Tier, tg: &TextGrid) {
+    if !source.interval_tier {
+        for item in &source.items {
+            let _ = writeln!(
+                script,
+                "Insert point: {}, {}, {}",
+                tier,
+                item.tmin,
+                praat_string(&item.label)
+            );
+        }
+        return;
+    }
+    let mut boundaries: Vec<f64> = source
+        .items
+        .iter()
+        .flat_map(|item| [item.tmin, item.tmax])
+        .filter(|&time| time - tg.tmin > TIME_EPSILON && tg.tmax - time > TIME_EPSILON)
+        .collect();
+    boundaries.sort_by(f64::total_cmp);
+    boundaries.dedup_by(|later, kept| *later - *kept <= TIME_EPSILON);
+    for time in &boundaries {
+        let _ = writeln!(script, "Insert boundary: {}, {}", tier, time);
+    }
+    for item in source.items.iter().filter(|item| !item.label.is_empty()) {
+        let interval = 1 + boundaries.partition_point(|&time| time <= item.tmin + TIME_EPSILON);
+        let _ = writeln!(
+            script,
+            "Set interval text: {}, {}, {}",
+            tier,
+            interval,
+            praat_string(&item.label)
+        );
+    }
+}
+
+impl TextGrid {
+    /// Generates a Praat script that recreates the annotation inside Praat, for pipelines whose
+    /// last step must run in Praat itself, e.g. to pair the annotation with a Sound object.
+    ///
+    /// The script uses the commands of the TextGrid editor: `Insert boundary`,
+    /// `Set interval text` and `Insert point`. In Praat, every tier spans the whole domain of
+    /// the TextGrid, so gaps in interval tiers become intervals with an empty label, and items
+    /// outside the domain of the TextGrid make the script fail.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - Whether the script creates a new TextGrid or adds tiers to the selected one
+    ///
+    /// # Errors
+    ///
+    /// Returns an error in [`PraatScriptMode::Create`] mode if the TextGrid has no tiers, since
+    /// Praat cannot create such a TextGrid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{PraatScriptMode, TextGrid};
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .name("greeting")
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.5, "hello").interval(0.5, 1.0, "world"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let script = tg.to_praat_script(PraatScriptMode::Create).unwrap();
+    /// assert!(script.contains("Insert boundary: 1, 0.5\n"));
+    /// assert!(script.contains("Set interval text: 1, 2, \"world\"\n"));
+    /// ```
+    pub fn to_praat_script(&self, mode: PraatScriptMode) -> Result<String> {
+        let mut script = String::new();
+        let tier_variable = match mode {
+            PraatScriptMode::Create => {
+                if self.tiers.is_empty() {
+                    return Err(input_error(
+                        "A TextGrid without tiers cannot be created in Praat",
+                    ));
+                }
+                let placeholders = |interval_tier: bool| {
+                    self.tiers
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, tier)| interval_tier || !tier.interval_tier)
+                        .map(|(index, _)| format!("tier{}", index + 1))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                };
+                let _ = writeln!(
+                    script,
+                    "Create TextGrid: {}, {}, \"{}\", \"{}\"",
+                    self.tmin,
+                    self.tmax,
+                    placeholders(true),
+                    placeholders(false)
+                );
+                if !self.name.is_empty() {
+                    let _ = writeln!(script, "Rename: {}", praat_string(&self.name));
+                }
+                for (index, tier) in self.tiers.iter().enumerate() {
+                    let _ = writeln!(
+                        script,
+                        "Set tier name: {}, {}",
+                        index + 1,
+                        praat_string(&tier.name)
+                    );
+                }
+                None
+            }
+            PraatScriptMode::Modify => {
+                let _ = writeln!(script, "n_tiers = Get number of tiers");
+                for (index, tier) in self.tiers.iter().enumerate() {
+                    let command = if tier.interval_tier {
+                        "Insert interval tier"
+                    } else {
+                        "Insert point tier"
+                    };
+                    let _ = writeln!(
+                        script,
+                        "{}: n_tiers + {}, {}",
+                        command,
+                        index + 1,
+                        praat_string(&tier.name)
+                    );
+                }
+                Some("n_tiers + ")
+            }
+        };
+        for (index, tier) in self.tiers.iter().enumerate() {
+            let tier_number = format!("{}{}", tier_variable.unwrap_or_default(), index + 1);
+            push_items(&mut script, &tier_number, tier, self);
+        }
+        Ok(script)
+    }
+}
@@ -0,0 +1,631 @@
+//! Lookup and query helpers for TextGrids and tiers.
+
+use crate::textgrid::{Item, TIME_EPSILON, TextGrid, Tier};
+use regex::Regex;
+use std::ops::{Index, IndexMut, Range};
+
+/// A label matched by a regular expression search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelMatch {
+    /// Name of the tier containing the item.
+    pub tier_name: String,
+    /// Index of the item within its tier.
+    pub item_index: usize,
+    /// Start time of the item.
+    pub tmin: f64,
+    /// End time of the item.
+    pub tmax: f64,
+    /// Full label of the item.
+    pub label: String,
+    /// Capture groups of the first match in the label, starting with the whole match.
+    /// Groups that did not participate in the match are `None`.
+    pub groups: Vec<Option<String>>,
+}
+
+/// A run of consecutive items matched by [`Tier::find_sequence`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceMatch {
+    /// Indices of the matched items within their tier.
+    pub items: Range<usize>,
+    /// Start time of the first matched item.
+    pub tmin: f64,
+    /// End time of the last matched item.
+    pub tmax: f64,
+}
+
+/// Something a label can match, used by [`Tier::extract_matching`] and
+/// [`Tier::find_sequence`].
+///
+/// A string matches labels equal to it, and a [`Regex`] matches labels it finds a match in,
+/// anywhere unless anchored. References and `&dyn LabelPattern` match like the pattern they
+/// point to, so exact and regex patterns can be mixed in a slice.
+pub trait LabelPattern {
+    /// Returns `true` if the label matches.
+    fn matches_label(&self, label: &str) -> bool;
+}
+
+impl LabelPattern for str {
+    fn matches_label(&self, label: &str) -> bool {
+        self == label
+    }
+}
+
+impl LabelPattern for String {
+    fn matches_label(&self, label: &str) -> bool {
+        self == label
+    }
+}
+
+impl LabelPattern for Regex {
+    fn matches_label(&self, label: &str) -> bool {
+        self.is_match(label)
+    }
+}
+
+impl<P: LabelPattern + ?Sized> LabelPattern for &P {
+    fn matches_label(&self, label: &str) -> bool {
+        (**self).matches_label(label)
+    }
+}
+
+impl Tier {
+    /// Returns `true` if the items are sorted by time, as the time lookups of the tier assume.
+    ///
+    /// Items must be sorted by start time and, in interval tiers, by end time as well. Parsed
+    /// and built tiers are sorted when their intervals do not overlap, and [`Tier::normalize`]
+    /// sorts the items of any tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, Tier};
+    ///
+    /// let mut tier = Tier::new_interval("words", 0.0, 1.0);
+    /// tier.items.push(Item::interval(0.5, 1.0, "world"));
+    /// tier.items.push(Item::interval(0.0, 0.5, "hello"));
+    /// assert!(!tier.is_sorted());
+    /// tier.normalize();
+    /// assert!(tier.is_sorted());
+    /// ```
+    pub fn is_sorted(&self) -> bool {
+        self.items.windows(2).all(|pair| {
+            pair[0].tmin <= pair[1].tmin && (!self.interval_tier || pair[0].tmax <= pair[1].tmax)
+        })
+    }
+
+    /// Finds the index of the item at a time point with a binary search.
+    ///
+    /// For interval tiers, this is the interval with `tmin <= time < tmax`, or else the interval
+    /// whose `tmax` equals `time`: a boundary belongs to the interval starting there, and the end
+    /// of the last interval, or of an interval followed by a gap, to the interval ending there.
+    /// For point tiers, this is the point nearest to `time`, provided it lies within a small
+    /// epsilon of it.
+    ///
+    /// The items must be sorted, see [`Tier::is_sorted`]; the result is unspecified otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time point to look up
+    ///
+    /// # Returns
+    ///
+    /// Returns the index of the item if one is found, otherwise `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, Tier};
+    ///
+    /// let mut tier = Tier::new_interval("words", 0.0, 1.0);
+    /// tier.push_item(Item::interval(0.0, 0.5, "hello"));
+    /// tier.push_item(Item::interval(0.5, 1.0, "world"));
+    ///
+    /// assert_eq!(tier.index_at_time(0.5), Some(1));
+    /// assert_eq!(tier.index_at_time(1.0), Some(1));
+    /// assert_eq!(tier.index_at_time(1.5), None);
+    ///
+    /// // The start of a gap belongs to the interval before it
+    /// tier.tmax = 2.0;
+    /// tier.push_item(Item::interval(1.5, 2.0, "again"));
+    /// assert_eq!(tier.index_at_time(1.0), Some(1));
+    /// assert_eq!(tier.index_at_time(1.2), None);
+    /// ```
+    pub fn index_at_time(&self, time: f64) -> Option<usize> {
+        let items = &self.items;
+        if self.interval_tier {
+            let index = items.partition_point(|item| item.tmax <= time);
+            if index < items.len() && items[index].tmin <= time {
+                return Some(index);
+            }
+            let last = items.partition_point(|item| item.tmax - time <= TIME_EPSILON);
+            (last > 0 && (items[last - 1].tmax - time).abs() <= TIME_EPSILON).then(|| last - 1)
+        } else {
+            let start = items.partition_point(|item| item.tmin < time - TIME_EPSILON);
+            items[start..]
+                .iter()
+                .take_while(|item| item.tmin <= time + TIME_EPSILON)
+                .enumerate()
+                .map(|(offset, item)| (start + offset, (item.tmin - time).abs()))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(index, _)| index)
+        }
+    }
+
+    /// Finds the item at a time point.
+    ///
+    /// See [`Tier::index_at_time`], which this looks the item up with.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time point to look up
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some((index, item))` if an item is found, otherwise `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(
+    ///     String::from("words"),
+    ///     true,
+    ///     vec![(0.0, 0.5, String::from("hello")), (0.5, 1.0, String::from("world"))],
+    /// )];
+    /// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// let (index, item) = tg.tiers[0].item_at(0.7).unwrap();
+    /// assert_eq!(index, 1);
+    /// assert_eq!(item.label, "world");
+    /// assert!(tg.tiers[0].item_at(1.5).is_none());
+    /// ```
+    pub fn item_at(&self, time: f64) -> Option<(usize, &Item)> {
+        self.index_at_time(time)
+            .map(|index| (index, &self.items[index]))
+    }
+
+    /// Finds all items within a time window `[t0, t1]`.
+    ///
+    /// The first item is found with a binary search, so the items must be sorted, see
+    /// [`Tier::is_sorted`].
+    ///
+    /// # Arguments
+    ///
+    /// * `t0` - Start of the window
+    /// * `t1` - End of the window
+    /// * `strict` - If `true`, only items lying entirely inside the window are returned;
+    ///   if `false`, every item overlapping the window is returned. Intervals that merely
+    ///   touch the window at a boundary do not overlap it.
+    ///
+    /// # Returns
+    ///
+    /// Returns the matching items with their indices, in tier order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(
+    ///     String::from("words"),
+    ///     true,
+    ///     vec![(0.0, 0.5, String::from("hello")), (0.5, 1.0, String::from("world"))],
+    /// )];
+    /// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// assert_eq!(tg.tiers[0].items_between(0.4, 0.6, false).len(), 2);
+    /// assert_eq!(tg.tiers[0].items_between(0.4, 1.0, true).len(), 1);
+    /// assert_eq!(tg.tiers[0].items_between(0.5, 1.0, false).len(), 1);
+    /// ```
+    pub fn items_between(&self, t0: f64, t1: f64, strict: bool) -> Vec<(usize, &Item)> {
+        let interval_tier = self.interval_tier;
+        let selected = |item: &Item| {
+            if strict {
+                item.tmin >= t0 - TIME_EPSILON && item.tmax <= t1 + TIME_EPSILON
+            } else if interval_tier {
+                item.tmin < t1 - TIME_EPSILON && item.tmax > t0 + TIME_EPSILON
+            } else {
+                item.tmin >= t0 - TIME_EPSILON && item.tmin <= t1 + TIME_EPSILON
+            }
+        };
+        let start = if interval_tier && !strict {
+            self.items
+                .partition_point(|item| item.tmax <= t0 + TIME_EPSILON)
+        } else {
+            self.items
+                .partition_point(|item| item.tmin < t0 - TIME_EPSILON)
+        };
+        self.items[start..]
+            .iter()
+            .take_while(|item| item.tmin <= t1 + TIME_EPSILON)
+            .enumerate()
+            .map(|(offset, item)| (start + offset, item))
+            .filter(|(_, item)| selected(item))
+            .collect()
+    }
+
+    /// Searches the labels of the tier with a regular expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The regular expression to search for; it matches anywhere in a label
+    ///   unless anchored
+    ///
+    /// # Returns
+    ///
+    /// Returns one [`LabelMatch`] per matching item, in tier order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Regex, TextGrid};
+    ///
+    /// let data = vec![(
+    ///     String::from("phones"),
+    ///     true,
+    ///     vec![(0.0, 0.5, String::from("AY1")), (0.5, 1.0, String::from("T"))],
+    /// )];
+    /// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// let matches = tg.tiers[0].find_labels(&Regex::new(r"^[A-Z]+(\d)$").unwrap());
+    /// assert_eq!(matches.len(), 1);
+    /// assert_eq!(matches[0].groups[1].as_deref(), Some("1"));
+    /// ```
+    pub fn find_labels(&self, pattern: &Regex) -> Vec<LabelMatch> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(item_index, item)| {
+                let captures = pattern.captures(&item.label)?;
+                Some(LabelMatch {
+                    tier_name: self.name.clone(),
+                    item_index,
+                    tmin: item.tmin,
+                    tmax: item.tmax,
+                    label: item.label.clone(),
+                    groups: captures
+                        .iter()
+                        .map(|group| group.map(|m| m.as_str().to_string()))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
+    /// Copies the items whose label matches a pattern into a new tier, keeping their times,
+    /// e.g. to make a "vowels only" tier from a phone tier.
+    ///
+    /// The new tier has the name, kind and bounds of this one, and gaps where other items were.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - A string matching equal labels, or a [`Regex`], see [`LabelPattern`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Regex, TextGrid};
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("phones", |t| {
+    ///         t.interval(0.0, 0.1, "k").interval(0.1, 0.3, "AE1").interval(0.3, 0.4, "t")
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let vowels = tg["phones"].extract_matching(Regex::new(r"^[AEIOU]").unwrap());
+    /// assert_eq!(vowels.size, 1);
+    /// assert_eq!((vowels[0].tmin, vowels[0].tmax), (0.1, 0.3));
+    /// assert_eq!(tg["phones"].extract_matching("t").items[0].tmin, 0.3);
+    /// ```
+    pub fn extract_matching(&self, pattern: impl LabelPattern) -> Tier {
+        let items: Vec<Item> = self
+            .items
+            .iter()
+            .filter(|item| pattern.matches_label(&item.label))
+            .cloned()
+            .collect();
+        Tier {
+            name: self.name.clone(),
+            size: items.len(),
+            items,
+            interval_tier: self.interval_tier,
+            tmin: self.tmin,
+            tmax: self.tmax,
+            unknown_fields: self.unknown_fields.clone(),
+        }
+    }
+
+    /// Finds the runs of consecutive items whose labels match a sequence of patterns, one per
+    /// item, e.g. a phone sequence or a word bigram.
+    ///
+    /// Runs may overlap: searching `["a", "a"]` in `a a a` finds two runs. An empty sequence
+    /// matches nothing.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns` - One pattern per item of a run, see [`LabelPattern`]
+    ///
+    /// # Returns
+    ///
+    /// Returns one [`SequenceMatch`] per run, in tier order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{LabelPattern, Regex, TextGrid};
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("phones", |t| {
+    ///         t.interval(0.0, 0.1, "ð").interval(0.1, 0.2, "ə").interval(0.2, 0.3, "k")
+    ///             .interval(0.3, 0.4, "ð").interval(0.4, 0.5, "i")
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let runs = tg["phones"].find_sequence(&["ð", "ə"]);
+    /// assert_eq!(runs.len(), 1);
+    /// assert_eq!((runs[0].tmin, runs[0].tmax), (0.0, 0.2));
+    ///
+    /// // Exact and regex patterns can be mixed
+    /// let vowel = Regex::new("^[əi]$").unwrap();
+    /// let runs = tg["phones"].find_sequence(&[&"ð" as &dyn LabelPattern, &vowel]);
+    /// assert_eq!(runs[1].items, 3..5);
+    /// ```
+    pub fn find_sequence<P: LabelPattern>(&self, patterns: &[P]) -> Vec<SequenceMatch> {
+        if patterns.is_empty() || patterns.len() > self.items.len() {
+            return Vec::new();
+        }
+        self.items
+            .windows(patterns.len())
+            .enumerate()
+            .filter(|(_, run)| {
+                run.iter()
+                    .zip(patterns)
+                    .all(|(item, pattern)| pattern.matches_label(&item.label))
+            })
+            .map(|(start, run)| SequenceMatch {
+                items: start..start + run.len(),
+                tmin: run[0].tmin,
+                tmax: run[run.len() - 1].tmax,
+            })
+            .collect()
+    }
+}
+
+impl TextGrid {
+    /// Finds the item at a time point in every tier.
+    ///
+    /// # Returns
+    ///
+    /// Returns one entry per tier, in tier order, holding the result of [`Tier::item_at`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{FileType, read_from_str};
+    ///
+    /// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+    /// let tg = read_from_str(&content, false, FileType::Auto).unwrap();
+    /// for (tier, found) in tg.tiers.iter().zip(tg.items_at(1.25)) {
+    ///     if let Some((_, item)) = found {
+    ///         println!("{}: {}", tier.name, item.label);
+    ///     }
+    /// }
+    /// ```
+    pub fn items_at(&self, time: f64) -> Vec<Option<(usize, &Item)>> {
+        self.tiers.iter().map(|tier| tier.item_at(time)).collect()
+    }
+
+    /// Finds all items within a time window `[t0, t1]` in every tier.
+    ///
+    /// # Returns
+    ///
+    /// Returns one entry per tier, in tier order, holding the result of [`Tier::items_between`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{FileType, read_from_str};
+    ///
+    /// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+    /// let tg = read_from_str(&content, false, FileType::Auto).unwrap();
+    /// for (tier, items) in tg.tiers.iter().zip(tg.items_between(1.0, 2.0, false)) {
+    ///     let labels: Vec<&str> = items.iter().map(|(_, item)| item.label.as_str()).collect();
+    ///     println!("{}: {}", tier.name, labels.join(" "));
+    /// }
+    /// ```
+    pub fn items_between(&self, t0: f64, t1: f64, strict: bool) -> Vec<Vec<(usize, &Item)>> {
+        self.tiers
+            .iter()
+            .map(|tier| tier.items_between(t0, t1, strict))
+            .collect()
+    }
+
+    /// Searches the labels of all tiers with a regular expression.
+    ///
+    /// # Returns
+    ///
+    /// Returns the matches of [`Tier::find_labels`] for every tier, in tier order.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{FileType, Regex, read_from_str};
+    ///
+    /// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+    /// let tg = read_from_str(&content, false, FileType::Auto).unwrap();
+    /// let vowels = Regex::new(r"^(AA|AE|AH|AO|AW|AY|EH|ER|EY|IH|IY|OW|OY|UH|UW)([012])$").unwrap();
+    /// for m in tg.find_labels(&vowels) {
+    ///     println!("{} [{}] {:.3}-{:.3}: {}", m.tier_name, m.item_index, m.tmin, m.tmax, m.label);
+    /// }
+    /// ```
+    pub fn find_labels(&self, pattern: &Regex) -> Vec<LabelMatch> {
+        self.tiers
+            .iter()
+            .flat_map(|tier| tier.find_labels(pattern))
+            .collect()
+    }
+
+    /// Finds the index of the first tier with the given name.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the tier
+    /// * `ignore_case` - If `true`, names are compared case-insensitively
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(index)` if a matching tier exists, otherwise `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{TextGrid, Tier};
+    ///
+    /// let mut tg = TextGrid::new();
+    /// let mut tier = Tier::new();
+    /// tier.name = String::from("Phones");
+    /// tg.tiers.push(tier);
+    ///
+    /// assert_eq!(tg.find_tier_index("phones", false), None);
+    /// assert_eq!(tg.find_tier_index("phones", true), Some(0));
+    /// ```
+    pub fn find_tier_index(&self, name: &str, ignore_case: bool) -> Option<usize> {
+        if ignore_case {
+            let name = name.to_lowercase();
+            self.tiers
+                .iter()
+                .position(|tier| tier.name.to_lowercase() == name)
+        } else {
+            self.tiers.iter().position(|tier| tier.name == name)
+        }
+    }
+
+    /// Finds the index of the first tier with exactly the given name.
+    ///
+    /// See [`TextGrid::find_tier_index`] for case-insensitive lookups.
+    pub fn tier_index(&self, name: &str) -> Option<usize> {
+        self.find_tier_index(name, false)
+    }
+
+    /// Returns the first tier with exactly the given name.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{FileType, read_from_str};
+    ///
+    /// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+    /// let tg = read_from_str(&content, false, FileType::Auto).unwrap();
+    /// if let Some(phones) = tg.get_tier("phones") {
+    ///     println!("{} phones", phones.items.len());
+    /// }
+    /// ```
+    pub fn get_tier(&self, name: &str) -> Option<&Tier> {
+        self.tier_index(name).map(|index| &self.tiers[index])
+    }
+
+    /// Returns a mutable reference to the first tier with exactly the given name.
+    pub fn get_tier_mut(&mut self, name: &str) -> Option<&mut Tier> {
+        self.tier_index(name).map(|index| &mut self.tiers[index])
+    }
+
+    /// Returns the names of all tiers, in tier order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 1.0, "hi"))
+    ///     .point_tier("beats", |t| t.point(0.5, "b1"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(tg.tier_names(), vec!["words", "beats"]);
+    /// assert!(tg.has_tier("beats") && !tg.has_tier("phones"));
+    /// assert_eq!(tg.point_tiers().count(), 1);
+    /// ```
+    pub fn tier_names(&self) -> Vec<&str> {
+        self.tiers.iter().map(|tier| tier.name.as_str()).collect()
+    }
+
+    /// Returns `true` if a tier has exactly the given name.
+    pub fn has_tier(&self, name: &str) -> bool {
+        self.tier_index(name).is_some()
+    }
+
+    /// Iterates over the interval tiers, in tier order.
+    pub fn interval_tiers(&self) -> impl Iterator<Item = &Tier> + '_ {
+        self.tiers.iter().filter(|tier| tier.interval_tier)
+    }
+
+    /// Iterates over the point tiers, in tier order.
+    pub fn point_tiers(&self) -> impl Iterator<Item = &Tier> + '_ {
+        self.tiers.iter().filter(|tier| !tier.interval_tier)
+    }
+}
+
+/// Returns the first tier with exactly the given name.
+///
+/// # Panics
+///
+/// Panics if there is no such tier; use [`TextGrid::get_tier`] to check first.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::TextGrid;
+///
+/// let tg = TextGrid::builder()
+///     .interval_tier("words", |t| t.interval(0.0, 1.0, "hi"))
+///     .build()
+///     .unwrap();
+/// assert_eq!(tg["words"][0].label, "hi");
+/// ```
+impl Index<&str> for TextGrid {
+    type Output = Tier;
+
+    fn index(&self, name: &str) -> &Tier {
+        self.get_tier(name)
+            .unwrap_or_else(|| panic!("No tier \"{}\" in TextGrid", name))
+    }
+}
+
+/// Returns the first tier with exactly the given name, mutably.
+///
+/// # Panics
+///
+/// Panics if there is no such tier; use [`TextGrid::get_tier_mut`] to check first.
+impl IndexMut<&str> for TextGrid {
+    fn index_mut(&mut self, name: &str) -> &mut Tier {
+        self.get_tier_mut(name)
+            .unwrap_or_else(|| panic!("No tier \"{}\" in TextGrid", name))
+    }
+}
+
+/// Returns the item at the given index.
+///
+/// # Panics
+///
+/// Panics if the index is out of range.
+impl Index<usize> for Tier {
+    type Output = Item;
+
+    fn index(&self, index: usize) -> &Item {
+        &self.items[index]
+    }
+}
+
+/// Returns the item at the given index, mutably.
+///
+/// # Panics
+///
+/// Panics if the index is out of range.
+impl IndexMut<usize> for Tier {
+    fn index_mut(&mut self, index: usize) -> &mut Item {
+        &mut self.items[index]
+    }
+}
@@ -0,0 +1,224 @@
+//! A builder composing item queries over the tiers of a TextGrid.
+
+use crate::query::LabelPattern;
+use crate::textgrid::{Item, TIME_EPSILON, TextGrid, Tier, input_error};
+use regex::Regex;
+use std::io::{Error, Result};
+
+/// A condition on an item and its tier.
+type Predicate<'a> = Box<dyn Fn(&Tier, &Item) -> bool + 'a>;
+
+/// Creates the error reported for a tier name that is not in the TextGrid.
+fn no_such_tier(name: &str) -> Error {
+    input_error(&format!("No tier \"{}\" in TextGrid", name))
+}
+
+/// An item selected by a [`Query`].
+#[derive(Debug, Clone, Copy)]
+pub struct QueryMatch<'a> {
+    /// The tier containing the item.
+    pub tier: &'a Tier,
+    /// Index of the item within its tier.
+    pub item_index: usize,
+    /// The item.
+    pub item: &'a Item,
+}
+
+/// A query over the items of a TextGrid, built with [`TextGrid::query`].
+///
+/// Every condition narrows the selection. Nothing is evaluated until [`Query::iter`],
+/// [`Query::collect`] or [`Query::count`] is called, and items are then visited lazily in tier
+/// order, without copying them.
+///
+/// Conditions that cannot be built, such as an invalid regular expression or an unknown tier,
+/// are reported when the query is evaluated.
+pub struct Query<'a> {
+    tg: &'a TextGrid,
+    tiers: Option<Vec<usize>>,
+    predicates: Vec<Predicate<'a>>,
+    error: Option<Error>,
+}
+
+impl<'a> Query<'a> {
+    /// Records the first error found while building the query.
+    fn fail(mut self, error: Error) -> Self {
+        self.error.get_or_insert(error);
+        self
+    }
+
+    /// Returns the tier with the given name, or an error.
+    fn find_tier(&self, name: &str) -> Result<&'a Tier> {
+        self.tg.get_tier(name).ok_or_else(|| no_such_tier(name))
+    }
+
+    /// Restricts the query to the tiers with the given name. Calling it several times selects
+    /// the items of all the named tiers; without it, all tiers are searched.
+    pub fn tier(mut self, name: &str) -> Self {
+        let found: Vec<usize> = (0..self.tg.tiers.len())
+            .filter(|&index| self.tg.tiers[index].name == name)
+            .collect();
+        if found.is_empty() {
+            return self.fail(no_such_tier(name));
+        }
+        self.tiers.get_or_insert_with(Vec::new).extend(found);
+        self
+    }
+
+    /// Keeps the items matching an arbitrary condition on the item and its tier.
+    pub fn filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Tier, &Item) -> bool + 'a,
+    {
+        self.predicates.push(Box::new(predicate));
+        self
+    }
+
+    /// Keeps the items whose label matches a pattern, see [`LabelPattern`].
+    pub fn label(self, pattern: impl LabelPattern + 'a) -> Self {
+        self.filter(move |_, item| pattern.matches_label(&item.label))
+    }
+
+    /// Keeps the items whose label matches a regular expression, anywhere unless anchored.
+    pub fn label_matches(self, pattern: &str) -> Self {
+        match Regex::new(pattern) {
+            Ok(regex) => self.label(regex),
+            Err(e) => self.fail(input_error(&format!("Invalid pattern {}: {}", pattern, e))),
+        }
+    }
+
+    /// Keeps the items lasting strictly longer than `duration` seconds.
+    pub fn duration_gt(self, duration: f64) -> Self {
+        self.filter(move |_, item| item.tmax - item.tmin > duration)
+    }
+
+    /// Keeps the items lasting strictly less than `duration` seconds.
+    pub fn duration_lt(self, duration: f64) -> Self {
+        self.filter(move |_, item| item.tmax - item.tmin < duration)
+    }
+
+    /// Keeps the items overlapping the time window `[t0, t1]`, see [`Tier::items_between`].
+    pub fn between(self, t0: f64, t1: f64) -> Self {
+        self.filter(move |tier, item| {
+            if tier.interval_tier {
+                item.tmin < t1 - TIME_EPSILON && item.tmax > t0 + TIME_EPSILON
+            } else {
+                item.tmin >= t0 - TIME_EPSILON && item.tmin <= t1 + TIME_EPSILON
+            }
+        })
+    }
+
+    /// Keeps the items overlapping at least one item of another tier that satisfies a
+    /// condition, e.g. phones within the word "the".
+    ///
+    /// The other tier is indexed once with [`Tier::build_index`], and overlap is tested as by
+    /// [`TierIndex::query`](crate::TierIndex::query), so intervals that merely touch do not overlap.
+    pub fn overlapping<F>(self, tier_name: &str, predicate: F) -> Self
+    where
+        F: Fn(&Item) -> bool + 'a,
+    {
+        match self.find_tier(tier_name) {
+            Ok(other) => {
+                let index = other.build_index();
+                self.filter(move |_, item| {
+                    index
+                        .query(item.tmin, item.tmax)
+                        .into_iter()
+                        .any(|(_, other_item)| predicate(other_item))
+                })
+            }
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Evaluates the query lazily.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a condition could not be built, e.g. an unknown tier or an invalid
+    /// regular expression.
+    pub fn iter(&self) -> Result<impl Iterator<Item = QueryMatch<'a>> + '_> {
+        if let Some(error) = &self.error {
+            return Err(Error::new(error.kind(), error.to_string()));
+        }
+        let tg = self.tg;
+        let tiers: Vec<usize> = match &self.tiers {
+            Some(tiers) => tiers.clone(),
+            None => (0..tg.tiers.len()).collect(),
+        };
+        Ok(tiers.into_iter().flat_map(move |tier_index| {
+            let tier = &tg.tiers[tier_index];
+            tier.items
+                .iter()
+                .enumerate()
+                .filter(move |(_, item)| {
+                    self.predicates
+                        .iter()
+                        .all(|predicate| predicate(tier, item))
+                })
+                .map(move |(item_index, item)| QueryMatch {
+                    tier,
+                    item_index,
+                    item,
+                })
+        }))
+    }
+
+    /// Evaluates the query and collects the selected items, in tier order.
+    ///
+    /// # Errors
+    ///
+    /// See [`Query::iter`].
+    pub fn collect(&self) -> Result<Vec<QueryMatch<'a>>> {
+        Ok(self.iter()?.collect())
+    }
+
+    /// Evaluates the query and counts the selected items.
+    ///
+    /// # Errors
+    ///
+    /// See [`Query::iter`].
+    pub fn count(&self) -> Result<usize> {
+        Ok(self.iter()?.count())
+    }
+}
+
+impl TextGrid {
+    /// Starts a query over the items of the TextGrid, composed from conditions instead of nested
+    /// loops.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.3, "the").interval(0.3, 0.9, "cat"))
+    ///     .interval_tier("phones", |t| {
+    ///         t.interval(0.0, 0.1, "dh").interval(0.1, 0.3, "ah")
+    ///             .interval(0.3, 0.5, "k").interval(0.5, 0.8, "ae").interval(0.8, 0.9, "t")
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let vowels_of_the = tg
+    ///     .query()
+    ///     .tier("phones")
+    ///     .label_matches("^[aeiou]")
+    ///     .duration_gt(0.05)
+    ///     .overlapping("words", |w| w.label == "the")
+    ///     .collect()
+    ///     .unwrap();
+    /// assert_eq!(vowels_of_the.len(), 1);
+    /// assert_eq!(vowels_of_the[0].item.label, "ah");
+    ///
+    /// assert!(tg.query().tier("syllables").collect().is_err());
+    /// ```
+    pub fn query(&self) -> Query<'_> {
+        Query {
+            tg: self,
+            tiers: None,
+            predicates: Vec::new(),
+            error: None,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Set operations between the labelled intervals of two tiers.
+
+use crate::edit::require_interval_tier;
+use crate::textgrid::{Item, TIME_EPSILON, Tier};
+use std::io::Result;
+
+/// A span of time during which the covering interval of each tier does not change.
+struct Segment {
+    tmin: f64,
+    tmax: f64,
+    /// Index of the labelled interval of the first tier covering the segment, if any.
+    first: Option<usize>,
+    /// Index of the labelled interval of the second tier covering the segment, if any.
+    second: Option<usize>,
+}
+
+/// Returns the labelled intervals of a tier; intervals with an empty label are not part of the
+/// set.
+fn labelled(tier: &Tier) -> Vec<&Item> {
+    tier.items
+        .iter()
+        .filter(|item| !item.label.is_empty())
+        .collect()
+}
+
+/// Sweeps the labelled intervals of two tiers and returns the segments for which `keep`
+/// returns `true` given whether each tier covers it.
+///
+/// Consecutive segments covered by the same pair of intervals are merged, so intervals are only
+/// split where the covering interval of one of the tiers changes.
+fn sweep(first: &[&Item], second: &[&Item], keep: impl Fn(bool, bool) -> bool) -> Vec<Segment> {
+    let mut times: Vec<f64> = first
+        .iter()
+        .chain(second)
+        .flat_map(|item| [item.tmin, item.tmax])
+        .collect();
+    times.sort_by(f64::total_cmp);
+    times.dedup_by(|later, kept| *later - *kept <= TIME_EPSILON);
+
+    let covering = |items: &[&Item], cursor: &mut usize, tmin: f64, tmax: f64| {
+        while *cursor < items.len() && items[*cursor].tmax - tmin <= TIME_EPSILON {
+            *cursor += 1;
+        }
+        (*cursor < items.len() && items[*cursor].tmin - tmax < -TIME_EPSILON).then_some(*cursor)
+    };
+    let mut segments: Vec<Segment> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for window in times.windows(2) {
+        let (tmin, tmax) = (window[0], window[1]);
+        let a = covering(first, &mut i, tmin, tmax);
+        let b = covering(second, &mut j, tmin, tmax);
+        if !keep(a.is_some(), b.is_some()) {
+            continue;
+        }
+        match segments.last_mut() {
+            Some(last)
+                if last.first == a && last.second == b && tmin - last.tmax <= TIME_EPSILON =>
+            {
+                last.tmax = tmax;
+            }
+            _ => segments.push(Segment {
+                tmin,
+                tmax,
+                first: a,
+                second: b,
+            }),
+        }
+    }
+    segments
+}
+
+impl Tier {
+    /// Builds the result tier of a set operation from the kept segments.
+    fn set_result(
+        &self,
+        other: &Tier,
+        segments: Vec<Segment>,
+        label: impl Fn(Option<&str>, Option<&str>) -> String,
+    ) -> Tier {
+        let (first, second) = (labelled(self), labelled(other));
+        let items: Vec<Item> = segments
+            .into_iter()
+            .map(|segment| Item {
+                tmin: segment.tmin,
+                tmax: segment.tmax,
+                label: label(
+                    segment.first.map(|index| first[index].label.as_str()),
+                    segment.second.map(|index| second[index].label.as_str()),
+                ),
+            })
+            .collect();
+        Tier {
+            name: self.name.clone(),
+            size: items.len(),
+            items,
+            interval_tier: true,
+            tmin: self.tmin.min(other.tmin),
+            tmax: self.tmax.max(other.tmax),
+            unknown_fields: Vec::new(),
+        }
+    }
+
+    /// Returns the spans during which both tiers have a labelled interval.
+    ///
+    /// Intervals with an empty label are treated as absent, as are the gaps of a tier. The
+    /// result is an interval tier named after this tier, spanning the bounds of both tiers and
+    /// holding only the resulting spans, so it may have gaps.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other interval tier
+    /// * `combine` - Returns the label of a span given the labels of this tier and of `other`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either tier is a point tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::Tier;
+    ///
+    /// let speaker = |name: &str, spans: &[(f64, f64, &str)]| {
+    ///     let mut tier = Tier::new_interval(name, 0.0, 10.0);
+    ///     for &(tmin, tmax, label) in spans {
+    ///         tier.push_item(textgrid::Item::interval(tmin, tmax, label));
+    ///     }
+    ///     tier
+    /// };
+    /// let a = speaker("A", &[(0.0, 4.0, "hello"), (4.0, 6.0, ""), (6.0, 9.0, "bye")]);
+    /// let b = speaker("B", &[(3.0, 7.0, "hi")]);
+    ///
+    /// let both = a.intersect(&b, |x, y| format!("{}+{}", x, y)).unwrap();
+    /// let spans: Vec<(f64, f64, &str)> =
+    ///     both.iter().map(|i| (i.tmin, i.tmax, i.label.as_str())).collect();
+    /// assert_eq!(spans, [(3.0, 4.0, "hello+hi"), (6.0, 7.0, "bye+hi")]);
+    ///
+    /// // Speech of A not overlapped by B
+    /// let alone = a.subtract(&b).unwrap();
+    /// let spans: Vec<(f64, f64)> = alone.iter().map(|i| (i.tmin, i.tmax)).collect();
+    /// assert_eq!(spans, [(0.0, 3.0), (7.0, 9.0)]);
+    /// ```
+    pub fn intersect<F>(&self, other: &Tier, combine: F) -> Result<Tier>
+    where
+        F: Fn(&str, &str) -> String,
+    {
+        require_interval_tier(self, "Intersection")?;
+        require_interval_tier(other, "Intersection")?;
+        let segments = sweep(&labelled(self), &labelled(other), |a, b| a && b);
+        Ok(self.set_result(other, segments, |a, b| {
+            combine(a.unwrap_or_default(), b.unwrap_or_default())
+        }))
+    }
+
+    /// Returns the spans during which either tier has a labelled interval.
+    ///
+    /// Spans are split wherever the labelled interval of either tier changes. See
+    /// [`Tier::intersect`] for how tiers are read and what the result is.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other interval tier
+    /// * `combine` - Returns the label of a span given the labels of this tier and of `other`;
+    ///   the label of a tier with no interval during the span is empty
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either tier is a point tier.
+    pub fn union<F>(&self, other: &Tier, combine: F) -> Result<Tier>
+    where
+        F: Fn(&str, &str) -> String,
+    {
+        require_interval_tier(self, "Union")?;
+        require_interval_tier(other, "Union")?;
+        let segments = sweep(&labelled(self), &labelled(other), |a, b| a || b);
+        Ok(self.set_result(other, segments, |a, b| {
+            combine(a.unwrap_or_default(), b.unwrap_or_default())
+        }))
+    }
+
+    /// Returns the spans during which this tier has a labelled interval and `other` has none,
+    /// keeping the labels of this tier.
+    ///
+    /// See [`Tier::intersect`] for how tiers are read and what the result is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either tier is a point tier.
+    pub fn subtract(&self, other: &Tier) -> Result<Tier> {
+        require_interval_tier(self, "Subtraction")?;
+        require_interval_tier(other, "Subtraction")?;
+        let segments = sweep(&labelled(self), &labelled(other), |a, b| a && !b);
+        Ok(self.set_result(other, segments, |a, _| a.unwrap_or_default().to_string()))
+    }
+}
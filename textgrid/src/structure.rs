@@ -0,0 +1,533 @@
+//! Structural operations on the tiers of a TextGrid.
+
+use crate::converter::get_extreme;
+use crate::textgrid::{Item, TextGrid, Tier, input_error};
+use std::io::Result;
+
+/// How [`TextGrid::dedup_tier_names`] makes tier names unique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateNamePolicy {
+    /// Rename later tiers by appending `_2`, `_3`, ... to their name.
+    Suffix,
+    /// Move the items of later tiers into the first tier with the same name, and remove them.
+    Merge,
+}
+
+/// Identifies a tier of a TextGrid, either by index or by name.
+pub trait TierKey {
+    /// Returns the index of the identified tier, or `None` if there is no such tier.
+    fn tier_index_in(&self, tg: &TextGrid) -> Option<usize>;
+
+    /// Describes the key for error messages.
+    fn describe(&self) -> String;
+}
+
+impl TierKey for usize {
+    fn tier_index_in(&self, tg: &TextGrid) -> Option<usize> {
+        (*self < tg.tiers.len()).then_some(*self)
+    }
+
+    fn describe(&self) -> String {
+        format!("tier {}", self)
+    }
+}
+
+impl TierKey for &str {
+    fn tier_index_in(&self, tg: &TextGrid) -> Option<usize> {
+        tg.tier_index(self)
+    }
+
+    fn describe(&self) -> String {
+        format!("tier \"{}\"", self)
+    }
+}
+
+impl TextGrid {
+    /// Resolves a tier key to an index, failing if the tier does not exist.
+    pub(crate) fn resolve_tier<K: TierKey>(&self, key: &K) -> Result<usize> {
+        key.tier_index_in(self)
+            .ok_or_else(|| input_error(&format!("No {} in TextGrid", key.describe())))
+    }
+
+    /// Fails if a tier with the given name already exists.
+    fn check_name_free(&self, name: &str) -> Result<()> {
+        match self.tier_index(name) {
+            Some(_) => Err(input_error(&format!(
+                "A tier named \"{}\" already exists",
+                name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns a tier name based on `name` that is not used by any tier yet.
+    ///
+    /// `suffix` is appended if `name` is taken, followed by a counter if that is taken too.
+    pub(crate) fn unique_tier_name(&self, name: &str, suffix: &str) -> String {
+        if self.tier_index(name).is_none() {
+            return name.to_string();
+        }
+        let candidate = format!("{}{}", name, suffix);
+        if self.tier_index(&candidate).is_none() {
+            return candidate;
+        }
+        (2..)
+            .map(|counter| format!("{}{}{}", name, suffix, counter))
+            .find(|candidate| self.tier_index(candidate).is_none())
+            .unwrap()
+    }
+
+    /// Merges the tiers of another TextGrid of the same recording into this one.
+    ///
+    /// The tiers of `other` are appended after the existing tiers, and the bounds of the
+    /// TextGrid are widened to cover both TextGrids.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The TextGrid whose tiers are moved into this one
+    /// * `suffix_on_conflict` - Appended to the name of a tier from `other` when the name is
+    ///   already taken; a counter is added as well if that is not enough
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let words = |label: &str| vec![(String::from("words"), true, vec![(0.0, 1.0, String::from(label))])];
+    /// let mut tg = TextGrid::from_data(words("hello"), None, None, None).unwrap();
+    /// let other = TextGrid::from_data(words("hallo"), None, None, None).unwrap();
+    ///
+    /// tg.merge_with(other, "_b");
+    /// assert_eq!(tg.tiers.len(), 2);
+    /// assert_eq!(tg.tiers[1].name, "words_b");
+    /// assert_eq!(tg.size, 2);
+    /// ```
+    pub fn merge_with(&mut self, other: TextGrid, suffix_on_conflict: &str) {
+        if self.tiers.is_empty() {
+            self.tmin = other.tmin;
+            self.tmax = other.tmax;
+        } else if !other.tiers.is_empty() {
+            self.tmin = self.tmin.min(other.tmin);
+            self.tmax = self.tmax.max(other.tmax);
+        }
+        for mut tier in other.tiers.into_iter() {
+            tier.name = self.unique_tier_name(&tier.name, suffix_on_conflict);
+            self.tiers.push(tier);
+        }
+        self.size = self.tiers.len();
+    }
+
+    /// Appends another TextGrid at the end of this one, e.g. to join the annotations of
+    /// consecutive recordings.
+    ///
+    /// The items of `other` are shifted so that it starts where this TextGrid ends, and are
+    /// appended to the tier with the same name, or to a new tier if there is none. Every tier
+    /// then ends with the TextGrid, so a tier missing from either TextGrid has a gap there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error, leaving the TextGrid unchanged, if a tier of `other` has the name of a
+    /// tier of another kind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let words = |label: &str| vec![(String::from("words"), true, vec![(0.0, 1.0, String::from(label))])];
+    /// let mut tg = TextGrid::from_data(words("hello"), None, None, None).unwrap();
+    /// let other = TextGrid::from_data(words("world"), None, None, None).unwrap();
+    ///
+    /// tg.concat(other).unwrap();
+    /// assert_eq!(tg.tmax, 2.0);
+    /// assert_eq!(tg.tiers.len(), 1);
+    /// assert_eq!((tg.tiers[0].items[1].tmin, tg.tiers[0].items[1].tmax), (1.0, 2.0));
+    /// ```
+    pub fn concat(&mut self, other: TextGrid) -> Result<()> {
+        for tier in &other.tiers {
+            if let Some(index) = self.tier_index(&tier.name)
+                && self.tiers[index].interval_tier != tier.interval_tier
+            {
+                return Err(input_error(&format!(
+                    "Cannot concatenate tier {} with a tier of another kind",
+                    tier.name
+                )));
+            }
+        }
+        let offset = self.tmax - other.tmin;
+        let tmax = self.tmax + (other.tmax - other.tmin);
+        for mut tier in other.tiers {
+            for item in &mut tier.items {
+                item.tmin += offset;
+                item.tmax += offset;
+            }
+            match self.tier_index(&tier.name) {
+                Some(index) => self.tiers[index].items.append(&mut tier.items),
+                None => {
+                    tier.tmin = self.tmin;
+                    self.tiers.push(tier);
+                }
+            }
+        }
+        for tier in &mut self.tiers {
+            tier.tmax = tmax;
+            tier.size = tier.items.len();
+        }
+        self.tmax = tmax;
+        self.size = self.tiers.len();
+        Ok(())
+    }
+
+    /// Renames a tier.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The index or name of the tier
+    /// * `new_name` - The new name of the tier
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier does not exist or another tier is already named `new_name`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(String::from("words"), true, vec![(0.0, 1.0, String::from("hello"))])];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// tg.rename_tier("words", "orthography").unwrap();
+    /// tg.duplicate_tier(0, "orthography_copy").unwrap();
+    /// tg.move_tier("orthography_copy", 0).unwrap();
+    /// let removed = tg.remove_tier(1).unwrap();
+    /// assert_eq!(removed.name, "orthography");
+    /// assert_eq!(tg.tiers[0].name, "orthography_copy");
+    /// assert_eq!(tg.size, 1);
+    /// ```
+    pub fn rename_tier<K: TierKey>(&mut self, key: K, new_name: &str) -> Result<()> {
+        let index = self.resolve_tier(&key)?;
+        if self.tiers[index].name != new_name {
+            self.check_name_free(new_name)?;
+        }
+        self.tiers[index].name = new_name.to_string();
+        Ok(())
+    }
+
+    /// Moves a tier to another position, shifting the tiers in between.
+    ///
+    /// # Arguments
+    ///
+    /// * `from` - The index or name of the tier to move
+    /// * `to` - The index the tier should have after the move
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier does not exist or `to` is out of range.
+    pub fn move_tier<K: TierKey>(&mut self, from: K, to: usize) -> Result<()> {
+        let index = self.resolve_tier(&from)?;
+        if to >= self.tiers.len() {
+            return Err(input_error(&format!(
+                "Target index {} is out of range for TextGrid with {} tiers",
+                to,
+                self.tiers.len()
+            )));
+        }
+        let tier = self.tiers.remove(index);
+        self.tiers.insert(to, tier);
+        Ok(())
+    }
+
+    /// Removes a tier.
+    ///
+    /// # Returns
+    ///
+    /// Returns the removed tier.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier does not exist.
+    pub fn remove_tier<K: TierKey>(&mut self, key: K) -> Result<Tier> {
+        let index = self.resolve_tier(&key)?;
+        let tier = self.tiers.remove(index);
+        self.size = self.tiers.len();
+        Ok(tier)
+    }
+
+    /// Duplicates a tier, inserting the copy right after the original.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The index or name of the tier to duplicate
+    /// * `new_name` - The name of the copy
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier does not exist or a tier is already named `new_name`.
+    pub fn duplicate_tier<K: TierKey>(&mut self, key: K, new_name: &str) -> Result<()> {
+        let index = self.resolve_tier(&key)?;
+        self.check_name_free(new_name)?;
+        let original = &self.tiers[index];
+        let copy = Tier {
+            name: new_name.to_string(),
+            size: original.items.len(),
+            items: original
+                .items
+                .iter()
+                .map(|item| Item {
+                    tmin: item.tmin,
+                    tmax: item.tmax,
+                    label: item.label.clone(),
+                })
+                .collect(),
+            interval_tier: original.interval_tier,
+            tmin: original.tmin,
+            tmax: original.tmax,
+            unknown_fields: original.unknown_fields.clone(),
+        };
+        self.tiers.insert(index + 1, copy);
+        self.size = self.tiers.len();
+        Ok(())
+    }
+}
+
+impl Tier {
+    /// Makes the tier structurally consistent: sorts its items by start time, recomputes its
+    /// `size`, and widens its bounds to cover all items.
+    ///
+    /// Items starting at the same time are sorted by end time; otherwise their order is kept.
+    /// Bounds are never narrowed, since unlabelled time at the edges of a tier is valid.
+    pub fn normalize(&mut self) {
+        self.items
+            .sort_by(|a, b| a.tmin.total_cmp(&b.tmin).then(a.tmax.total_cmp(&b.tmax)));
+        self.size = self.items.len();
+        self.recompute_bounds(true);
+    }
+
+    /// Sets the bounds of the tier to the extent of its items, e.g. after editing them.
+    ///
+    /// If `expand_only` is true, the bounds are only widened to cover the items and never
+    /// narrowed. The bounds are left unchanged if the tier has no items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, Tier};
+    ///
+    /// let mut tier = Tier::new_interval("words", 0.0, 2.0);
+    /// tier.items.push(Item::interval(0.5, 2.5, "hello"));
+    ///
+    /// tier.recompute_bounds(true);
+    /// assert_eq!((tier.tmin, tier.tmax), (0.0, 2.5));
+    /// tier.recompute_bounds(false);
+    /// assert_eq!((tier.tmin, tier.tmax), (0.5, 2.5));
+    /// ```
+    pub fn recompute_bounds(&mut self, expand_only: bool) {
+        let (Some(tmin), Some(tmax)) = (
+            get_extreme(&self.items, |item| item.tmin, false),
+            get_extreme(&self.items, |item| item.tmax, true),
+        ) else {
+            return;
+        };
+        if expand_only {
+            self.tmin = self.tmin.min(tmin);
+            self.tmax = self.tmax.max(tmax);
+        } else {
+            (self.tmin, self.tmax) = (tmin, tmax);
+        }
+    }
+}
+
+impl TextGrid {
+    /// Makes the TextGrid structurally consistent before validation or writing.
+    ///
+    /// Normalizes every tier (see [`Tier::normalize`]), recomputes `size`, and sets the bounds of
+    /// the TextGrid to the extent of its tiers. The bounds are left unchanged if there are no
+    /// tiers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, TextGrid, Tier};
+    ///
+    /// let mut tier = Tier::new_interval("words", 0.0, 1.0);
+    /// tier.items.push(Item::interval(1.0, 1.5, "world"));
+    /// tier.items.push(Item::interval(0.0, 1.0, "hello"));
+    /// let mut tg = TextGrid::new();
+    /// tg.tiers.push(tier);
+    ///
+    /// tg.normalize();
+    /// assert_eq!(tg.tiers[0].items[0].label, "hello");
+    /// assert_eq!((tg.size, tg.tiers[0].size), (1, 2));
+    /// assert_eq!((tg.tmin, tg.tmax), (0.0, 1.5));
+    /// assert!(tg.assert_valid().is_ok());
+    /// ```
+    pub fn normalize(&mut self) {
+        self.tiers.iter_mut().for_each(Tier::normalize);
+        self.size = self.tiers.len();
+        if let (Some(tmin), Some(tmax)) = (
+            self.tiers.iter().map(|tier| tier.tmin).reduce(f64::min),
+            self.tiers.iter().map(|tier| tier.tmax).reduce(f64::max),
+        ) {
+            self.tmin = tmin;
+            self.tmax = tmax;
+        }
+    }
+
+    /// Recomputes the bounds of every tier from its items, then the bounds of the TextGrid
+    /// from its tiers, see [`Tier::recompute_bounds`].
+    ///
+    /// If `expand_only` is true, bounds are only widened and never narrowed. Bounds are left
+    /// unchanged where there are no items or tiers to derive them from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, TextGrid};
+    ///
+    /// let mut tg = TextGrid::builder()
+    ///     .tmax(2.0)
+    ///     .interval_tier("words", |t| t.interval(0.0, 2.0, "hello"))
+    ///     .build()
+    ///     .unwrap();
+    /// tg.tiers[0].items.push(Item::interval(2.0, 3.0, "world"));
+    ///
+    /// tg.recompute_bounds(true);
+    /// assert_eq!((tg.tmax, tg.tiers[0].tmax), (3.0, 3.0));
+    /// ```
+    pub fn recompute_bounds(&mut self, expand_only: bool) {
+        for tier in self.tiers.iter_mut() {
+            tier.recompute_bounds(expand_only);
+        }
+        let (Some(tmin), Some(tmax)) = (
+            get_extreme(&self.tiers, |tier| tier.tmin, false),
+            get_extreme(&self.tiers, |tier| tier.tmax, true),
+        ) else {
+            return;
+        };
+        if expand_only {
+            self.tmin = self.tmin.min(tmin);
+            self.tmax = self.tmax.max(tmax);
+        } else {
+            (self.tmin, self.tmax) = (tmin, tmax);
+        }
+    }
+}
+
+impl TextGrid {
+    /// Splits the TextGrid into one TextGrid per tier, e.g. for tools that only accept
+    /// single-tier TextGrids.
+    ///
+    /// Every TextGrid keeps the bounds of this one, and is named after it followed by `_` and
+    /// the name of its tier.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .name("rec")
+    ///     .tmax(2.0)
+    ///     .interval_tier("words", |t| t.interval(0.0, 1.0, "hello"))
+    ///     .point_tier("notes", |t| t.point(0.5, "laugh"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let parts = tg.split_tiers();
+    /// assert_eq!(parts.len(), 2);
+    /// assert_eq!(parts[1].name, "rec_notes");
+    /// assert_eq!((parts[1].tmin, parts[1].tmax), (0.0, 2.0));
+    /// assert!(parts.iter().all(|part| part.assert_valid().is_ok()));
+    /// ```
+    pub fn split_tiers(&self) -> Vec<TextGrid> {
+        self.tiers
+            .iter()
+            .map(|tier| TextGrid {
+                tmin: self.tmin,
+                tmax: self.tmax,
+                size: 1,
+                name: format!("{}_{}", self.name, tier.name),
+                tiers: vec![tier.clone()],
+                unknown_fields: self.unknown_fields.clone(),
+                warnings: Vec::new(),
+            })
+            .collect()
+    }
+
+    /// Makes tier names unique, so that tiers can be looked up by name unambiguously.
+    ///
+    /// Praat allows several tiers with the same name, which [`TextGrid::validate`] reports as a
+    /// warning.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Whether to rename or merge tiers whose name is already used by an earlier tier
+    ///
+    /// # Errors
+    ///
+    /// With [`DuplicateNamePolicy::Merge`], returns an error if tiers with the same name are not
+    /// all interval tiers or all point tiers. The TextGrid is left unchanged in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{DuplicateNamePolicy, TextGrid};
+    ///
+    /// let mut tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.5, "a"))
+    ///     .interval_tier("words", |t| t.interval(0.5, 1.0, "b"))
+    ///     .build()
+    ///     .unwrap();
+    /// let mut renamed = tg.clone();
+    ///
+    /// renamed.dedup_tier_names(DuplicateNamePolicy::Suffix).unwrap();
+    /// assert_eq!(renamed.tier_names(), vec!["words", "words_2"]);
+    ///
+    /// tg.dedup_tier_names(DuplicateNamePolicy::Merge).unwrap();
+    /// assert_eq!(tg.tiers.len(), 1);
+    /// assert_eq!(tg.tiers[0].items.len(), 2);
+    /// ```
+    pub fn dedup_tier_names(&mut self, policy: DuplicateNamePolicy) -> Result<()> {
+        match policy {
+            DuplicateNamePolicy::Suffix => {
+                for index in 1..self.tiers.len() {
+                    let name = &self.tiers[index].name;
+                    if self.tiers[..index].iter().any(|tier| tier.name == *name) {
+                        let new_name = (2..)
+                            .map(|counter| format!("{}_{}", name, counter))
+                            .find(|candidate| self.tier_index(candidate).is_none())
+                            .unwrap();
+                        self.tiers[index].name = new_name;
+                    }
+                }
+            }
+            DuplicateNamePolicy::Merge => {
+                for (index, tier) in self.tiers.iter().enumerate() {
+                    let first = self.tier_index(&tier.name).unwrap();
+                    if self.tiers[first].interval_tier != tier.interval_tier {
+                        return Err(input_error(&format!(
+                            "Cannot merge tiers {} and {} named \"{}\": one is a point tier",
+                            first, index, tier.name
+                        )));
+                    }
+                }
+                let mut merged: Vec<Tier> = Vec::with_capacity(self.tiers.len());
+                for tier in self.tiers.drain(..) {
+                    match merged.iter_mut().find(|kept| kept.name == tier.name) {
+                        Some(kept) => {
+                            kept.tmin = kept.tmin.min(tier.tmin);
+                            kept.tmax = kept.tmax.max(tier.tmax);
+                            kept.items.extend(tier.items);
+                            kept.normalize();
+                        }
+                        None => merged.push(tier),
+                    }
+                }
+                self.tiers = merged;
+                self.size = self.tiers.len();
+            }
+        }
+        Ok(())
+    }
+}
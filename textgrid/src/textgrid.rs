@@ -1,11 +1,13 @@
+use std::fmt;
 use std::io::{Error, ErrorKind, Result};
 
 /// Epsilon value for floating-point time comparisons.
-const TIME_EPSILON: f64 = 1e-6;
+pub(crate) const TIME_EPSILON: f64 = 1e-6;
 
 /// Represents an item (interval or point) in a TextGrid tier.
 ///
 /// For point tiers, `tmin` equals `tmax`.
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Item {
     /// Start time of the item.
     pub tmin: f64,
@@ -18,10 +20,18 @@ pub struct Item {
 /// Represents a tier in a TextGrid.
 ///
 /// A tier can be either an interval tier (with time ranges) or a point tier (with time points).
+///
+/// Two tiers are equal if their names, kinds, bounds, items and unknown fields are equal; the
+/// redundant `size` field is not compared.
+#[derive(Debug, Clone)]
 pub struct Tier {
     /// Name of the tier.
     pub name: String,
     /// Number of items in the tier.
+    ///
+    /// The library's own editing methods keep it in sync with `items`, and writers ignore it;
+    /// after modifying `items` directly, use [`Tier::push_item`] or [`Tier::normalize`], or
+    /// read [`Tier::len`] instead.
     pub size: usize,
     /// Vector of items contained in the tier.
     pub items: Vec<Item>,
@@ -31,22 +41,49 @@ pub struct Tier {
     pub tmin: f64,
     /// Maximum time of the tier.
     pub tmax: f64,
+    /// Key/value lines of the tier not recognized by the parser, in file order.
+    ///
+    /// Only filled when parsing with [`ParseOptions::preserve_unknown_fields`], and written back
+    /// after the tier bounds in the long format.
+    ///
+    /// [`ParseOptions::preserve_unknown_fields`]: crate::ParseOptions::preserve_unknown_fields
+    pub unknown_fields: Vec<(String, String)>,
 }
 
 /// Represents a Praat TextGrid object.
 ///
 /// A TextGrid contains multiple tiers and defines a time range.
+///
+/// Two TextGrids are equal if their names, bounds, tiers and unknown fields are equal; the
+/// redundant `size` field is not compared.
+#[derive(Debug, Clone)]
 pub struct TextGrid {
     /// Minimum time of the TextGrid.
     pub tmin: f64,
     /// Maximum time of the TextGrid.
     pub tmax: f64,
     /// Number of tiers in the TextGrid.
+    ///
+    /// The library's own editing methods keep it in sync with `tiers`, and writers ignore it;
+    /// after modifying `tiers` directly, use [`TextGrid::push_tier`] or
+    /// [`TextGrid::normalize`], or read [`TextGrid::len`] instead.
     pub size: usize,
     /// Name of the TextGrid.
     pub name: String,
     /// Vector of tiers contained in the TextGrid.
     pub tiers: Vec<Tier>,
+    /// Key/value lines of the header not recognized by the parser, in file order.
+    ///
+    /// Only filled when parsing with [`ParseOptions::preserve_unknown_fields`], and written back
+    /// after the TextGrid bounds in the long format.
+    ///
+    /// [`ParseOptions::preserve_unknown_fields`]: crate::ParseOptions::preserve_unknown_fields
+    pub unknown_fields: Vec<(String, String)>,
+    /// Problems the parser recovered from, such as items clamped with
+    /// [`ParseOptions::clamp_to_bounds`], in file order. Not compared by `==`.
+    ///
+    /// [`ParseOptions::clamp_to_bounds`]: crate::ParseOptions::clamp_to_bounds
+    pub warnings: Vec<String>,
 }
 
 impl Item {
@@ -73,6 +110,30 @@ impl Item {
             label: String::new(),
         }
     }
+
+    /// Creates an interval `[tmin, tmax]` with a label.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::Item;
+    ///
+    /// let item = Item::interval(0.5, 1.0, "hello");
+    /// assert_eq!((item.tmin, item.tmax), (0.5, 1.0));
+    /// assert_eq!(item.label, "hello");
+    /// ```
+    pub fn interval(tmin: f64, tmax: f64, label: &str) -> Self {
+        Item {
+            tmin,
+            tmax,
+            label: label.to_string(),
+        }
+    }
+
+    /// Creates a point at `time` with a label, i.e. an item whose tmin and tmax are both `time`.
+    pub fn point(time: f64, label: &str) -> Self {
+        Self::interval(time, time, label)
+    }
 }
 
 impl Tier {
@@ -102,14 +163,75 @@ impl Tier {
             interval_tier: true,
             tmin: 0.0,
             tmax: 0.0,
+            unknown_fields: Vec::new(),
+        }
+    }
+
+    /// Creates an empty interval tier with a name and bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, Tier};
+    ///
+    /// let mut tier = Tier::new_interval("words", 0.0, 1.0);
+    /// tier.items.push(Item::interval(0.0, 1.0, "hello"));
+    /// tier.size = tier.items.len();
+    /// assert!(tier.assert_valid().is_ok());
+    /// ```
+    pub fn new_interval(name: &str, tmin: f64, tmax: f64) -> Self {
+        Tier {
+            name: name.to_string(),
+            tmin,
+            tmax,
+            ..Self::new()
         }
     }
 
-    /// Adds an empty item to the tier's item list.
+    /// Creates an empty point tier with a name and bounds.
+    pub fn new_point(name: &str, tmin: f64, tmax: f64) -> Self {
+        Tier {
+            interval_tier: false,
+            ..Self::new_interval(name, tmin, tmax)
+        }
+    }
+
+    /// Adds an empty item to the tier's item list, and updates `size`.
     ///
     /// Used during parsing to add placeholder items that will be populated with data.
     pub fn add_empty_item(&mut self) {
-        self.items.push(Item::new());
+        self.push_item(Item::new());
+    }
+
+    /// Appends an item to the tier and keeps `size` in sync with the items.
+    ///
+    /// The item is not checked; call [`Tier::normalize`] if it may be out of order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, Tier};
+    ///
+    /// let mut tier = Tier::new_interval("words", 0.0, 1.0);
+    /// tier.push_item(Item::interval(0.0, 1.0, "hello"));
+    /// assert_eq!(tier.size, 1);
+    /// assert!(tier.assert_valid().is_ok());
+    /// ```
+    pub fn push_item(&mut self, item: Item) {
+        self.items.push(item);
+        self.size = self.items.len();
+    }
+
+    /// Returns the number of items in the tier.
+    ///
+    /// Unlike the `size` field, this is always the actual number of items.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the tier has no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
     }
 
     /// Validates the tier structure and constraints.
@@ -124,43 +246,45 @@ impl Tier {
     /// * The `size` field doesn't match the actual number of items
     /// * The time bounds are invalid
     /// * Any item has invalid time bounds (for interval tiers)
+    /// * Any item lies outside the bounds of the tier
     /// * Any point tier item has tmin != tmax
     /// * Any adjacent items overlap
+    /// * Point times are not strictly increasing
     pub fn assert_valid(&self) -> Result<()> {
-        if self.size != self.items.len() {
-            return Err(data_error("Tier size does not match number of items"));
-        }
-        assert_valid_time_bounds(self.tmin, self.tmax, &format!("tier {}", self.name))?;
-        for item_idx in 0..self.items.len() {
-            let this_item = &self.items[item_idx];
+        self.validate().into_result()
+    }
+}
 
-            if self.interval_tier {
-                assert_valid_time_bounds(
-                    this_item.tmin,
-                    this_item.tmax,
-                    &format!("item {} in tier {}", item_idx, self.name),
-                )?;
-            } else {
-                if (this_item.tmin - this_item.tmax).abs() > TIME_EPSILON {
-                    return Err(data_error(&format!(
-                        "Item {} should have tmin == tmax in PointTier {}",
-                        item_idx, self.name
-                    )));
-                }
-            }
-            if item_idx + 1 < self.items.len() {
-                let next_item = &self.items[item_idx + 1];
-                if this_item.tmax - next_item.tmin > TIME_EPSILON {
-                    return Err(data_error(&format!(
-                        "Items {} and {} overlap in tier {}",
-                        item_idx,
-                        item_idx + 1,
-                        self.name
-                    )));
-                }
-            }
-        }
-        Ok(())
+impl Default for Tier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for TextGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for Tier {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.interval_tier == other.interval_tier
+            && self.tmin == other.tmin
+            && self.tmax == other.tmax
+            && self.items == other.items
+            && self.unknown_fields == other.unknown_fields
+    }
+}
+
+impl PartialEq for TextGrid {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.tmin == other.tmin
+            && self.tmax == other.tmax
+            && self.tiers == other.tiers
+            && self.unknown_fields == other.unknown_fields
     }
 }
 
@@ -189,14 +313,51 @@ impl TextGrid {
             size: 0,
             name: String::new(),
             tiers: Vec::new(),
+            unknown_fields: Vec::new(),
+            warnings: Vec::new(),
         }
     }
 
-    /// Adds an empty tier to the TextGrid's tier list.
+    /// Adds an empty tier to the TextGrid's tier list, and updates `size`.
     ///
     /// Used during parsing to add placeholder tiers that will be populated with data.
     pub fn add_empty_tier(&mut self) {
-        self.tiers.push(Tier::new());
+        self.push_tier(Tier::new());
+    }
+
+    /// Appends a tier to the TextGrid and keeps the `size` fields of the TextGrid and of the
+    /// tier in sync with their contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, TextGrid, Tier};
+    ///
+    /// let mut tier = Tier::new_interval("words", 0.0, 1.0);
+    /// tier.items.push(Item::interval(0.0, 1.0, "hello"));
+    ///
+    /// let mut tg = TextGrid::new();
+    /// tg.tmax = 1.0;
+    /// tg.push_tier(tier);
+    /// assert_eq!((tg.size, tg.tiers[0].size), (1, 1));
+    /// assert!(tg.assert_valid().is_ok());
+    /// ```
+    pub fn push_tier(&mut self, mut tier: Tier) {
+        tier.size = tier.items.len();
+        self.tiers.push(tier);
+        self.size = self.tiers.len();
+    }
+
+    /// Returns the number of tiers in the TextGrid.
+    ///
+    /// Unlike the `size` field, this is always the actual number of tiers.
+    pub fn len(&self) -> usize {
+        self.tiers.len()
+    }
+
+    /// Returns `true` if the TextGrid has no tiers.
+    pub fn is_empty(&self) -> bool {
+        self.tiers.is_empty()
     }
 
     /// Validates the TextGrid structure and all its tiers.
@@ -210,18 +371,46 @@ impl TextGrid {
     /// Returns an error if:
     /// * The `size` field doesn't match the actual number of tiers
     /// * The time bounds are invalid
+    /// * Any tier lies outside the bounds of the TextGrid
     /// * Any tier is invalid (see [`Tier::assert_valid`])
     pub fn assert_valid(&self) -> Result<()> {
-        if self.size != self.tiers.len() {
-            return Err(data_error("TextGrid size does not match number of tiers"));
-        }
+        self.validate().into_result()
+    }
+}
 
-        assert_valid_time_bounds(self.tmin, self.tmax, "TextGrid")?;
+/// Prints a one-line summary of the tier, such as `words(145 intervals)`.
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match (self.interval_tier, self.items.len()) {
+            (true, 1) => "interval",
+            (true, _) => "intervals",
+            (false, 1) => "point",
+            (false, _) => "points",
+        };
+        write!(f, "{}({} {})", self.name, self.items.len(), kind)
+    }
+}
 
-        for tier in &self.tiers {
-            tier.assert_valid()?;
+/// Prints a one-line summary of the TextGrid, such as
+/// `TextGrid "rec01" [0, 12.3] – 2 tiers: words(145 intervals), notes(4 points)`.
+impl fmt::Display for TextGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TextGrid {:?} [{}, {}] – {} {}",
+            self.name,
+            self.tmin,
+            self.tmax,
+            self.tiers.len(),
+            if self.tiers.len() == 1 {
+                "tier"
+            } else {
+                "tiers"
+            }
+        )?;
+        for (index, tier) in self.tiers.iter().enumerate() {
+            write!(f, "{}{}", if index == 0 { ": " } else { ", " }, tier)?;
         }
-
         Ok(())
     }
 }
@@ -232,27 +421,14 @@ impl TextGrid {
 ///
 /// Convenience function for creating data validation errors consistently.
 #[inline]
-fn data_error(msg: &str) -> Error {
+pub(crate) fn data_error(msg: &str) -> Error {
     Error::new(ErrorKind::InvalidData, msg)
 }
 
-/// Validates time bounds for any TextGrid element.
+/// Creates an `Error` with `InvalidInput` kind.
 ///
-/// Checks that tmin is non-negative, tmax is positive, and tmax > tmin.
-/// Used internally by tier and TextGrid validation.
+/// Convenience function for rejecting arguments that would break TextGrid invariants.
 #[inline]
-fn assert_valid_time_bounds(tmin: f64, tmax: f64, where_msg: &str) -> Result<()> {
-    if tmin < 0.0 || tmax <= 0.0 {
-        return Err(data_error(&format!(
-            "Time bounds should be non-negative in {}",
-            where_msg
-        )));
-    }
-    if tmax - tmin <= TIME_EPSILON {
-        return Err(data_error(&format!(
-            "tmin should be less than tmax in {}",
-            where_msg
-        )));
-    }
-    Ok(())
+pub(crate) fn input_error(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput, msg)
 }
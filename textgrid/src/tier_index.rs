@@ -0,0 +1,137 @@
+//! An index answering repeated overlap queries against a tier.
+
+use crate::textgrid::{Item, TIME_EPSILON, Tier};
+
+/// An interval tree over the items of a tier, built once with [`Tier::build_index`] and then
+/// queried any number of times.
+///
+/// Unlike [`Tier::items_between`], the index does not require the items to be sorted or
+/// non-overlapping, which makes it suited to tiers such as diarization output where segments of
+/// different speakers overlap. The items are sorted by start time into an implicit balanced tree
+/// whose nodes record the latest end time below them, so a query only visits the subtrees that
+/// can hold an overlapping item.
+///
+/// The index borrows the tier, so the tier cannot be modified while the index is alive.
+#[derive(Debug, Clone)]
+pub struct TierIndex<'a> {
+    tier: &'a Tier,
+    /// Indices of the items of the tier, sorted by start time.
+    order: Vec<usize>,
+    /// Latest end time of the items in the subtree rooted at each position of `order`.
+    max_end: Vec<f64>,
+}
+
+impl<'a> TierIndex<'a> {
+    /// Builds the index of a tier in O(n log n).
+    pub fn new(tier: &'a Tier) -> Self {
+        let mut order: Vec<usize> = (0..tier.items.len()).collect();
+        order.sort_by(|&a, &b| tier.items[a].tmin.total_cmp(&tier.items[b].tmin));
+        let mut index = TierIndex {
+            tier,
+            max_end: vec![f64::NEG_INFINITY; order.len()],
+            order,
+        };
+        index.fill_max_end(0, index.order.len());
+        index
+    }
+
+    /// Computes `max_end` for the subtree spanning `order[lo..hi]` and returns its value.
+    fn fill_max_end(&mut self, lo: usize, hi: usize) -> f64 {
+        if lo >= hi {
+            return f64::NEG_INFINITY;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let end = self.tier.items[self.order[mid]].tmax;
+        let max_end = end
+            .max(self.fill_max_end(lo, mid))
+            .max(self.fill_max_end(mid + 1, hi));
+        self.max_end[mid] = max_end;
+        max_end
+    }
+
+    /// Returns the indexed tier.
+    pub fn tier(&self) -> &'a Tier {
+        self.tier
+    }
+
+    /// Finds all items overlapping the time window `[t0, t1]`.
+    ///
+    /// Items are selected as by [`Tier::items_between`] with `strict` set to `false`:
+    /// intervals that merely touch the window at a boundary do not overlap it, and points are
+    /// selected if they lie within the window.
+    ///
+    /// # Returns
+    ///
+    /// Returns the matching items with their indices, in tier order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, Tier};
+    ///
+    /// let mut speakers = Tier::new_interval("speakers", 0.0, 10.0);
+    /// speakers.push_item(Item::interval(0.0, 6.0, "A"));
+    /// speakers.push_item(Item::interval(2.0, 3.0, "B"));
+    /// speakers.push_item(Item::interval(5.0, 9.0, "B"));
+    ///
+    /// let index = speakers.build_index();
+    /// let labels: Vec<&str> =
+    ///     index.query(4.0, 5.5).iter().map(|(_, item)| item.label.as_str()).collect();
+    /// assert_eq!(labels, ["A", "B"]);
+    /// assert!(index.query(9.0, 10.0).is_empty());
+    /// ```
+    pub fn query(&self, t0: f64, t1: f64) -> Vec<(usize, &'a Item)> {
+        let mut found = Vec::new();
+        self.visit(0, self.order.len(), t0, t1, &mut found);
+        found.sort_unstable();
+        found
+            .into_iter()
+            .map(|index| (index, &self.tier.items[index]))
+            .collect()
+    }
+
+    /// Collects the items of the subtree spanning `order[lo..hi]` that overlap `[t0, t1]`.
+    fn visit(&self, lo: usize, hi: usize, t0: f64, t1: f64, found: &mut Vec<usize>) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let ends_before = if self.tier.interval_tier {
+            self.max_end[mid] <= t0 + TIME_EPSILON
+        } else {
+            self.max_end[mid] < t0 - TIME_EPSILON
+        };
+        if ends_before {
+            return;
+        }
+        self.visit(lo, mid, t0, t1, found);
+        let item = &self.tier.items[self.order[mid]];
+        let starts_after = if self.tier.interval_tier {
+            item.tmin >= t1 - TIME_EPSILON
+        } else {
+            item.tmin > t1 + TIME_EPSILON
+        };
+        if starts_after {
+            return;
+        }
+        let selected = if self.tier.interval_tier {
+            item.tmax > t0 + TIME_EPSILON
+        } else {
+            item.tmin >= t0 - TIME_EPSILON
+        };
+        if selected {
+            found.push(self.order[mid]);
+        }
+        self.visit(mid + 1, hi, t0, t1, found);
+    }
+}
+
+impl Tier {
+    /// Builds a [`TierIndex`] for repeated overlap queries against the tier.
+    ///
+    /// Building takes O(n log n); each query then takes time proportional to the depth of the
+    /// tree and the number of items found, instead of a scan of the tier.
+    pub fn build_index(&self) -> TierIndex<'_> {
+        TierIndex::new(self)
+    }
+}
@@ -0,0 +1,561 @@
+//! Time transformations of TextGrids and tiers.
+
+use crate::edit::require_interval_tier;
+use crate::structure::TierKey;
+use crate::textgrid::{Item, TIME_EPSILON, TextGrid, Tier, input_error};
+use std::io::Result;
+
+/// Ensures that a time window is non-empty.
+#[inline]
+fn check_window(t0: f64, t1: f64) -> Result<()> {
+    if t1 - t0 > TIME_EPSILON {
+        Ok(())
+    } else {
+        Err(input_error(&format!(
+            "Window [{}, {}] is empty: t0 should be less than t1",
+            t0, t1
+        )))
+    }
+}
+
+/// Ensures that a scaling factor keeps times ordered.
+#[inline]
+fn check_scale_factor(factor: f64) -> Result<()> {
+    if factor > 0.0 && factor.is_finite() {
+        Ok(())
+    } else {
+        Err(input_error(&format!(
+            "Scale factor should be positive and finite, got {}",
+            factor
+        )))
+    }
+}
+
+/// How [`TextGrid::quantize`] moves a time to a multiple of the step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// To the nearest multiple.
+    Nearest,
+    /// To the largest multiple not after the time.
+    Floor,
+    /// To the smallest multiple not before the time.
+    Ceil,
+}
+
+impl Rounding {
+    /// Returns the multiple of `step` that `time` is rounded to.
+    ///
+    /// Times within float noise of a multiple are rounded to it whatever the rounding, so that
+    /// e.g. `0.3` floors to `0.3` and not `0.2` with a step of `0.1`.
+    #[inline]
+    fn apply(self, time: f64, step: f64) -> f64 {
+        let steps = time / step;
+        let nearest = steps.round();
+        let steps = if (steps - nearest).abs() < 1e-9 {
+            nearest
+        } else {
+            match self {
+                Rounding::Nearest => nearest,
+                Rounding::Floor => steps.floor(),
+                Rounding::Ceil => steps.ceil(),
+            }
+        };
+        // Dividing by a whole number of steps per second gives the closest float to the exact
+        // time, e.g. 0.3 rather than 0.30000000000000004 for three steps of 0.1.
+        let per_second = (1.0 / step).round();
+        if (per_second * step - 1.0).abs() < 1e-12 {
+            steps / per_second
+        } else {
+            steps * step
+        }
+    }
+}
+
+/// Ensures that a duration argument, such as a chunk length, is positive and finite.
+#[inline]
+fn check_duration(value: f64, what: &str) -> Result<()> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(input_error(&format!(
+            "{} should be positive and finite, got {}",
+            what, value
+        )))
+    }
+}
+
+/// Ensures that clamping a shift leaves something of a tier or TextGrid ending at `tmax`.
+#[inline]
+fn check_clamped_shift(what: &str, tmax: f64, offset: f64) -> Result<()> {
+    if tmax + offset > TIME_EPSILON {
+        Ok(())
+    } else {
+        Err(input_error(&format!(
+            "Shifting {} by {} with clamping would leave nothing of it, as it ends at {}",
+            what, offset, tmax
+        )))
+    }
+}
+
+/// Ensures that a quantization step is usable.
+#[inline]
+fn check_step(step: f64) -> Result<()> {
+    if step > 0.0 && step.is_finite() {
+        Ok(())
+    } else {
+        Err(input_error(&format!(
+            "Quantization step should be positive and finite, got {}",
+            step
+        )))
+    }
+}
+
+impl Tier {
+    /// Translates all times in the tier, including its bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset added to every time; may be negative
+    /// * `clamp` - If `true`, times that would become negative are clamped to zero.
+    ///   Intervals ending at or before zero and points before zero are removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidInput` error, leaving the tier unchanged, if `clamp` is `true` and the
+    /// whole tier would be clamped away, i.e. it would end at or before zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(
+    ///     String::from("words"),
+    ///     true,
+    ///     vec![(0.0, 0.5, String::from("hello")), (0.5, 1.0, String::from("world"))],
+    /// )];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// tg.tiers[0].shift(-0.7, true).unwrap();
+    /// assert_eq!(tg.tiers[0].items.len(), 1);
+    /// assert_eq!(tg.tiers[0].items[0].tmin, 0.0);
+    /// assert!(tg.tiers[0].shift(-0.5, true).is_err());
+    /// ```
+    pub fn shift(&mut self, offset: f64, clamp: bool) -> Result<()> {
+        if clamp {
+            check_clamped_shift(&format!("tier {}", self.name), self.tmax, offset)?;
+        }
+        for item in self.items.iter_mut() {
+            item.tmin += offset;
+            item.tmax += offset;
+        }
+        self.tmin += offset;
+        self.tmax += offset;
+        if clamp {
+            let interval_tier = self.interval_tier;
+            self.items.retain(|item| {
+                if interval_tier {
+                    item.tmax > TIME_EPSILON
+                } else {
+                    item.tmin >= 0.0
+                }
+            });
+            for item in self.items.iter_mut() {
+                item.tmin = item.tmin.max(0.0);
+            }
+            self.tmin = self.tmin.max(0.0);
+        }
+        self.size = self.items.len();
+        Ok(())
+    }
+
+    /// Scales all times in the tier linearly around an anchor point.
+    ///
+    /// Every time `t`, including the tier bounds, becomes `anchor + (t - anchor) * factor`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `factor` is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(String::from("words"), true, vec![(1.0, 2.0, String::from("hello"))])];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// tg.tiers[0].scale(2.0, 1.0).unwrap();
+    /// assert_eq!(tg.tiers[0].items[0].tmin, 1.0);
+    /// assert_eq!(tg.tiers[0].items[0].tmax, 3.0);
+    /// ```
+    pub fn scale(&mut self, factor: f64, anchor: f64) -> Result<()> {
+        check_scale_factor(factor)?;
+        let scale = |time: f64| anchor + (time - anchor) * factor;
+        for item in self.items.iter_mut() {
+            item.tmin = scale(item.tmin);
+            item.tmax = scale(item.tmax);
+        }
+        self.tmin = scale(self.tmin);
+        self.tmax = scale(self.tmax);
+        Ok(())
+    }
+
+    /// Moves all times in the tier, including its bounds, to multiples of `step`.
+    ///
+    /// See [`TextGrid::quantize`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `step` is not positive and finite.
+    pub fn quantize(&mut self, step: f64, rounding: Rounding) -> Result<()> {
+        check_step(step)?;
+        for item in self.items.iter_mut() {
+            item.tmin = rounding.apply(item.tmin, step);
+            item.tmax = rounding.apply(item.tmax, step);
+        }
+        self.tmin = rounding.apply(self.tmin, step);
+        self.tmax = rounding.apply(self.tmax, step);
+        if self.interval_tier {
+            self.items
+                .retain(|item| item.tmax - item.tmin > TIME_EPSILON);
+        } else {
+            self.items
+                .dedup_by(|later, kept| later.tmin - kept.tmin <= TIME_EPSILON);
+        }
+        self.size = self.items.len();
+        Ok(())
+    }
+
+    /// Copies the part of the tier within a time window `[t0, t1]`.
+    ///
+    /// Intervals crossing the window edges are truncated, and points outside it are dropped.
+    /// The bounds of the new tier are the window itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `t0` - Start of the window
+    /// * `t1` - End of the window
+    /// * `rebase` - If `true`, times are shifted so that the window starts at zero
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `t0` is not less than `t1`.
+    pub fn extract(&self, t0: f64, t1: f64, rebase: bool) -> Result<Tier> {
+        check_window(t0, t1)?;
+        let offset = if rebase { -t0 } else { 0.0 };
+        let items: Vec<Item> = self
+            .items_between(t0, t1, false)
+            .into_iter()
+            .map(|(_, item)| Item {
+                tmin: item.tmin.max(t0) + offset,
+                tmax: item.tmax.min(t1) + offset,
+                label: item.label.clone(),
+            })
+            .collect();
+        Ok(Tier {
+            name: self.name.clone(),
+            size: items.len(),
+            items,
+            interval_tier: self.interval_tier,
+            tmin: t0 + offset,
+            tmax: t1 + offset,
+            unknown_fields: self.unknown_fields.clone(),
+        })
+    }
+}
+
+impl TextGrid {
+    /// Translates all times in the TextGrid, including the bounds of the TextGrid and its tiers.
+    ///
+    /// Useful to realign a TextGrid made on a clipped audio file to the original recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The offset added to every time; may be negative
+    /// * `clamp` - If `true`, negative times are clamped to zero (see [`Tier::shift`])
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidInput` error, leaving the TextGrid unchanged, if `clamp` is `true` and
+    /// the TextGrid or one of its tiers would be clamped away.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{FileType, read_from_str};
+    ///
+    /// // The clip started 12.5 seconds into the recording
+    /// let content = std::fs::read_to_string("clip.TextGrid").unwrap();
+    /// let mut tg = read_from_str(&content, false, FileType::Auto).unwrap();
+    /// tg.shift(12.5, false).unwrap();
+    /// ```
+    pub fn shift(&mut self, offset: f64, clamp: bool) -> Result<()> {
+        if clamp {
+            check_clamped_shift("the TextGrid", self.tmax, offset)?;
+            for tier in &self.tiers {
+                check_clamped_shift(&format!("tier {}", tier.name), tier.tmax, offset)?;
+            }
+        }
+        for tier in self.tiers.iter_mut() {
+            tier.shift(offset, clamp)?;
+        }
+        self.tmin += offset;
+        self.tmax += offset;
+        if clamp {
+            self.tmin = self.tmin.max(0.0);
+        }
+        Ok(())
+    }
+
+    /// Scales all times in the TextGrid linearly around an anchor point.
+    ///
+    /// Every time `t` becomes `anchor + (t - anchor) * factor`. Useful to correct sample-rate
+    /// mismatches, or to map a TextGrid to normalized `[0, 1]` time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `factor` is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{FileType, read_from_str};
+    ///
+    /// // Annotation made on 44.1 kHz audio, applied to the 48 kHz version
+    /// let content = std::fs::read_to_string("example.TextGrid").unwrap();
+    /// let mut tg = read_from_str(&content, false, FileType::Auto).unwrap();
+    /// tg.scale(44100.0 / 48000.0, 0.0).unwrap();
+    ///
+    /// // Normalize to [0, 1]
+    /// let (tmin, tmax) = (tg.tmin, tg.tmax);
+    /// tg.scale(1.0 / (tmax - tmin), tmin).unwrap();
+    /// tg.shift(-tmin, false).unwrap();
+    /// ```
+    pub fn scale(&mut self, factor: f64, anchor: f64) -> Result<()> {
+        check_scale_factor(factor)?;
+        for tier in self.tiers.iter_mut() {
+            tier.scale(factor, anchor)?;
+        }
+        self.tmin = anchor + (self.tmin - anchor) * factor;
+        self.tmax = anchor + (self.tmax - anchor) * factor;
+        Ok(())
+    }
+
+    /// Moves all boundaries, points and bounds of the TextGrid and its tiers to multiples of
+    /// `step`, e.g. `0.01` for 10 ms frames or `1.0 / 16000.0` for 16 kHz samples, to align
+    /// annotations with frame-based features.
+    ///
+    /// Intervals shorter than a step may collapse to zero length; they are removed, and since
+    /// their start and end collapsed to the same time their neighbours still meet. Points that
+    /// collapse to the same time are merged into the first of them, whose label is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - The time between two allowed times, in seconds
+    /// * `rounding` - How times between two multiples of `step` are moved
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `step` is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Rounding, TextGrid};
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("phones", |t| {
+    ///         t.interval(0.0, 0.0123, "a")
+    ///             .interval(0.0123, 0.0149, "b")
+    ///             .interval(0.0149, 0.0307, "c")
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut frames = tg.clone();
+    /// frames.quantize(0.01, Rounding::Nearest).unwrap();
+    /// let labels: Vec<&str> = frames.tiers[0].iter().map(|i| i.label.as_str()).collect();
+    /// assert_eq!(labels, ["a", "c"]);
+    /// assert_eq!(frames.tiers[0].items[1].tmax, 0.03);
+    /// ```
+    pub fn quantize(&mut self, step: f64, rounding: Rounding) -> Result<()> {
+        check_step(step)?;
+        for tier in self.tiers.iter_mut() {
+            tier.quantize(step, rounding)?;
+        }
+        self.tmin = rounding.apply(self.tmin, step);
+        self.tmax = rounding.apply(self.tmax, step);
+        Ok(())
+    }
+
+    /// Copies the part of the TextGrid within a time window `[t0, t1]`, like Praat's
+    /// "Extract part".
+    ///
+    /// Every tier is extracted with [`Tier::extract`]; the new TextGrid keeps all tiers, even
+    /// those left empty, and its bounds are the window itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `t0` - Start of the window
+    /// * `t1` - End of the window
+    /// * `rebase` - If `true`, times are shifted so that the window starts at zero
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `t0` is not less than `t1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let data = vec![(
+    ///     String::from("words"),
+    ///     true,
+    ///     vec![(0.0, 0.5, String::from("hello")), (0.5, 1.0, String::from("world"))],
+    /// )];
+    /// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// let part = tg.extract(0.25, 0.75, true).unwrap();
+    /// assert_eq!(part.tmax, 0.5);
+    /// assert_eq!(part.tiers[0].items[0].tmax, 0.25);
+    /// assert_eq!(part.tiers[0].items[1].label, "world");
+    /// ```
+    pub fn extract(&self, t0: f64, t1: f64, rebase: bool) -> Result<TextGrid> {
+        check_window(t0, t1)?;
+        let tiers = self
+            .tiers
+            .iter()
+            .map(|tier| tier.extract(t0, t1, rebase))
+            .collect::<Result<Vec<Tier>>>()?;
+        let offset = if rebase { -t0 } else { 0.0 };
+        Ok(TextGrid {
+            tmin: t0 + offset,
+            tmax: t1 + offset,
+            size: tiers.len(),
+            name: self.name.clone(),
+            tiers,
+            unknown_fields: self.unknown_fields.clone(),
+            warnings: Vec::new(),
+        })
+    }
+
+    /// Splits the TextGrid into fixed-length windows, e.g. to cut a long recording into
+    /// training segments.
+    ///
+    /// Windows start at the start of the TextGrid and every `hop` seconds after it, up to the
+    /// end of the TextGrid; the last window is cut at that end and may be shorter than `window`.
+    /// Each chunk is
+    /// extracted with [`TextGrid::extract`], so its times start at zero, and is named after the
+    /// TextGrid followed by `_` and its index.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - The duration of each chunk, in seconds
+    /// * `hop` - The time between the starts of two consecutive chunks; chunks overlap if it is
+    ///   less than `window`, and the time between them is skipped if it is greater
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `window` or `hop` is not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .name("rec")
+    ///     .interval_tier("words", |t| t.interval(0.0, 4.0, "a").interval(4.0, 10.0, "b"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let chunks = tg.chunk(4.0, 3.0).unwrap();
+    /// let spans: Vec<f64> = chunks.iter().map(|chunk| chunk.tmax).collect();
+    /// assert_eq!(spans, [4.0, 4.0, 4.0]);
+    /// assert_eq!(chunks[1].name, "rec_1");
+    /// assert_eq!(chunks[1].tiers[0].items[0].tmax, 1.0);
+    /// ```
+    pub fn chunk(&self, window: f64, hop: f64) -> Result<Vec<TextGrid>> {
+        check_duration(window, "Chunk window")?;
+        check_duration(hop, "Chunk hop")?;
+        let mut windows = Vec::new();
+        let mut start = self.tmin;
+        loop {
+            let end = (start + window).min(self.tmax);
+            windows.push((start, end));
+            start += hop;
+            if self.tmax - end <= TIME_EPSILON || start >= self.tmax - TIME_EPSILON {
+                break;
+            }
+        }
+        self.extract_chunks(windows)
+    }
+
+    /// Splits the TextGrid into chunks of at most `max_duration` seconds, cut only at the
+    /// boundaries of an interval tier, so that no interval of that tier, e.g. an utterance, is
+    /// split.
+    ///
+    /// Consecutive intervals are grouped greedily; an interval longer than `max_duration` makes
+    /// a chunk on its own. Chunks are extracted and named as by [`TextGrid::chunk`].
+    ///
+    /// # Arguments
+    ///
+    /// * `tier` - The index or name of the interval tier whose boundaries chunks follow
+    /// * `max_duration` - The longest duration of a chunk, unless a single interval is longer
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tier does not exist or is a point tier, or if `max_duration` is
+    /// not positive and finite.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("utterances", |t| {
+    ///         t.interval(0.0, 3.0, "a").interval(3.0, 5.0, "b").interval(5.0, 12.0, "c")
+    ///     })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let chunks = tg.chunk_by_tier("utterances", 6.0).unwrap();
+    /// let spans: Vec<f64> = chunks.iter().map(|chunk| chunk.tmax).collect();
+    /// assert_eq!(spans, [5.0, 7.0]);
+    /// ```
+    pub fn chunk_by_tier<K: TierKey>(&self, tier: K, max_duration: f64) -> Result<Vec<TextGrid>> {
+        check_duration(max_duration, "Chunk duration")?;
+        let tier = &self.tiers[self.resolve_tier(&tier)?];
+        require_interval_tier(tier, "Chunking by tier")?;
+        let mut windows: Vec<(f64, f64)> = Vec::new();
+        for item in &tier.items {
+            match windows.last_mut() {
+                Some((start, end))
+                    if (item.tmin - *end).abs() <= TIME_EPSILON
+                        && item.tmax - *start <= max_duration + TIME_EPSILON =>
+                {
+                    *end = item.tmax;
+                }
+                _ => windows.push((item.tmin, item.tmax)),
+            }
+        }
+        self.extract_chunks(windows)
+    }
+
+    /// Extracts the chunks of [`TextGrid::chunk`] and [`TextGrid::chunk_by_tier`], rebased and
+    /// named after their index.
+    fn extract_chunks(&self, windows: Vec<(f64, f64)>) -> Result<Vec<TextGrid>> {
+        windows
+            .into_iter()
+            .enumerate()
+            .map(|(index, (t0, t1))| {
+                let mut chunk = self.extract(t0, t1, true)?;
+                chunk.name = format!("{}_{}", self.name, index);
+                Ok(chunk)
+            })
+            .collect()
+    }
+}
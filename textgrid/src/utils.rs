@@ -3,53 +3,67 @@
 //! This module provides helper functions for:
 //! * Parallel mapping operations with automatic parallelization based on data size
 //! * Parsing string values to various types
+//!
+//! Without the `parallel` feature, the mapping helpers always run sequentially.
 
-use num_cpus;
+#[cfg(feature = "parallel")]
+use crate::parallel::plan;
+use crate::textgrid::data_error;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
+use std::borrow::Cow;
+use std::io::Result;
 
 // Parallel mapping helper functions
 
-/// Maps a function over a vector, using parallel processing for large collections.
+/// Maps a function over a slice, using parallel processing for large collections.
 ///
 /// Automatically switches between sequential and parallel processing based on
-/// the number of items and available CPU cores. Used internally throughout the library.
+/// the number of items and the [`ParallelConfig`](crate::ParallelConfig). Used internally
+/// throughout the library.
 #[inline]
-pub(crate) fn fast_map<T, F, R>(items: &Vec<T>, func: F, min_len: usize) -> Vec<R>
+pub(crate) fn fast_map<T, F, R>(items: &[T], func: F) -> Vec<R>
 where
     F: Fn(&T) -> R + Sync + Send,
     R: Send,
     T: Sync,
 {
-    match items.len() >= (num_cpus::get() * min_len / 2) {
-        false => items.iter().map(func).collect::<Vec<R>>(),
-        true => items
-            .par_iter()
-            .with_min_len(min_len)
-            .map(func)
-            .collect::<Vec<R>>(),
+    #[cfg(feature = "parallel")]
+    if let Some(plan) = plan(items.len()) {
+        return plan.install(|| {
+            items
+                .par_iter()
+                .with_min_len(plan.min_len)
+                .map(func)
+                .collect::<Vec<R>>()
+        });
     }
+    items.iter().map(func).collect::<Vec<R>>()
 }
 
-/// Maps a function over a vector with indices, using parallel processing for large collections.
+/// Maps a function over a slice with indices, using parallel processing for large collections.
 ///
 /// Similar to `fast_map` but provides the index along with each element.
 /// Used for operations that need to know the position of items.
 #[inline]
-pub(crate) fn fast_enumerate_map<T, F, R>(items: &Vec<T>, func: F, min_len: usize) -> Vec<R>
+pub(crate) fn fast_enumerate_map<T, F, R>(items: &[T], func: F) -> Vec<R>
 where
     F: Fn((usize, &T)) -> R + Sync + Send,
     R: Send,
     T: Sync,
 {
-    match items.len() >= (num_cpus::get() * min_len / 2) {
-        false => items.iter().enumerate().map(func).collect::<Vec<R>>(),
-        true => items
-            .par_iter()
-            .enumerate()
-            .with_min_len(min_len)
-            .map(func)
-            .collect::<Vec<R>>(),
+    #[cfg(feature = "parallel")]
+    if let Some(plan) = plan(items.len()) {
+        return plan.install(|| {
+            items
+                .par_iter()
+                .enumerate()
+                .with_min_len(plan.min_len)
+                .map(func)
+                .collect::<Vec<R>>()
+        });
     }
+    items.iter().enumerate().map(func).collect::<Vec<R>>()
 }
 
 /// Maps a function over a vector with ownership transfer, using parallel processing for large collections.
@@ -57,44 +71,124 @@ where
 /// Consumes the input vector and transfers ownership to the mapping function.
 /// Used when the original data is no longer needed.
 #[inline]
-pub(crate) fn fast_move_map<T, F, R>(items: Vec<T>, func: F, min_len: usize) -> Vec<R>
+pub(crate) fn fast_move_map<T, F, R>(items: Vec<T>, func: F) -> Vec<R>
 where
     F: Fn(T) -> R + Sync + Send,
     T: Send,
     R: Send,
 {
-    match items.len() >= (num_cpus::get() * min_len / 2) {
-        false => items.into_iter().map(func).collect::<Vec<R>>(),
-        true => items
-            .into_par_iter()
-            .with_min_len(min_len)
-            .map(func)
-            .collect::<Vec<R>>(),
+    #[cfg(feature = "parallel")]
+    if let Some(plan) = plan(items.len()) {
+        return plan.install(|| {
+            items
+                .into_par_iter()
+                .with_min_len(plan.min_len)
+                .map(func)
+                .collect::<Vec<R>>()
+        });
     }
+    items.into_iter().map(func).collect::<Vec<R>>()
 }
 
 // Parsing helper functions
 
-/// Parses a string to a floating-point number.
+/// Parses a string to a finite floating-point number, in decimal or scientific notation.
+///
+/// If `decimal_comma` is true, a single comma is read as the decimal point, e.g. `1,5` as 1.5.
 ///
-/// Returns 0.0 if parsing fails, providing a fallback for malformed data.
+/// # Errors
+///
+/// Returns an `InvalidData` error naming the 1-based `line_number` if the string is not a
+/// finite number, instead of substituting a value that would silently corrupt the data.
 #[inline]
-pub(crate) fn parse_float(s: &str) -> f64 {
-    s.parse().unwrap_or(0.0)
+pub(crate) fn parse_float(s: &str, line_number: usize, decimal_comma: bool) -> Result<f64> {
+    let parsed = if decimal_comma && s.contains(',') && !s.contains('.') {
+        s.replacen(',', ".", 1).parse::<f64>()
+    } else {
+        s.parse::<f64>()
+    };
+    match parsed {
+        Ok(number) if number.is_finite() => Ok(number),
+        _ => Err(data_error(&format!(
+            "Line {}: invalid number \"{}\"",
+            line_number, s
+        ))),
+    }
+}
+
+/// Splits the content of a text file into trimmed logical lines, each with the 1-based number
+/// of its first physical line.
+///
+/// Praat writes labels containing newlines as they are, so a quoted string may span several
+/// physical lines: a line with an odd number of quotes continues on the next lines until its
+/// quotes balance. Escaped quotes are doubled, so they never unbalance a line.
+pub(crate) fn logical_lines(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = content;
+    let mut line_number = 0;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let first_line = line_number + 1;
+        let (mut end, mut quotes) = (0, 0);
+        loop {
+            let line_end = rest[end..].find('\n').map_or(rest.len(), |i| end + i + 1);
+            quotes += rest.as_bytes()[end..line_end]
+                .iter()
+                .filter(|&&b| b == b'"')
+                .count();
+            line_number += 1;
+            end = line_end;
+            if quotes % 2 == 0 || end == rest.len() {
+                break;
+            }
+        }
+        let line = &rest[..end];
+        rest = &rest[end..];
+        Some((first_line, line.trim()))
+    })
 }
 
 /// Parses a string by removing surrounding quotes.
 ///
-/// Removes leading and trailing double quote characters from TextGrid string values.
+/// Removes the leading and trailing double quote characters from TextGrid string values, and
+/// unescapes quotes inside the value, which Praat doubles (`""`). Line breaks inside the value
+/// are read as `\n`, whatever the line ending of the file. The result borrows from `s` unless
+/// the value contains escaped quotes or `\r\n` line breaks.
 #[inline]
-pub(crate) fn parse_str(s: &str) -> String {
-    s.trim_matches('"').to_string()
+pub(crate) fn parse_str(s: &str) -> Cow<'_, str> {
+    let inner = s.strip_prefix('"').unwrap_or(s);
+    let inner = inner.strip_suffix('"').unwrap_or(inner);
+    let mut value = Cow::Borrowed(inner);
+    if value.contains("\"\"") {
+        value = Cow::Owned(value.replace("\"\"", "\""));
+    }
+    if value.contains("\r\n") {
+        value = Cow::Owned(value.replace("\r\n", "\n"));
+    }
+    value
+}
+
+/// Escapes a string for a TextGrid file by doubling its quotes, as Praat does, and writing its
+/// line breaks with the line ending `nl` of the file.
+#[inline]
+pub(crate) fn escape_str(s: &str, nl: &str) -> String {
+    let escaped = s.replace('"', "\"\"");
+    if nl != "\n" && escaped.contains('\n') {
+        escaped.replace("\r\n", "\n").replace('\n', nl)
+    } else {
+        escaped
+    }
 }
 
 /// Parses a string to an unsigned integer.
 ///
-/// Returns 0 if parsing fails, providing a fallback for malformed data.
+/// # Errors
+///
+/// Returns an `InvalidData` error naming the 1-based `line_number` if the string is not an
+/// unsigned integer.
 #[inline]
-pub(crate) fn parse_uint(s: &str) -> usize {
-    s.parse().unwrap_or(0)
+pub(crate) fn parse_uint(s: &str, line_number: usize) -> Result<usize> {
+    s.parse()
+        .map_err(|_| data_error(&format!("Line {}: invalid count \"{}\"", line_number, s)))
 }
@@ -0,0 +1,535 @@
+//! Validation of TextGrids, collecting every issue instead of stopping at the first.
+
+use crate::textgrid::{TIME_EPSILON, TextGrid, Tier, data_error};
+use std::fmt;
+use std::io::Result;
+
+/// How serious a validation issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The TextGrid can be used, but the issue is likely a mistake.
+    Warning,
+    /// The TextGrid is invalid, and [`TextGrid::assert_valid`] fails.
+    Error,
+}
+
+/// One problem found by [`TextGrid::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// How serious the issue is.
+    pub severity: Severity,
+    /// Index of the tier concerned, or `None` if the issue concerns the TextGrid itself, or if a
+    /// tier was validated on its own.
+    pub tier_index: Option<usize>,
+    /// Index of the item concerned within its tier, if any.
+    pub item_index: Option<usize>,
+    /// Description of the issue.
+    pub message: String,
+    /// Suggested fix.
+    pub suggestion: String,
+}
+
+/// Which optional rules [`TextGrid::validate_with`] and [`Tier::validate_with`] check in
+/// addition to the default ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ValidationOptions {
+    /// Whether interval tiers must cover their bounds exactly, without gaps, as Praat requires.
+    ///
+    /// Each gap is reported as an error: the gap before the first interval, the gap after the
+    /// last interval, and every gap between consecutive intervals larger than the epsilon.
+    pub pedantic: bool,
+    /// How intervals of zero length are reported: as errors with
+    /// [`ZeroLengthPolicy::Reject`], the default, and as warnings with any other policy.
+    pub zero_length: ZeroLengthPolicy,
+}
+
+/// What to do with intervals whose end time equals their start time, which some aligners
+/// output and Praat tolerates in some contexts, see [`ValidationOptions::zero_length`] and
+/// [`ParseOptions::zero_length`].
+///
+/// Points, and intervals whose end time is before their start time, are not affected.
+///
+/// [`ParseOptions::zero_length`]: crate::ParseOptions::zero_length
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZeroLengthPolicy {
+    /// Report zero-length intervals as errors.
+    #[default]
+    Reject,
+    /// Keep zero-length intervals, and report them as warnings.
+    Warn,
+    /// Remove zero-length intervals while parsing.
+    Drop,
+    /// Merge zero-length intervals into the previous interval of their tier, or the next one
+    /// for the first interval, while parsing. Their label is appended to the label of that
+    /// interval, separated by a space, unless empty.
+    Merge,
+}
+
+impl ZeroLengthPolicy {
+    /// The severity of a zero-length interval under this policy.
+    pub(crate) fn severity(self) -> Severity {
+        match self {
+            ZeroLengthPolicy::Reject => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+/// All issues found by [`TextGrid::validate`] or [`Tier::validate`], in the order they were
+/// found.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValidationReport {
+    /// The issues found.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no issue is an error; warnings are allowed.
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Returns `true` if there are no issues at all.
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Iterates over the issues that are errors.
+    pub fn errors(&self) -> impl Iterator<Item = &ValidationIssue> + '_ {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    /// Iterates over the issues that are warnings.
+    pub fn warnings(&self) -> impl Iterator<Item = &ValidationIssue> + '_ {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    /// Converts the report into a `Result`, failing with the message of the first error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error if the report contains an error.
+    pub fn into_result(self) -> Result<()> {
+        match self.errors().next() {
+            Some(issue) => Err(data_error(&issue.message)),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds an issue to the report.
+    pub(crate) fn push(
+        &mut self,
+        severity: Severity,
+        tier_index: Option<usize>,
+        item_index: Option<usize>,
+        message: String,
+        suggestion: &str,
+    ) {
+        self.issues.push(ValidationIssue {
+            severity,
+            tier_index,
+            item_index,
+            message,
+            suggestion: suggestion.to_string(),
+        });
+    }
+
+    /// Adds an error about the time bounds of an element, if they are invalid.
+    ///
+    /// Time bounds are invalid if tmin or tmax is negative, or tmax is not greater
+    /// than tmin. Equal bounds are reported with the severity `zero_length`.
+    fn check_time_bounds(
+        &mut self,
+        (tmin, tmax): (f64, f64),
+        (tier_index, item_index): (Option<usize>, Option<usize>),
+        where_msg: &str,
+        zero_length: Severity,
+    ) {
+        if tmin < 0.0 || tmax < 0.0 {
+            self.push(
+                Severity::Error,
+                tier_index,
+                item_index,
+                format!("Time bounds should be non-negative in {}", where_msg),
+                "Shift the times so that they start at 0 or later",
+            );
+        } else if tmax - tmin <= TIME_EPSILON {
+            let severity = if tmax - tmin >= -TIME_EPSILON {
+                zero_length
+            } else {
+                Severity::Error
+            };
+            self.push(
+                severity,
+                tier_index,
+                item_index,
+                format!("tmin should be less than tmax in {}", where_msg),
+                "Swap the bounds or remove the empty span",
+            );
+        }
+    }
+
+    /// Adds the issues of the header of a TextGrid.
+    ///
+    /// Equal bounds are only a warning for a TextGrid without tiers.
+    pub(crate) fn check_textgrid(&mut self, size: usize, n_tiers: usize, bounds: (f64, f64)) {
+        if size != n_tiers {
+            self.push(
+                Severity::Error,
+                None,
+                None,
+                String::from("TextGrid size does not match number of tiers"),
+                "Call `normalize` to recompute the size",
+            );
+        }
+        // An empty TextGrid, as created by `TextGrid::new`, is usable without bounds
+        let zero_length = if n_tiers == 0 {
+            Severity::Warning
+        } else {
+            Severity::Error
+        };
+        self.check_time_bounds(bounds, (None, None), "TextGrid", zero_length);
+    }
+
+    /// Adds an error if the bounds of a tier do not lie within the bounds of its TextGrid, which
+    /// Praat rejects.
+    pub(crate) fn check_tier_within(
+        &mut self,
+        tier_index: usize,
+        name: &str,
+        (tier_tmin, tier_tmax): (f64, f64),
+        (tg_tmin, tg_tmax): (f64, f64),
+    ) {
+        if tg_tmin - tier_tmin > TIME_EPSILON || tier_tmax - tg_tmax > TIME_EPSILON {
+            self.push(
+                Severity::Error,
+                Some(tier_index),
+                None,
+                format!(
+                    "Tier {} [{}, {}] lies outside the bounds [{}, {}] of the TextGrid",
+                    name, tier_tmin, tier_tmax, tg_tmin, tg_tmax
+                ),
+                "Crop the tier, or call `recompute_bounds` to widen the TextGrid",
+            );
+        }
+    }
+
+    /// Adds a warning for every tier whose name is already used by an earlier tier.
+    pub(crate) fn check_tier_names<'a>(&mut self, names: impl Iterator<Item = &'a str>) {
+        let mut seen: Vec<&str> = Vec::new();
+        for (tier_index, name) in names.enumerate() {
+            match seen.iter().position(|seen_name| *seen_name == name) {
+                Some(first) => self.push(
+                    Severity::Warning,
+                    Some(tier_index),
+                    None,
+                    format!("Tier name \"{}\" is already used by tier {}", name, first),
+                    "Call `dedup_tier_names` to rename or merge the tiers",
+                ),
+                None => seen.push(name),
+            }
+        }
+    }
+
+    /// Adds the issues of a tier, shared by [`Tier`] and borrowed tiers.
+    ///
+    /// `span` returns the `(tmin, tmax)` of an item, and `zero_length` decides the severity of
+    /// zero-length intervals.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn check_tier<T>(
+        &mut self,
+        tier_index: Option<usize>,
+        name: &str,
+        size: usize,
+        interval_tier: bool,
+        bounds: (f64, f64),
+        items: &[T],
+        span: impl Fn(&T) -> (f64, f64),
+        zero_length: ZeroLengthPolicy,
+    ) {
+        if size != items.len() {
+            self.push(
+                Severity::Error,
+                tier_index,
+                None,
+                String::from("Tier size does not match number of items"),
+                "Call `normalize` to recompute the size",
+            );
+        }
+        self.check_time_bounds(
+            bounds,
+            (tier_index, None),
+            &format!("tier {}", name),
+            Severity::Error,
+        );
+        for item_idx in 0..items.len() {
+            let (item_tmin, item_tmax) = span(&items[item_idx]);
+            if bounds.0 - item_tmin > TIME_EPSILON || item_tmax - bounds.1 > TIME_EPSILON {
+                self.push(
+                    Severity::Error,
+                    tier_index,
+                    Some(item_idx),
+                    format!(
+                        "Item {} [{}, {}] lies outside the bounds [{}, {}] of tier {}",
+                        item_idx, item_tmin, item_tmax, bounds.0, bounds.1, name
+                    ),
+                    "Clip the item to the tier, or call `recompute_bounds` to widen the tier",
+                );
+            }
+
+            if interval_tier {
+                self.check_time_bounds(
+                    (item_tmin, item_tmax),
+                    (tier_index, Some(item_idx)),
+                    &format!("item {} in tier {}", item_idx, name),
+                    zero_length.severity(),
+                );
+            } else if (item_tmin - item_tmax).abs() > TIME_EPSILON {
+                self.push(
+                    Severity::Error,
+                    tier_index,
+                    Some(item_idx),
+                    format!(
+                        "Item {} should have tmin == tmax in PointTier {}",
+                        item_idx, name
+                    ),
+                    "Set tmax to tmin",
+                );
+            }
+            if item_idx + 1 < items.len() {
+                let (next_tmin, _) = span(&items[item_idx + 1]);
+                if !interval_tier {
+                    if next_tmin - item_tmin <= TIME_EPSILON {
+                        let problem = if (next_tmin - item_tmin).abs() <= TIME_EPSILON {
+                            "have the same time"
+                        } else {
+                            "are out of order"
+                        };
+                        self.push(
+                            Severity::Error,
+                            tier_index,
+                            Some(item_idx + 1),
+                            format!(
+                                "Points {} and {} {} in PointTier {}",
+                                item_idx,
+                                item_idx + 1,
+                                problem,
+                                name
+                            ),
+                            "Remove the duplicate point, or call `normalize` if the points are \
+                             out of order",
+                        );
+                    }
+                } else if item_tmax - next_tmin > TIME_EPSILON {
+                    self.push(
+                        Severity::Error,
+                        tier_index,
+                        Some(item_idx),
+                        format!(
+                            "Items {} and {} overlap in tier {}",
+                            item_idx,
+                            item_idx + 1,
+                            name
+                        ),
+                        "Move the boundary between the items, or call `normalize` if they are \
+                         out of order",
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl ValidationReport {
+    /// Adds an error for every part of the bounds of an interval tier that its items do not
+    /// cover.
+    pub(crate) fn check_tiling<T>(
+        &mut self,
+        tier_index: Option<usize>,
+        name: &str,
+        (tier_tmin, tier_tmax): (f64, f64),
+        items: &[T],
+        span: impl Fn(&T) -> (f64, f64),
+    ) {
+        let suggestion = "Fill the gap with an empty interval, e.g. with `fill_gaps`";
+        let mut covered_until = tier_tmin;
+        for (item_idx, item) in items.iter().enumerate() {
+            let (item_tmin, item_tmax) = span(item);
+            if item_tmin - covered_until > TIME_EPSILON {
+                let message = if item_idx == 0 {
+                    format!(
+                        "Tier {} starts at {} but its first interval starts at {}",
+                        name, tier_tmin, item_tmin
+                    )
+                } else {
+                    format!(
+                        "Gap between items {} and {} in tier {}: [{}, {}] is not covered",
+                        item_idx - 1,
+                        item_idx,
+                        name,
+                        covered_until,
+                        item_tmin
+                    )
+                };
+                self.push(
+                    Severity::Error,
+                    tier_index,
+                    Some(item_idx),
+                    message,
+                    suggestion,
+                );
+            }
+            covered_until = covered_until.max(item_tmax);
+        }
+        if tier_tmax - covered_until > TIME_EPSILON {
+            let message = if items.is_empty() {
+                format!("Tier {} has no intervals", name)
+            } else {
+                format!(
+                    "Tier {} ends at {} but its last interval ends at {}",
+                    name, tier_tmax, covered_until
+                )
+            };
+            self.push(
+                Severity::Error,
+                tier_index,
+                items.len().checked_sub(1),
+                message,
+                suggestion,
+            );
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {} ({})", severity, self.message, self.suggestion)
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.issues.is_empty() {
+            return writeln!(f, "No issues");
+        }
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl Tier {
+    /// Validates the tier and reports every issue found, see [`Tier::assert_valid`] for the
+    /// checks.
+    pub fn validate(&self) -> ValidationReport {
+        self.validate_with(&ValidationOptions::default())
+    }
+
+    /// Validates the tier like [`Tier::validate`], and also checks the optional rules enabled
+    /// in `options`.
+    pub fn validate_with(&self, options: &ValidationOptions) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        self.check(&mut report, None, options);
+        report
+    }
+
+    /// Adds the issues of the tier to a report.
+    fn check(
+        &self,
+        report: &mut ValidationReport,
+        tier_index: Option<usize>,
+        options: &ValidationOptions,
+    ) {
+        report.check_tier(
+            tier_index,
+            &self.name,
+            self.size,
+            self.interval_tier,
+            (self.tmin, self.tmax),
+            &self.items,
+            |item| (item.tmin, item.tmax),
+            options.zero_length,
+        );
+        if options.pedantic && self.interval_tier {
+            report.check_tiling(
+                tier_index,
+                &self.name,
+                (self.tmin, self.tmax),
+                &self.items,
+                |item| (item.tmin, item.tmax),
+            );
+        }
+    }
+}
+
+impl TextGrid {
+    /// Validates the TextGrid and all its tiers, and reports every issue found instead of
+    /// stopping at the first like [`TextGrid::assert_valid`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{Item, TextGrid};
+    ///
+    /// let mut tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.5, "a").interval(0.5, 1.0, "b"))
+    ///     .build()
+    ///     .unwrap();
+    /// tg.tiers[0].items.push(Item::interval(0.8, 0.7, "c"));
+    ///
+    /// let report = tg.validate();
+    /// assert!(!report.is_valid());
+    /// assert_eq!(report.errors().count(), 3);
+    /// assert_eq!(report.issues[1].item_index, Some(1));
+    /// ```
+    pub fn validate(&self) -> ValidationReport {
+        self.validate_with(&ValidationOptions::default())
+    }
+
+    /// Validates the TextGrid like [`TextGrid::validate`], and also checks the optional rules
+    /// enabled in `options`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{TextGrid, ValidationOptions};
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .tmax(2.0)
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.5, "a").interval(1.0, 1.5, "b"))
+    ///     .build()
+    ///     .unwrap();
+    /// assert!(tg.validate().is_valid());
+    ///
+    /// let pedantic = ValidationOptions {
+    ///     pedantic: true,
+    ///     ..Default::default()
+    /// };
+    /// let report = tg.validate_with(&pedantic);
+    /// assert_eq!(report.errors().count(), 2);
+    /// ```
+    pub fn validate_with(&self, options: &ValidationOptions) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        report.check_textgrid(self.size, self.tiers.len(), (self.tmin, self.tmax));
+        for (tier_index, tier) in self.tiers.iter().enumerate() {
+            tier.check(&mut report, Some(tier_index), options);
+            report.check_tier_within(
+                tier_index,
+                &tier.name,
+                (tier.tmin, tier.tmax),
+                (self.tmin, self.tmax),
+            );
+        }
+        report.check_tier_names(self.tiers.iter().map(|tier| tier.name.as_str()));
+        report
+    }
+}
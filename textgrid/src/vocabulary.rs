@@ -0,0 +1,110 @@
+//! Label inventories and frequencies of tiers and TextGrids.
+
+use crate::textgrid::{TextGrid, Tier};
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
+/// How often a label occurs, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LabelStats {
+    /// Number of items with the label.
+    pub count: usize,
+    /// Total duration of the items with the label, in seconds; always 0 for points.
+    pub duration: f64,
+}
+
+impl AddAssign for LabelStats {
+    fn add_assign(&mut self, other: LabelStats) {
+        self.count += other.count;
+        self.duration += other.duration;
+    }
+}
+
+/// Adds the labelled items of a tier to a vocabulary.
+fn count_labels(vocabulary: &mut BTreeMap<String, LabelStats>, tier: &Tier) {
+    for item in tier.labeled_items() {
+        let stats = vocabulary.entry(item.label.clone()).or_default();
+        stats.count += 1;
+        stats.duration += item.tmax - item.tmin;
+    }
+}
+
+impl Tier {
+    /// Counts the labels of the tier, e.g. to build a phone inventory or a word frequency list.
+    ///
+    /// Items whose label is empty or only whitespace are skipped, see [`Tier::labeled_items`].
+    ///
+    /// # Returns
+    ///
+    /// Returns a map from each label to its [`LabelStats`], sorted by label.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TierBuilder;
+    ///
+    /// let tier = TierBuilder::intervals("words")
+    ///     .interval(0.0, 0.5, "the")
+    ///     .interval(0.5, 1.0, "cat")
+    ///     .interval(1.0, 1.25, "the")
+    ///     .interval(1.25, 2.0, "")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let vocabulary = tier.vocabulary();
+    /// assert_eq!(vocabulary.len(), 2);
+    /// assert_eq!(vocabulary["the"].count, 2);
+    /// assert_eq!(vocabulary["the"].duration, 0.75);
+    /// ```
+    pub fn vocabulary(&self) -> BTreeMap<String, LabelStats> {
+        let mut vocabulary = BTreeMap::new();
+        count_labels(&mut vocabulary, self);
+        vocabulary
+    }
+}
+
+impl TextGrid {
+    /// Counts the labels of all tiers of the TextGrid.
+    ///
+    /// # Arguments
+    ///
+    /// * `per_tier` - If `true`, labels are counted separately for each tier name, with tiers
+    ///   sharing a name counted together; if `false`, all tiers are counted together, under the
+    ///   empty string
+    ///
+    /// # Returns
+    ///
+    /// Returns a map from tier name to the vocabulary of the tier, as returned by
+    /// [`Tier::vocabulary`]. Vocabularies of several TextGrids, e.g. of a corpus, can be merged
+    /// by adding their [`LabelStats`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.5, "a").interval(0.5, 1.0, "b"))
+    ///     .interval_tier("phones", |t| t.interval(0.0, 1.0, "a"))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let per_tier = tg.vocabulary(true);
+    /// assert_eq!(per_tier["words"].len(), 2);
+    /// assert_eq!(per_tier["phones"]["a"].count, 1);
+    ///
+    /// let all = tg.vocabulary(false);
+    /// assert_eq!(all[""]["a"].count, 2);
+    /// ```
+    pub fn vocabulary(&self, per_tier: bool) -> BTreeMap<String, BTreeMap<String, LabelStats>> {
+        let mut vocabularies: BTreeMap<String, BTreeMap<String, LabelStats>> = BTreeMap::new();
+        for tier in &self.tiers {
+            let key = if per_tier { tier.name.as_str() } else { "" };
+            count_labels(vocabularies.entry(key.to_string()).or_default(), tier);
+        }
+        if !per_tier {
+            vocabularies.entry(String::new()).or_default();
+        }
+        vocabularies
+    }
+}
@@ -0,0 +1,86 @@
+//! WebAssembly bindings for parsing and serializing TextGrids.
+//!
+//! This module is only available with the `wasm` feature. It exposes string- and byte-based
+//! entry points through `wasm-bindgen`, so TextGrids can be validated and converted client-side
+//! without touching the file system. TextGrids cross the boundary as JSON in the same
+//! `(tmin, tmax, tiers)` layout produced by [`TextGrid::to_data`].
+
+use crate::converter::TextGridData;
+use crate::read_from_str;
+use crate::textgrid::TextGrid;
+use wasm_bindgen::prelude::*;
+
+/// Converts any displayable error into a JavaScript error value.
+#[inline]
+fn js_error<E: std::fmt::Display>(e: E) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
+
+/// Parses TextGrid content, converting errors, including unknown file types, to JavaScript.
+fn parse(content: &str, strict: bool, file_type: &str) -> Result<TextGrid, JsValue> {
+    read_from_str(content, strict, file_type).map_err(js_error)
+}
+
+/// Parses TextGrid content and returns it as a JSON string.
+///
+/// # Arguments
+///
+/// * `content` - The content of the TextGrid file
+/// * `strict` - Whether to perform strict validation on the parsed data
+/// * `file_type` - The format of the content: "long", "short", or "auto"
+///
+/// # Errors
+///
+/// Returns an error if the file type is unknown or the content is invalid.
+#[wasm_bindgen(js_name = parseTextGrid)]
+pub fn parse_textgrid(content: &str, strict: bool, file_type: &str) -> Result<String, JsValue> {
+    let tg = parse(content, strict, file_type)?;
+    serde_json::to_string(&tg.into_data()).map_err(js_error)
+}
+
+/// Parses UTF-8 encoded TextGrid bytes and returns them as a JSON string.
+///
+/// # Errors
+///
+/// Returns an error if the bytes are not valid UTF-8, the file type is unknown,
+/// or the content is invalid.
+#[wasm_bindgen(js_name = parseTextGridBytes)]
+pub fn parse_textgrid_bytes(
+    content: &[u8],
+    strict: bool,
+    file_type: &str,
+) -> Result<String, JsValue> {
+    let content = std::str::from_utf8(content).map_err(js_error)?;
+    parse_textgrid(content, strict, file_type)
+}
+
+/// Validates TextGrid content.
+///
+/// # Errors
+///
+/// Returns an error describing the first problem found.
+#[wasm_bindgen(js_name = validateTextGrid)]
+pub fn validate_textgrid(content: &str, file_type: &str) -> Result<(), JsValue> {
+    parse(content, true, file_type).map(|_| ())
+}
+
+/// Serializes a JSON TextGrid, as returned by [`parse_textgrid`], to TextGrid content.
+///
+/// # Arguments
+///
+/// * `json` - The TextGrid as JSON in `(tmin, tmax, tiers)` layout
+/// * `long` - If `true`, writes long format; if `false`, writes short format
+///
+/// # Errors
+///
+/// Returns an error if the JSON is malformed or the TextGrid is invalid.
+#[wasm_bindgen(js_name = toTextGridString)]
+pub fn to_textgrid_string(json: &str, long: bool) -> Result<String, JsValue> {
+    let (tmin, tmax, tiers): TextGridData = serde_json::from_str(json).map_err(js_error)?;
+    let tg = TextGrid::from_data(tiers, None, Some(tmin), Some(tmax)).map_err(js_error)?;
+    if long {
+        Ok(tg.to_long_textgrid_string())
+    } else {
+        Ok(tg.to_short_textgrid_string())
+    }
+}
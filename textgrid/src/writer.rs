@@ -1,5 +1,268 @@
 use crate::textgrid::{Item, TextGrid, Tier};
-use crate::utils::{fast_enumerate_map, fast_map};
+use crate::utils::{escape_str, fast_enumerate_map, fast_map};
+
+/// A column of the CSV export, see [`CsvOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvColumn {
+    /// Start time of the item.
+    Tmin,
+    /// End time of the item.
+    Tmax,
+    /// Text label of the item.
+    Label,
+    /// Name of the tier.
+    Tier,
+    /// Whether the tier is an interval tier.
+    IsInterval,
+}
+
+impl CsvColumn {
+    /// Returns the header of the column.
+    pub fn header(&self) -> &'static str {
+        match self {
+            CsvColumn::Tmin => "tmin",
+            CsvColumn::Tmax => "tmax",
+            CsvColumn::Label => "label",
+            CsvColumn::Tier => "tier",
+            CsvColumn::IsInterval => "is_interval",
+        }
+    }
+}
+
+/// Which fields of the CSV export are quoted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvQuoting {
+    /// Quote every field that is not a number.
+    NonNumeric,
+    /// Quote only fields containing the delimiter, quotes or line breaks.
+    Necessary,
+    /// Quote every field.
+    Always,
+}
+
+#[cfg(feature = "fs")]
+impl CsvQuoting {
+    fn quote_style(&self) -> csv::QuoteStyle {
+        match self {
+            CsvQuoting::NonNumeric => csv::QuoteStyle::NonNumeric,
+            CsvQuoting::Necessary => csv::QuoteStyle::Necessary,
+            CsvQuoting::Always => csv::QuoteStyle::Always,
+        }
+    }
+}
+
+/// Options controlling the CSV export of TextGrids.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    /// The field delimiter. Defaults to `b','`.
+    pub delimiter: u8,
+    /// Which fields are quoted. Defaults to [`CsvQuoting::NonNumeric`].
+    pub quoting: CsvQuoting,
+    /// Whether to add a leading `filename` column holding the name of the TextGrid. Defaults
+    /// to `false`.
+    pub include_filename: bool,
+    /// How times are formatted. Defaults to [`FloatFormat::Shortest`].
+    pub float_format: FloatFormat,
+    /// The columns to write, in order. Defaults to all columns in declaration order.
+    pub columns: Vec<CsvColumn>,
+}
+
+impl CsvOptions {
+    /// Creates a CSV writer with these options and writes the header row.
+    #[cfg(feature = "fs")]
+    pub(crate) fn csv_writer<W: std::io::Write>(
+        &self,
+        writer: W,
+    ) -> std::io::Result<csv::Writer<W>> {
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .quote_style(self.quoting.quote_style())
+            .from_writer(writer);
+        let mut header: Vec<&str> = Vec::with_capacity(self.columns.len() + 1);
+        if self.include_filename {
+            header.push("filename");
+        }
+        header.extend(self.columns.iter().map(CsvColumn::header));
+        wtr.write_record(&header)?;
+        Ok(wtr)
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {
+            delimiter: b',',
+            quoting: CsvQuoting::NonNumeric,
+            include_filename: false,
+            float_format: FloatFormat::Shortest,
+            columns: vec![
+                CsvColumn::Tmin,
+                CsvColumn::Tmax,
+                CsvColumn::Label,
+                CsvColumn::Tier,
+                CsvColumn::IsInterval,
+            ],
+        }
+    }
+}
+
+/// The delimiter of a table export, see [`TextGrid::save_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    /// Comma-separated values.
+    Csv,
+    /// Tab-separated values.
+    Tsv,
+    /// Values separated by a custom delimiter byte.
+    Custom(u8),
+}
+
+impl TableFormat {
+    /// Returns the delimiter byte.
+    pub fn delimiter(&self) -> u8 {
+        match self {
+            TableFormat::Csv => b',',
+            TableFormat::Tsv => b'\t',
+            TableFormat::Custom(delimiter) => *delimiter,
+        }
+    }
+}
+
+/// The layout of a table export, see [`TextGrid::save_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableLayout {
+    /// One row per item, with a column for the tier name.
+    Long,
+    /// One block of columns per tier, with the `n`-th items of all tiers on the `n`-th row.
+    Wide,
+}
+
+/// The format of a TextGrid file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextGridFormat {
+    /// The long (full text) format, with explicit keys.
+    Long,
+    /// The short text format, with values only.
+    Short,
+}
+
+/// How times are formatted when writing TextGrids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// The shortest representation that reads back to the same value, e.g.
+    /// `0.30000000000000004`.
+    Shortest,
+    /// A fixed number of decimal places, e.g. `Fixed(3)` writes `0.300`.
+    Fixed(usize),
+    /// Rounded to a number of significant digits, without trailing zeros. `Significant(15)`
+    /// hides accumulated rounding errors, e.g. writes `0.3` instead of `0.30000000000000004`.
+    Significant(usize),
+}
+
+impl FloatFormat {
+    /// Formats a time; scientific notation is never used.
+    pub(crate) fn format(&self, value: f64) -> String {
+        match *self {
+            FloatFormat::Shortest => value.to_string(),
+            FloatFormat::Fixed(decimals) => format!("{:.*}", decimals, value),
+            FloatFormat::Significant(digits) => {
+                let rounded = format!("{:.*e}", digits.max(1) - 1, value);
+                rounded.parse::<f64>().unwrap_or(value).to_string()
+            }
+        }
+    }
+}
+
+/// The line ending used when writing TextGrids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`, as usual on Unix.
+    Lf,
+    /// `\r\n`, as written by Praat.
+    CrLf,
+}
+
+impl LineEnding {
+    /// Returns the characters of the line ending.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// The text encoding of written TextGrid files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, with a byte order mark only if [`WriteOptions::bom`] is set.
+    Utf8,
+    /// UTF-16 little-endian, always with a byte order mark, as Praat writes non-ASCII files.
+    Utf16Le,
+    /// UTF-16 big-endian, always with a byte order mark.
+    Utf16Be,
+}
+
+/// Options controlling how TextGrids are written.
+///
+/// # Examples
+///
+/// ```
+/// use textgrid::{FloatFormat, TextGrid, WriteOptions};
+///
+/// let data = vec![(String::from("words"), true, vec![(0.0, 0.1 + 0.2, String::from("hello"))])];
+/// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+///
+/// let options = WriteOptions {
+///     float_format: FloatFormat::Fixed(3),
+///     ..Default::default()
+/// };
+/// assert!(tg.to_short_textgrid_string_with(&options).contains("0.300"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteOptions {
+    /// How times are formatted. Defaults to [`FloatFormat::Shortest`].
+    pub float_format: FloatFormat,
+    /// The line ending. Defaults to [`LineEnding::CrLf`] for compatibility with Praat.
+    pub line_ending: LineEnding,
+    /// Whether lines of the long format end with a space, as written by Praat. Defaults to
+    /// `true`; turn it off for cleaner diffs.
+    pub praat_trailing_spaces: bool,
+    /// Whether the output starts with a byte order mark. Defaults to `false`; Praat accepts it,
+    /// and some tools expect it.
+    pub bom: bool,
+    /// The text encoding used by [`TextGrid::to_textgrid_bytes`]. Defaults to
+    /// [`Encoding::Utf8`].
+    pub encoding: Encoding,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            float_format: FloatFormat::Shortest,
+            line_ending: LineEnding::CrLf,
+            praat_trailing_spaces: true,
+            bom: false,
+            encoding: Encoding::Utf8,
+        }
+    }
+}
+
+/// Appends key/value lines kept by [`ParseOptions::preserve_unknown_fields`] to long-format
+/// output, with their values written exactly as they were read.
+///
+/// [`ParseOptions::preserve_unknown_fields`]: crate::ParseOptions::preserve_unknown_fields
+fn push_unknown_fields(
+    output: &mut String,
+    fields: &[(String, String)],
+    indent: &str,
+    sp: &str,
+    nl: &str,
+) {
+    for (key, value) in fields {
+        output.push_str(&format!("{indent}{key} = {value}{sp}{nl}"));
+    }
+}
 
 impl Tier {
     /// Converts the tier to a string representation in long TextGrid format.
@@ -14,44 +277,63 @@ impl Tier {
     ///
     /// Used internally by `TextGrid::to_long_textgrid_string` to serialize tiers.
     pub fn to_long_textgrid_string(&self, index: usize) -> String {
+        self.to_long_textgrid_string_with(index, &WriteOptions::default())
+    }
+
+    /// Converts the tier to a string representation in long TextGrid format, with custom
+    /// write options.
+    ///
+    /// See [`Tier::to_long_textgrid_string`].
+    pub fn to_long_textgrid_string_with(&self, index: usize, options: &WriteOptions) -> String {
+        let time = |value: f64| options.float_format.format(value);
+        let nl = options.line_ending.as_str();
+        let sp = if options.praat_trailing_spaces {
+            " "
+        } else {
+            ""
+        };
         let (tier_class, tier_name) = if self.interval_tier {
             ("IntervalTier", "intervals")
         } else {
             ("TextTier", "points")
         };
         let mut output = format!(
-            "    item [{}]:\r\n        class = \"{}\" \r\n        name = \"{}\" \r\n        xmin = {} \r\n        xmax = {} \r\n        {}: size = {} \r\n",
+            "    item [{}]:{nl}        class = \"{}\"{sp}{nl}        name = \"{}\"{sp}{nl}        xmin = {}{sp}{nl}        xmax = {}{sp}{nl}",
             index + 1,
             tier_class,
-            self.name.replace('"', "\\\""),
-            self.tmin,
-            self.tmax,
+            escape_str(&self.name, nl),
+            time(self.tmin),
+            time(self.tmax),
+        );
+        push_unknown_fields(&mut output, &self.unknown_fields, "        ", sp, nl);
+        output.push_str(&format!(
+            "        {}: size = {}{sp}{nl}",
             tier_name,
             self.items.len()
-        );
+        ));
         let map_fun = |(index, item): (usize, &Item)| -> String {
             match tier_class {
                 "IntervalTier" => {
                     format!(
-                        "        intervals [{}]:\r\n            xmin = {} \r\n            xmax = {} \r\n            text = \"{}\" \r\n",
+                        "        intervals [{}]:{nl}            xmin = {}{sp}{nl}            xmax = {}{sp}{nl}            text = \"{}\"{sp}{nl}",
                         index + 1,
-                        item.tmin,
-                        item.tmax,
-                        item.label.replace('"', "\\\"")
+                        time(item.tmin),
+                        time(item.tmax),
+                        escape_str(&item.label, nl)
                     )
                 }
                 "TextTier" => {
                     format!(
-                        "        points [{}]:\r\n            number = {} \r\n            mark = \"{}\" \r\n",
+                        "        points [{}]:{nl}            number = {}{sp}{nl}            mark = \"{}\"{sp}{nl}",
                         index + 1,
-                        item.tmin,
-                        item.label.replace('"', "\\\"")
+                        time(item.tmin),
+                        escape_str(&item.label, nl)
                     )
                 }
                 _ => String::new(),
             }
         };
-        let item_strings = fast_enumerate_map(&self.items, map_fun, 20);
+        let item_strings = fast_enumerate_map(&self.items, map_fun);
         output.push_str(item_strings.join("").as_str());
         output
     }
@@ -64,41 +346,50 @@ impl Tier {
     ///
     /// Used internally by `TextGrid::to_short_textgrid_string` to serialize tiers.
     pub fn to_short_textgrid_string(&self) -> String {
-        let tier_class;
-        if self.interval_tier {
-            tier_class = "IntervalTier";
+        self.to_short_textgrid_string_with(&WriteOptions::default())
+    }
+
+    /// Converts the tier to a string representation in short TextGrid format, with custom
+    /// write options.
+    ///
+    /// See [`Tier::to_short_textgrid_string`].
+    pub fn to_short_textgrid_string_with(&self, options: &WriteOptions) -> String {
+        let time = |value: f64| options.float_format.format(value);
+        let nl = options.line_ending.as_str();
+        let tier_class = if self.interval_tier {
+            "IntervalTier"
         } else {
-            tier_class = "TextTier";
-        }
+            "TextTier"
+        };
         let mut output = format!(
-            "\"{}\"\r\n\"{}\"\r\n{}\r\n{}\r\n{}\r\n",
+            "\"{}\"{nl}\"{}\"{nl}{}{nl}{}{nl}{}{nl}",
             tier_class,
-            self.name.replace('"', "\\\""),
-            self.tmin,
-            self.tmax,
+            escape_str(&self.name, nl),
+            time(self.tmin),
+            time(self.tmax),
             self.items.len()
         );
         let map_fun = |item: &Item| -> String {
             match tier_class {
                 "IntervalTier" => {
                     format!(
-                        "{}\r\n{}\r\n\"{}\"\r\n",
-                        item.tmin,
-                        item.tmax,
-                        item.label.replace('"', "\\\"")
+                        "{}{nl}{}{nl}\"{}\"{nl}",
+                        time(item.tmin),
+                        time(item.tmax),
+                        escape_str(&item.label, nl)
                     )
                 }
                 "TextTier" => {
                     format!(
-                        "{}\r\n\"{}\"\r\n",
-                        item.tmin,
-                        item.label.replace('"', "\\\"")
+                        "{}{nl}\"{}\"{nl}",
+                        time(item.tmin),
+                        escape_str(&item.label, nl)
                     )
                 }
                 _ => String::new(),
             }
         };
-        let item_strings = fast_map(&self.items, map_fun, 20);
+        let item_strings = fast_map(&self.items, map_fun);
         output.push_str(item_strings.join("").as_str());
         output
     }
@@ -109,22 +400,48 @@ impl TextGrid {
     ///
     /// The long format is the traditional Praat TextGrid format with explicit key-value pairs
     /// and detailed structure. Note: In the long format, many lines are ended with a space character
-    /// for compatibility with Praat.
+    /// for compatibility with Praat; see [`WriteOptions::praat_trailing_spaces`].
     ///
     /// # Returns
     ///
     /// Returns a string containing the complete TextGrid data in long format.
     pub fn to_long_textgrid_string(&self) -> String {
+        self.to_long_textgrid_string_with(&WriteOptions::default())
+    }
+
+    /// Converts the TextGrid to a string representation in long format, with custom write
+    /// options.
+    ///
+    /// See [`TextGrid::to_long_textgrid_string`] and [`WriteOptions`].
+    pub fn to_long_textgrid_string_with(&self, options: &WriteOptions) -> String {
+        let time = |value: f64| options.float_format.format(value);
+        let nl = options.line_ending.as_str();
+        let bom = if options.bom { "\u{feff}" } else { "" };
+        let sp = if options.praat_trailing_spaces {
+            " "
+        } else {
+            ""
+        };
         // Note: In the long format, many lines are ended with a space character.
         // I don't know why and it seems unnecessary, but to be compatible, we add them here.
         let nitems = self.tiers.len();
-        let tiers_existence = if nitems > 0 { "<exists>" } else { "<absent>" };
         let mut output = format!(
-            "File type = \"ooTextFile\"\r\nObject class = \"TextGrid\"\r\n\r\nxmin = {} \r\nxmax = {} \r\ntiers? {} \r\nsize = {} \r\nitem []: \r\n",
-            self.tmin, self.tmax, tiers_existence, nitems,
+            "{bom}File type = \"ooTextFile\"{nl}Object class = \"TextGrid\"{nl}{nl}xmin = {}{sp}{nl}xmax = {}{sp}{nl}",
+            time(self.tmin),
+            time(self.tmax),
         );
+        push_unknown_fields(&mut output, &self.unknown_fields, "", sp, nl);
+        // Like Praat, a TextGrid without tiers has neither a size nor a tier list
+        if nitems > 0 {
+            output.push_str(&format!(
+                "tiers? <exists>{sp}{nl}size = {}{sp}{nl}item []:{sp}{nl}",
+                nitems,
+            ));
+        } else {
+            output.push_str(&format!("tiers? <absent>{sp}{nl}"));
+        }
         for (i, item) in self.tiers.iter().enumerate() {
-            output.push_str(&item.to_long_textgrid_string(i));
+            output.push_str(&item.to_long_textgrid_string_with(i, options));
         }
         output
     }
@@ -137,49 +454,195 @@ impl TextGrid {
     ///
     /// Returns a string containing the complete TextGrid data in short format.
     pub fn to_short_textgrid_string(&self) -> String {
+        self.to_short_textgrid_string_with(&WriteOptions::default())
+    }
+
+    /// Converts the TextGrid to a string representation in short format, with custom write
+    /// options.
+    ///
+    /// See [`TextGrid::to_short_textgrid_string`] and [`WriteOptions`].
+    pub fn to_short_textgrid_string_with(&self, options: &WriteOptions) -> String {
+        let time = |value: f64| options.float_format.format(value);
+        let nl = options.line_ending.as_str();
+        let bom = if options.bom { "\u{feff}" } else { "" };
         let nitems = self.tiers.len();
-        let tiers_existence = if nitems > 0 { "<exists>" } else { "<absent>" };
         let mut output = format!(
-            "File type = \"ooTextFile\"\r\nObject class = \"TextGrid\"\r\n\r\n{}\r\n{}\r\n{}\r\n{}\r\n",
-            self.tmin, self.tmax, tiers_existence, nitems,
+            "{bom}File type = \"ooTextFile\"{nl}Object class = \"TextGrid\"{nl}{nl}{}{nl}{}{nl}",
+            time(self.tmin),
+            time(self.tmax),
         );
+        // Like Praat, a TextGrid without tiers has no tier count
+        if nitems > 0 {
+            output.push_str(&format!("<exists>{nl}{}{nl}", nitems));
+        } else {
+            output.push_str(&format!("<absent>{nl}"));
+        }
         for item in self.tiers.iter() {
-            output.push_str(&item.to_short_textgrid_string());
+            output.push_str(&item.to_short_textgrid_string_with(options));
+        }
+        output
+    }
+
+    /// Converts the TextGrid to a Praat Table text file, as Praat's `Down to Table...` with its
+    /// default settings would create and save it, so Praat scripts written against that table
+    /// can consume it unchanged.
+    ///
+    /// The table has the columns `tmin`, `tier`, `text` and `tmax`, with times written with 6
+    /// decimals. It has one row per item with a non-empty label, points having equal `tmin` and
+    /// `tmax`, sorted by start time; items starting at the same time keep their tier order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::TextGrid;
+    ///
+    /// let tg = TextGrid::builder()
+    ///     .interval_tier("words", |t| t.interval(0.0, 0.5, "hello").interval(0.5, 1.0, ""))
+    ///     .build()
+    ///     .unwrap();
+    /// let table = tg.to_praat_table_string();
+    /// assert!(table.starts_with("File type = \"ooTextFile\"\nObject class = \"Table\"\n"));
+    /// assert!(table.contains("numberOfRows = 1 \n"));
+    /// assert!(table.contains("string = \"hello\" \n"));
+    /// ```
+    pub fn to_praat_table_string(&self) -> String {
+        let time = |value: f64| FloatFormat::Fixed(6).format(value);
+        let mut rows: Vec<(&Item, &str)> = self
+            .tiers
+            .iter()
+            .flat_map(|tier| {
+                tier.items
+                    .iter()
+                    .filter(|item| !item.label.is_empty())
+                    .map(move |item| (item, tier.name.as_str()))
+            })
+            .collect();
+        rows.sort_by(|a, b| a.0.tmin.total_cmp(&b.0.tmin));
+        let columns = ["tmin", "tier", "text", "tmax"];
+        let mut output = format!(
+            "File type = \"ooTextFile\"\nObject class = \"Table\"\n\nnumberOfColumns = {} \ncolumnHeaders []: \n",
+            columns.len()
+        );
+        for (index, column) in columns.iter().enumerate() {
+            output.push_str(&format!(
+                "    columnHeaders [{}]:\n        label = \"{}\" \n",
+                index + 1,
+                column
+            ));
+        }
+        output.push_str(&format!("numberOfRows = {} \nrows []: \n", rows.len()));
+        for (index, (item, tier_name)) in rows.into_iter().enumerate() {
+            output.push_str(&format!(
+                "    rows [{}]:\n        numberOfColumns = {} \n        cells []: \n",
+                index + 1,
+                columns.len()
+            ));
+            let cells = [
+                time(item.tmin),
+                escape_str(tier_name, "\n"),
+                escape_str(&item.label, "\n"),
+                time(item.tmax),
+            ];
+            for (column, cell) in cells.iter().enumerate() {
+                output.push_str(&format!(
+                    "            cells [{}]:\n                string = \"{}\" \n",
+                    column + 1,
+                    cell
+                ));
+            }
         }
         output
     }
 
+    /// Converts the TextGrid to the bytes of a TextGrid file, encoded as set in the options.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The TextGrid format to write
+    /// * `options` - The write options, including the [`Encoding`]
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{Encoding, FileType, TextGridFormat, WriteOptions, read_from_str};
+    ///
+    /// let content = std::fs::read_to_string("input.TextGrid").unwrap();
+    /// let tg = read_from_str(&content, false, FileType::Auto).unwrap();
+    /// let options = WriteOptions {
+    ///     encoding: Encoding::Utf16Le,
+    ///     ..Default::default()
+    /// };
+    /// std::fs::write("output.TextGrid", tg.to_textgrid_bytes(TextGridFormat::Long, &options)).unwrap();
+    /// ```
+    pub fn to_textgrid_bytes(&self, format: TextGridFormat, options: &WriteOptions) -> Vec<u8> {
+        let content = match format {
+            TextGridFormat::Long => self.to_long_textgrid_string_with(options),
+            TextGridFormat::Short => self.to_short_textgrid_string_with(options),
+        };
+        let utf16 = || {
+            let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
+            std::iter::once(0xfeff).chain(content.encode_utf16())
+        };
+        match options.encoding {
+            Encoding::Utf8 => content.into_bytes(),
+            Encoding::Utf16Le => utf16().flat_map(u16::to_le_bytes).collect(),
+            Encoding::Utf16Be => utf16().flat_map(u16::to_be_bytes).collect(),
+        }
+    }
+
     /// Saves the TextGrid to a file.
     ///
     /// # Arguments
     ///
     /// * `filename` - The path where the file will be saved
-    /// * `long` - If `true`, saves in long format; if `false`, saves in short format
+    /// * `format` - The TextGrid format to write
+    /// * `options` - The write options, see [`WriteOptions`]
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if the file cannot be written.
+    /// Returns an error if the file cannot be written.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use textgrid::{TextGrid, read_from_file};
+    /// use textgrid::{FileType, TextGridFormat, WriteOptions, read_from_file};
     ///
-    /// let tg = read_from_file("input.TextGrid", false, "auto").unwrap();
+    /// let tg = read_from_file("input.TextGrid", false, FileType::Auto).unwrap();
     ///
     /// // Save in long format
-    /// tg.save_textgrid("output_long.TextGrid", true);
+    /// tg.save_textgrid("output_long.TextGrid", TextGridFormat::Long, &WriteOptions::default())
+    ///     .unwrap();
     ///
     /// // Save in short format
-    /// tg.save_textgrid("output_short.TextGrid", false);
+    /// tg.save_textgrid("output_short.TextGrid", TextGridFormat::Short, &WriteOptions::default())
+    ///     .unwrap();
     /// ```
-    pub fn save_textgrid(&self, filename: &str, long: bool) {
-        let content = if long {
-            self.to_long_textgrid_string()
+    #[cfg(feature = "fs")]
+    pub fn save_textgrid(
+        &self,
+        filename: &str,
+        format: TextGridFormat,
+        options: &WriteOptions,
+    ) -> std::io::Result<()> {
+        std::fs::write(filename, self.to_textgrid_bytes(format, options))
+    }
+
+    /// Saves the TextGrid to a file, in long format if `long` is `true` and short format
+    /// otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file cannot be written.
+    #[cfg(feature = "fs")]
+    #[deprecated(note = "use `save_textgrid`, which takes a `TextGridFormat` and returns errors")]
+    pub fn save_textgrid_legacy(&self, filename: &str, long: bool) {
+        let format = if long {
+            TextGridFormat::Long
         } else {
-            self.to_short_textgrid_string()
+            TextGridFormat::Short
         };
-        std::fs::write(filename, content).unwrap();
+        self.save_textgrid(filename, format, &WriteOptions::default())
+            .unwrap();
     }
 
     /// Saves the TextGrid to a CSV file.
@@ -187,47 +650,222 @@ impl TextGrid {
     /// # Arguments
     ///
     /// * `filename` - The path where the CSV file will be saved
+    /// * `options` - The CSV options, see [`CsvOptions`]
     ///
     /// # Format
     ///
-    /// The CSV file will have the following columns:
+    /// With the default options, the CSV file will have the following columns:
     /// * `tmin` - Start time of the item
     /// * `tmax` - End time of the item
     /// * `label` - Text label of the item
     /// * `tier` - Name of the tier
     /// * `is_interval` - Whether the tier is an interval tier
     ///
-    /// # Panics
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use textgrid::{CsvOptions, FileType, read_from_file};
+    ///
+    /// let tg = read_from_file("input.TextGrid", false, FileType::Auto).unwrap();
+    /// tg.save_csv("output.csv", &CsvOptions::default()).unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn save_csv(&self, filename: &str, options: &CsvOptions) -> std::io::Result<()> {
+        self.write_csv_to(std::fs::File::create(filename)?, options)
+    }
+
+    /// Writes the items of the TextGrid as CSV to a writer, one row per item.
+    ///
+    /// See [`TextGrid::save_csv`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{CsvColumn, CsvOptions, FloatFormat, TextGrid};
+    ///
+    /// let data = vec![(String::from("words"), true, vec![(0.0, 0.5, String::from("hello"))])];
+    /// let mut tg = TextGrid::from_data(data, None, None, None).unwrap();
+    /// tg.name = String::from("speaker1");
+    ///
+    /// let options = CsvOptions {
+    ///     delimiter: b';',
+    ///     include_filename: true,
+    ///     float_format: FloatFormat::Fixed(2),
+    ///     columns: vec![CsvColumn::Label, CsvColumn::Tmin, CsvColumn::Tmax],
+    ///     ..Default::default()
+    /// };
+    /// let mut output = Vec::new();
+    /// tg.write_csv_to(&mut output, &options).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(output).unwrap(),
+    ///     "\"filename\";\"label\";\"tmin\";\"tmax\"\n\"speaker1\";\"hello\";0.00;0.50\n"
+    /// );
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn write_csv_to<W: std::io::Write>(
+        &self,
+        writer: W,
+        options: &CsvOptions,
+    ) -> std::io::Result<()> {
+        let mut wtr = options.csv_writer(writer)?;
+        self.write_csv_records(&mut wtr, options, &self.name)?;
+        wtr.flush()
+    }
+
+    /// Writes one CSV record per item, with `filename` in the filename column if enabled.
+    #[cfg(feature = "fs")]
+    pub(crate) fn write_csv_records<W: std::io::Write>(
+        &self,
+        wtr: &mut csv::Writer<W>,
+        options: &CsvOptions,
+        filename: &str,
+    ) -> std::io::Result<()> {
+        let time = |value: f64| options.float_format.format(value);
+        let mut record: Vec<String> = Vec::with_capacity(options.columns.len() + 1);
+        for tier in self.tiers.iter() {
+            for item in tier.items.iter() {
+                record.clear();
+                if options.include_filename {
+                    record.push(filename.to_string());
+                }
+                record.extend(options.columns.iter().map(|column| match column {
+                    CsvColumn::Tmin => time(item.tmin),
+                    CsvColumn::Tmax => time(item.tmax),
+                    CsvColumn::Label => item.label.clone(),
+                    CsvColumn::Tier => tier.name.clone(),
+                    CsvColumn::IsInterval => tier.interval_tier.to_string(),
+                }));
+                wtr.write_record(&record)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Saves the items of the TextGrid as a delimited table, such as TSV.
+    ///
+    /// # Arguments
     ///
-    /// Panics if the file cannot be created or written.
+    /// * `filename` - The path where the table will be saved
+    /// * `format` - The delimiter of the table
+    /// * `layout` - Whether to write one row per item or one column block per tier
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use textgrid::read_from_file;
+    /// use textgrid::{FileType, TableFormat, TableLayout, read_from_file};
+    ///
+    /// let tg = read_from_file("input.TextGrid", false, FileType::Auto).unwrap();
+    /// tg.save_table("output.tsv", TableFormat::Tsv, TableLayout::Long).unwrap();
+    /// ```
+    #[cfg(feature = "fs")]
+    pub fn save_table(
+        &self,
+        filename: &str,
+        format: TableFormat,
+        layout: TableLayout,
+    ) -> std::io::Result<()> {
+        self.write_table_to(std::fs::File::create(filename)?, format, layout)
+    }
+
+    /// Writes the items of the TextGrid as a delimited table to a writer.
+    ///
+    /// With [`TableLayout::Long`], the columns are those of [`TextGrid::save_csv`]. With
+    /// [`TableLayout::Wide`], each interval tier contributes `<tier>_tmin`, `<tier>_tmax` and
+    /// `<tier>_label` columns, each point tier `<tier>_time` and `<tier>_label` columns, and the
+    /// `n`-th row holds the `n`-th item of every tier, with empty fields for shorter tiers.
+    ///
+    /// Fields are quoted like [`CsvQuoting::NonNumeric`] for CSV, and only when necessary for
+    /// other formats.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
     ///
-    /// let tg = read_from_file("input.TextGrid", false, "auto").unwrap();
-    /// tg.save_csv("output.csv");
+    /// # Examples
+    ///
+    /// ```
+    /// use textgrid::{TableFormat, TableLayout, TextGrid};
+    ///
+    /// let data = vec![
+    ///     (String::from("words"), true, vec![(0.0, 1.0, String::from("hi"))]),
+    ///     (String::from("tones"), false, vec![(0.2, 0.2, String::from("H")), (0.8, 0.8, String::from("L"))]),
+    /// ];
+    /// let tg = TextGrid::from_data(data, None, None, None).unwrap();
+    ///
+    /// let mut output = Vec::new();
+    /// tg.write_table_to(&mut output, TableFormat::Tsv, TableLayout::Wide).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(output).unwrap(),
+    ///     "words_tmin\twords_tmax\twords_label\ttones_time\ttones_label\n\
+    ///      0\t1\thi\t0.2\tH\n\
+    ///      \t\t\t0.8\tL\n"
+    /// );
     /// ```
-    pub fn save_csv(&self, filename: &str) {
+    #[cfg(feature = "fs")]
+    pub fn write_table_to<W: std::io::Write>(
+        &self,
+        writer: W,
+        format: TableFormat,
+        layout: TableLayout,
+    ) -> std::io::Result<()> {
+        let quoting = match format {
+            TableFormat::Csv => CsvQuoting::NonNumeric,
+            TableFormat::Tsv | TableFormat::Custom(_) => CsvQuoting::Necessary,
+        };
+        let options = CsvOptions {
+            delimiter: format.delimiter(),
+            quoting,
+            ..Default::default()
+        };
+        if layout == TableLayout::Long {
+            return self.write_csv_to(writer, &options);
+        }
         let mut wtr = csv::WriterBuilder::new()
-            .delimiter(b',')
-            .quote_style(csv::QuoteStyle::NonNumeric)
-            .from_writer(std::fs::File::create(filename).unwrap());
-        let (_, _, data) = self.to_data();
-        wtr.write_record(&["tmin", "tmax", "label", "tier", "is_interval"])
-            .unwrap();
-        for (tier_name, is_interval, items) in data {
-            for (tmin, tmax, label) in items {
-                wtr.write_record(&[
-                    tmin.to_string(),
-                    tmax.to_string(),
-                    label,
-                    tier_name.clone(),
-                    is_interval.to_string(),
-                ])
-                .unwrap();
+            .delimiter(options.delimiter)
+            .quote_style(options.quoting.quote_style())
+            .from_writer(writer);
+        let mut header = Vec::new();
+        for tier in self.tiers.iter() {
+            if tier.interval_tier {
+                header.push(format!("{}_tmin", tier.name));
+                header.push(format!("{}_tmax", tier.name));
+            } else {
+                header.push(format!("{}_time", tier.name));
+            }
+            header.push(format!("{}_label", tier.name));
+        }
+        wtr.write_record(&header)?;
+        let n_rows = self.tiers.iter().map(|tier| tier.items.len()).max();
+        let mut record: Vec<String> = Vec::with_capacity(header.len());
+        for row in 0..n_rows.unwrap_or(0) {
+            record.clear();
+            for tier in self.tiers.iter() {
+                let n_columns = if tier.interval_tier { 3 } else { 2 };
+                match tier.items.get(row) {
+                    Some(item) => {
+                        record.push(item.tmin.to_string());
+                        if tier.interval_tier {
+                            record.push(item.tmax.to_string());
+                        }
+                        record.push(item.label.clone());
+                    }
+                    None => record.extend(std::iter::repeat_n(String::new(), n_columns)),
+                }
             }
+            wtr.write_record(&record)?;
         }
+        wtr.flush()
     }
 }
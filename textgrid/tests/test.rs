@@ -7,49 +7,76 @@ mod tests {
 
     static LONG_FILE: &str = "tests/data/long_format.TextGrid";
     static SHORT_FILE: &str = "tests/data/short_format.TextGrid";
+    #[cfg(feature = "fs")]
     static NTIERS: usize = 5;
 
+    #[cfg(feature = "fs")]
     fn assert_tgt_correct(tgt: &TextGrid) {
         assert_eq!(tgt.tiers.len(), NTIERS);
     }
 
+    #[cfg(feature = "fs")]
     #[test]
     fn test_auto_read() {
-        let tgt_long = read_from_file(LONG_FILE, true, "auto").unwrap();
+        let tgt_long = read_from_file(LONG_FILE, true, FileType::Auto).unwrap();
         assert_tgt_correct(&tgt_long);
-        let tgt_short = read_from_file(SHORT_FILE, true, "auto").unwrap();
+        let tgt_short = read_from_file(SHORT_FILE, true, FileType::Auto).unwrap();
         assert_tgt_correct(&tgt_short);
     }
 
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_read_from_str() {
+        let content = fs::read_to_string(LONG_FILE).unwrap();
+        let tgt = read_from_str(&content, true, FileType::Auto).unwrap();
+        let tgt_file = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert_eq!(tgt.to_data(), tgt_file.to_data());
+        assert_eq!(tgt.name, "");
+        assert_eq!(tgt_file.name, "long_format");
+    }
+
+    #[cfg(feature = "fs")]
     #[test]
     fn test_read_write_long() {
-        let tgt = read_from_file(LONG_FILE, true, "long").unwrap();
-        tgt.save_textgrid("tmp_long.TextGrid", true);
+        let tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        tgt.save_textgrid(
+            "tmp_long.TextGrid",
+            TextGridFormat::Long,
+            &WriteOptions::default(),
+        )
+        .unwrap();
         let org_content = fs::read_to_string(LONG_FILE).unwrap();
         let new_content = fs::read_to_string("tmp_long.TextGrid").unwrap();
         assert_eq!(org_content, new_content);
 
-        let new_tgt = read_from_file("tmp_long.TextGrid", true, "long").unwrap();
+        let new_tgt = read_from_file("tmp_long.TextGrid", true, FileType::Long).unwrap();
         assert_eq!(tgt.to_data(), new_tgt.to_data());
         fs::remove_file("tmp_long.TextGrid").unwrap();
     }
+    #[cfg(feature = "fs")]
     #[test]
     fn test_read_write_short() {
-        let tgt = read_from_file(SHORT_FILE, true, "short").unwrap();
-        tgt.save_textgrid("tmp_short.TextGrid", false);
+        let tgt = read_from_file(SHORT_FILE, true, FileType::Short).unwrap();
+        tgt.save_textgrid(
+            "tmp_short.TextGrid",
+            TextGridFormat::Short,
+            &WriteOptions::default(),
+        )
+        .unwrap();
 
         let org_content = fs::read_to_string(SHORT_FILE).unwrap();
         let new_content = fs::read_to_string("tmp_short.TextGrid").unwrap();
         assert_eq!(org_content, new_content);
 
-        let new_tgt = read_from_file("tmp_short.TextGrid", true, "short").unwrap();
+        let new_tgt = read_from_file("tmp_short.TextGrid", true, FileType::Short).unwrap();
         assert_eq!(tgt.to_data(), new_tgt.to_data());
         fs::remove_file("tmp_short.TextGrid").unwrap();
     }
 
+    #[cfg(feature = "fs")]
     #[test]
     fn test_to_data_df() {
-        let tgt = read_from_file(LONG_FILE, true, "long").unwrap();
+        let tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
         let (_, _, tiers) = tgt.to_data();
         let (tmins, tmaxs, labels, tier_names, is_intervals) = tgt.to_vectors();
         let nitems: usize = tiers.iter().map(|tier| tier.2.len()).sum();
@@ -61,9 +88,10 @@ mod tests {
         assert_eq!(tiers.len(), NTIERS);
     }
 
+    #[cfg(feature = "fs")]
     #[test]
     fn test_from_data_df() {
-        let tgt = read_from_file(LONG_FILE, true, "long").unwrap();
+        let tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
         let (tmin, tmax, tiers) = tgt.to_data();
         let (tmins, tmaxs, labels, tier_names, is_intervals) = tgt.to_vectors();
 
@@ -84,4 +112,3479 @@ mod tests {
         assert_eq!(tgt.to_data(), rebuilt_tgt_data.to_data());
         assert_eq!(tgt.to_data(), rebuilt_tgt_vectors.to_data());
     }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_get_tier() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert_eq!(tgt.tier_index("word"), Some(1));
+        assert_eq!(tgt.get_tier("word").unwrap().name, "word");
+        assert!(tgt.get_tier("WORD").is_none());
+        assert_eq!(tgt.find_tier_index("WORD", true), Some(1));
+
+        tgt.get_tier_mut("word").unwrap().name = String::from("words");
+        assert!(tgt.get_tier("word").is_none());
+        assert!(tgt.get_tier("words").is_some());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_item_at() {
+        let tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let (index, item) = tgt.get_tier("word").unwrap().item_at(1361.9).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(item.label, "RIGHT");
+        let (_, point) = tgt.get_tier("points").unwrap().item_at(1396.8225).unwrap();
+        assert_eq!(point.label, "point2");
+        assert!(tgt.get_tier("points").unwrap().item_at(1396.8).is_none());
+
+        let found = tgt.items_at(tgt.tmax);
+        assert_eq!(found.len(), NTIERS);
+        assert_eq!(found[0].unwrap().1.label, "sil");
+        assert!(found[4].is_none());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_items_between() {
+        let tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let words = tgt.get_tier("word").unwrap();
+        let labels: Vec<&str> = words
+            .items_between(1361.8925, 1363.4925, true)
+            .iter()
+            .map(|(_, item)| item.label.as_str())
+            .collect();
+        assert_eq!(labels, vec!["RIGHT", "sp", "THEN"]);
+        assert_eq!(words.items_between(1362.0, 1363.4, false).len(), 3);
+
+        let grouped = tgt.items_between(1396.7, 1396.9, false);
+        assert_eq!(grouped.len(), NTIERS);
+        assert_eq!(grouped[4].len(), 2);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_find_labels() {
+        let tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let matches = tgt.find_labels(&Regex::new(r"^\{(\w+)\}$").unwrap());
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].tier_name, "word");
+        assert_eq!(matches[0].label, "{OOV}");
+        assert_eq!(matches[0].groups[1].as_deref(), Some("OOV"));
+
+        let stressed = tgt
+            .get_tier("phone")
+            .unwrap()
+            .find_labels(&Regex::new(r"1$").unwrap());
+        assert!(stressed.iter().all(|m| m.tier_name == "phone"));
+        assert!(!stressed.is_empty());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_map_labels() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        tgt.map_labels(
+            |label| label.to_lowercase(),
+            Some(&Regex::new("^word$").unwrap()),
+        );
+        assert_eq!(tgt.get_tier("word").unwrap().items[1].label, "right");
+        assert_eq!(tgt.get_tier("phone").unwrap().items[1].label, "R");
+
+        tgt.map_labels(|label| format!("<{}>", label), None);
+        assert_eq!(tgt.get_tier("phone").unwrap().items[1].label, "<R>");
+        assert_eq!(tgt.get_tier("points").unwrap().items[0].label, "<point1>");
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_split_at() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let words = tgt.get_tier_mut("word").unwrap();
+        let nitems = words.items.len();
+        words.split_at(1362.0, "RI", "GHT").unwrap();
+        assert_eq!(words.items.len(), nitems + 1);
+        assert_eq!(words.size, nitems + 1);
+        assert_eq!(words.items[1].label, "RI");
+        assert_eq!(words.items[2].label, "GHT");
+        assert_eq!(words.items[2].tmax, 1362.1625);
+        assert!(words.split_at(1362.0, "a", "b").is_err());
+        assert!(tgt.assert_valid().is_ok());
+
+        let points = tgt.get_tier_mut("points").unwrap();
+        assert!(points.split_at(1370.0, "a", "b").is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_merge_adjacent() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let errors = tgt.get_tier_mut("Errors").unwrap();
+        errors.merge_equal_labels().unwrap();
+        assert_eq!(errors.items.len(), 3);
+
+        errors.merge_adjacent(|_, _| true).unwrap();
+        assert_eq!(errors.items.len(), 1);
+        assert_eq!(errors.size, 1);
+        assert_eq!(errors.items[0].label, ".");
+        assert_eq!(errors.items[0].tmax, 1422.5525);
+        assert!(tgt.assert_valid().is_ok());
+
+        let points = tgt.get_tier_mut("points").unwrap();
+        assert!(points.merge_equal_labels().is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_insert_interval() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let errors = tgt.get_tier_mut("Errors").unwrap();
+        assert!(
+            errors
+                .insert_interval(1360.0, 1363.0, "x", OverlapPolicy::Error)
+                .is_err()
+        );
+
+        errors
+            .insert_interval(1360.0, 1363.0, "x", OverlapPolicy::Split)
+            .unwrap();
+        let spans: Vec<(f64, f64, &str)> = errors
+            .items
+            .iter()
+            .map(|item| (item.tmin, item.tmax, item.label.as_str()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                (1358.8925, 1360.0, "."),
+                (1360.0, 1363.0, "x"),
+                (1363.0, 1363.3325, "loud silence"),
+                (1363.3325, 1422.5525, "."),
+            ]
+        );
+
+        errors
+            .insert_interval(1362.0, 1364.0, "y", OverlapPolicy::Overwrite)
+            .unwrap();
+        let labels: Vec<&str> = errors
+            .items
+            .iter()
+            .map(|item| item.label.as_str())
+            .collect();
+        assert_eq!(labels, vec![".", "y"]);
+        assert_eq!(errors.size, 2);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_remove_item() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let errors = tgt.get_tier_mut("Errors").unwrap();
+        let removed = errors.remove_item(1, GapPolicy::FillEmpty).unwrap();
+        assert_eq!(removed.label, "loud silence");
+        assert_eq!(errors.items.len(), 3);
+        assert_eq!(errors.items[1].label, "");
+
+        assert!(errors.remove_item(2, GapPolicy::MergeRight).is_err());
+        errors.remove_item(2, GapPolicy::MergeLeft).unwrap();
+        assert_eq!(errors.items[1].tmax, 1422.5525);
+        errors.remove_item(0, GapPolicy::MergeRight).unwrap();
+        assert_eq!(errors.items.len(), 1);
+        assert_eq!(errors.items[0].tmin, 1358.8925);
+        errors.remove_item(0, GapPolicy::Leave).unwrap();
+        assert_eq!(errors.size, 0);
+        assert!(errors.remove_item(0, GapPolicy::Leave).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_boundaries() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let words = tgt.get_tier_mut("word").unwrap();
+        let nitems = words.items.len();
+        words.insert_boundary(1362.0).unwrap();
+        assert_eq!(words.items.len(), nitems + 1);
+        assert_eq!(words.items[1].label, "RIGHT");
+        assert_eq!(words.items[2].label, "");
+        assert!(words.insert_boundary(1362.0).is_err());
+
+        words
+            .remove_boundary_at(1362.0, MergeLabelPolicy::Right)
+            .unwrap();
+        assert_eq!(words.items[1].label, "");
+        words
+            .remove_boundary_at(1362.1625, MergeLabelPolicy::Concat)
+            .unwrap();
+        assert_eq!(words.items[1].label, "sp");
+        assert_eq!(words.items[1].tmax, 1363.3325);
+        assert!(
+            words
+                .remove_boundary_at(1362.0, MergeLabelPolicy::Left)
+                .is_err()
+        );
+        assert_eq!(words.size, nitems - 1);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_fill_gaps() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let words = tgt.get_tier_mut("word").unwrap();
+        words.remove_item(0, GapPolicy::Leave).unwrap();
+        words.remove_item(5, GapPolicy::Leave).unwrap();
+        let nitems = words.items.len();
+        words.fill_gaps("").unwrap();
+        assert_eq!(words.items.len(), nitems + 2);
+        assert_eq!(words.items[0].tmin, words.tmin);
+        assert_eq!(words.items[0].label, "");
+        assert_eq!(words.items[6].label, "");
+        assert!(tgt.assert_valid().is_ok());
+
+        let points = tgt.get_tier_mut("points").unwrap();
+        assert!(points.fill_gaps("").is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_shift() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let (_, _, org_tiers) = tgt.to_data();
+        tgt.shift(-tgt.tmin, false).unwrap();
+        assert_eq!(tgt.tmin, 0.0);
+        assert!(tgt.assert_valid().is_ok());
+        tgt.shift(1358.8925, false).unwrap();
+        let (_, _, new_tiers) = tgt.to_data();
+        for (org_tier, new_tier) in org_tiers.iter().zip(new_tiers.iter()) {
+            for (org_item, new_item) in org_tier.2.iter().zip(new_tier.2.iter()) {
+                assert!((org_item.0 - new_item.0).abs() < 1e-9);
+                assert!((org_item.1 - new_item.1).abs() < 1e-9);
+            }
+        }
+
+        tgt.shift(-1362.0, true).unwrap();
+        assert_eq!(tgt.tmin, 0.0);
+        let words = tgt.get_tier("word").unwrap();
+        assert_eq!(words.items[0].label, "RIGHT");
+        assert_eq!(words.items[0].tmin, 0.0);
+        assert!(tgt.assert_valid().is_ok());
+
+        let before = tgt.clone();
+        let err = tgt.shift(-(tgt.tmax + 1.0), true).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(tgt, before);
+        let mut tier = tgt.tiers[0].clone();
+        assert!(tier.shift(-tier.tmax, true).is_err());
+        assert_eq!(tier, before.tiers[0]);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_scale() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let (tmin, tmax) = (tgt.tmin, tgt.tmax);
+        tgt.scale(1.0 / (tmax - tmin), tmin).unwrap();
+        tgt.shift(-tmin, false).unwrap();
+        assert!(tgt.tmin.abs() < 1e-9);
+        assert!((tgt.tmax - 1.0).abs() < 1e-9);
+        let phones = tgt.get_tier("phone").unwrap();
+        assert!((phones.items.last().unwrap().tmax - 1.0).abs() < 1e-9);
+        assert!(tgt.scale(0.0, 0.0).is_err());
+        assert!(tgt.scale(-1.0, 0.0).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_extract() {
+        let tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let part = tgt.extract(1362.0, 1364.0, false).unwrap();
+        assert_eq!(part.tiers.len(), NTIERS);
+        let words = part.get_tier("word").unwrap();
+        let labels: Vec<&str> = words.items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["RIGHT", "sp", "THEN", "sp"]);
+        assert_eq!(words.items[0].tmin, 1362.0);
+        assert_eq!(words.items[3].tmax, 1364.0);
+        assert!(part.get_tier("points").unwrap().items.is_empty());
+
+        let rebased = tgt.extract(1362.0, 1364.0, true).unwrap();
+        assert_eq!(rebased.tmin, 0.0);
+        assert_eq!(rebased.tmax, 2.0);
+        assert_eq!(rebased.get_tier("word").unwrap().items[0].tmin, 0.0);
+        assert!(tgt.extract(2.0, 1.0, false).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_merge_with() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let other = read_from_file(SHORT_FILE, true, FileType::Short).unwrap();
+        tgt.merge_with(other, "_b");
+        assert_eq!(tgt.tiers.len(), NTIERS * 2);
+        assert_eq!(tgt.size, NTIERS * 2);
+        assert_eq!(tgt.tiers[NTIERS].name, "phone_b");
+
+        let again = read_from_file(SHORT_FILE, true, FileType::Short).unwrap();
+        tgt.merge_with(again, "_b");
+        assert_eq!(tgt.tiers[NTIERS * 2].name, "phone_b2");
+        assert!(tgt.assert_valid().is_ok());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_tier_management() {
+        let mut tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert!(tgt.rename_tier("word", "phone").is_err());
+        tgt.rename_tier("word", "words").unwrap();
+        assert_eq!(tgt.tiers[1].name, "words");
+
+        tgt.duplicate_tier("words", "words_copy").unwrap();
+        assert_eq!(tgt.size, NTIERS + 1);
+        assert_eq!(tgt.tiers[2].name, "words_copy");
+        assert_eq!(tgt.tiers[2].items.len(), tgt.tiers[1].items.len());
+
+        tgt.move_tier("points", 0).unwrap();
+        assert_eq!(tgt.tiers[0].name, "points");
+        assert!(tgt.move_tier(0, NTIERS + 1).is_err());
+
+        let removed = tgt.remove_tier("words_copy").unwrap();
+        assert_eq!(removed.name, "words_copy");
+        tgt.remove_tier(0).unwrap();
+        assert_eq!(tgt.size, NTIERS - 1);
+        assert!(tgt.remove_tier("points").is_err());
+        assert!(tgt.remove_tier(NTIERS).is_err());
+        assert!(tgt.assert_valid().is_ok());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_point_interval_conversion() {
+        let tgt = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let words = tgt.get_tier("word").unwrap();
+        let ends = words.to_point_tier(PointFrom::Ends).unwrap();
+        assert_eq!(ends.items.len(), words.items.len());
+        assert_eq!(ends.items[0].tmin, words.items[0].tmax);
+        assert!(ends.assert_valid().is_ok());
+        assert!(ends.to_point_tier(PointFrom::Starts).is_err());
+
+        let points = tgt.get_tier("points").unwrap();
+        let windows = points.to_interval_tier(IntervalFrom::Window(1.0)).unwrap();
+        assert_eq!(windows.items.len(), 3);
+        assert_eq!(windows.items[1].tmax, (1396.7125 + 1396.8225) / 2.0);
+        assert!(windows.assert_valid().is_ok());
+        assert!(points.to_interval_tier(IntervalFrom::Window(0.0)).is_err());
+
+        let pairs = points.to_interval_tier(IntervalFrom::PairUp).unwrap();
+        assert_eq!(pairs.items.len(), 1);
+        assert_eq!(pairs.items[0].tmax, 1396.7125);
+        assert_eq!(pairs.items[0].label, "point1");
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_diff() {
+        let old = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert!(old.diff(&old, 0.0).is_empty());
+
+        let mut new = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        new.rename_tier("mute", "silence").unwrap();
+        new.remove_tier("points").unwrap();
+        new.duplicate_tier("word", "word_copy").unwrap();
+        let words = new.get_tier_mut("word").unwrap();
+        words.items[1].label = String::from("LEFT");
+        words.items[2].tmin += 0.05;
+        words.items[1].tmax += 0.05;
+
+        let diff = old.diff(&new, 0.01);
+        assert_eq!(diff.added_tiers, vec!["word_copy"]);
+        assert_eq!(diff.removed_tiers, vec!["points"]);
+        assert_eq!(
+            diff.renamed_tiers,
+            vec![(String::from("mute"), String::from("silence"))]
+        );
+        assert_eq!(diff.tier_diffs.len(), 1);
+        let changes = &diff.tier_diffs[0].changes;
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(changes[0], ItemChange::Moved { old_index: 1, .. }));
+        assert!(matches!(
+            changes[1],
+            ItemChange::Relabeled { old_index: 1, .. }
+        ));
+        assert!(matches!(changes[2], ItemChange::Moved { old_index: 2, .. }));
+        assert!(diff.to_string().contains("\"RIGHT\" -> \"LEFT\""));
+
+        let mut longer = old.clone();
+        longer.tmax += 3.0;
+        longer.tiers[0].tmax += 3.0;
+        let diff = old.diff(&longer, 0.0);
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.bounds,
+            Some(BoundsChange {
+                old: (old.tmin, old.tmax),
+                new: (longer.tmin, longer.tmax),
+            })
+        );
+        assert_eq!(diff.tier_diffs.len(), 1);
+        assert!(diff.tier_diffs[0].bounds.is_some());
+        assert!(diff.tier_diffs[0].changes.is_empty());
+        assert!(diff.to_string().contains("~ bounds"));
+        assert!(old.diff(&longer, 3.0).is_empty());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_agreement() {
+        let a = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let mut b = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+
+        let same = a.agreement(&b, 0.001, 0.01).unwrap();
+        assert_eq!(same.len(), 4);
+        for tier in &same {
+            assert_eq!(tier.boundary_agreement, 1.0);
+            assert_eq!(tier.kappa, 1.0);
+            assert_eq!(tier.label_overlap, 1.0);
+        }
+
+        let words = b.get_tier_mut("word").unwrap();
+        let n_boundaries = words.boundaries().len();
+        words.items[1].tmax += 0.01;
+        words.items[2].tmin += 0.01;
+        words.items[3].label = String::from("CHANGED");
+        let word_a = a.get_tier("word").unwrap();
+        let word_b = b.get_tier("word").unwrap();
+        let expected = (n_boundaries - 1) as f64 / n_boundaries as f64;
+        assert!((agreement::boundary_agreement(word_a, word_b, 0.005) - expected).abs() < 1e-9);
+        assert_eq!(agreement::boundary_agreement(word_a, word_b, 0.02), 1.0);
+        assert!(agreement::cohen_kappa(word_a, word_b, 0.01).unwrap() < 1.0);
+        assert!(agreement::label_overlap_ratio(word_a, word_b).unwrap() < 1.0);
+        assert!(agreement::cohen_kappa(word_a, a.get_tier("points").unwrap(), 0.01).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_align_boundaries() {
+        let reference = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let mut hypothesis = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let phones = hypothesis.get_tier_mut("phone").unwrap();
+        let n_boundaries = phones.boundaries().len();
+        phones.items[0].tmax += 0.05;
+        phones.items[1].tmin += 0.05;
+
+        let reference = reference.get_tier("phone").unwrap();
+        let hypothesis = hypothesis.get_tier("phone").unwrap();
+        let result = align_boundaries(reference, hypothesis, 0.01);
+        assert_eq!(result.matched.len(), n_boundaries - 1);
+        assert_eq!(result.unmatched_reference.len(), 1);
+        assert_eq!(result.unmatched_hypothesis.len(), 1);
+        assert_eq!(result.precision, result.recall);
+        assert!((result.f1 - result.precision).abs() < 1e-12);
+
+        let loose = align_boundaries(reference, hypothesis, 0.1);
+        assert_eq!(loose.f1, 1.0);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_coverage_report() {
+        let mut tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let report = tg.coverage_report(&[]).unwrap();
+        assert_eq!(report.tiers.len(), 4);
+        assert!(report.is_clean());
+
+        let removed = tg
+            .get_tier_mut("word")
+            .unwrap()
+            .remove_item(1, GapPolicy::Leave)
+            .unwrap();
+        let report = tg.coverage_report(&[("phone", "word")]).unwrap();
+        let words = &report.tiers[1];
+        assert_eq!(words.tier_name, "word");
+        assert_eq!(words.gaps.len(), 1);
+        assert!((words.gap_duration() - (removed.tmax - removed.tmin)).abs() < 1e-9);
+        assert!(words.overlaps.is_empty());
+        assert!(report.pair_overlaps[0].overlap_duration() > 0.0);
+        assert!(tg.coverage_report(&[("phone", "points")]).is_err());
+        assert!(tg.coverage_report(&[("phone", "missing")]).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_link_tiers() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let hierarchy = tg.link_tiers("word", "phone", 1e-4).unwrap();
+        let words = tg.get_tier("word").unwrap();
+        let phones = tg.get_tier("phone").unwrap();
+
+        let mut n_children = 0;
+        for (word, children) in hierarchy.iter() {
+            for phone in children {
+                assert!(phone.tmin >= word.tmin - 1e-4 && phone.tmax <= word.tmax + 1e-4);
+            }
+            n_children += children.len();
+        }
+        assert_eq!(n_children + hierarchy.orphans().len(), phones.items.len());
+        for index in 0..words.items.len() {
+            if let Some(range) = hierarchy.child_range(index) {
+                for child_index in range {
+                    assert_eq!(hierarchy.parent_of(child_index), Some(index));
+                }
+            }
+        }
+        assert!(hierarchy.children_of(words.items.len()).is_empty());
+
+        let points = tg.link_tiers("word", "points", 0.0).unwrap();
+        assert_eq!(points.orphans().len(), 0);
+        assert!(tg.link_tiers("points", "word", 0.0).is_err());
+        assert!(tg.link_tiers("word", "missing", 0.0).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_float_format() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert_eq!(
+            tg.to_long_textgrid_string_with(&WriteOptions::default()),
+            tg.to_long_textgrid_string()
+        );
+
+        let data = vec![(
+            String::from("words"),
+            true,
+            vec![
+                (0.0, 0.1 + 0.2, String::from("a")),
+                (0.1 + 0.2, 1.0, String::from("b")),
+            ],
+        )];
+        let tg = TextGrid::from_data(data, None, None, None).unwrap();
+        assert!(
+            tg.to_short_textgrid_string()
+                .contains("0.30000000000000004")
+        );
+        let options = WriteOptions {
+            float_format: FloatFormat::Significant(15),
+            ..Default::default()
+        };
+        let short = tg.to_short_textgrid_string_with(&options);
+        assert!(!short.contains("0.30000000000000004"));
+        assert!(short.contains("\r\n0.3\r\n"));
+
+        let options = WriteOptions {
+            float_format: FloatFormat::Fixed(4),
+            ..Default::default()
+        };
+        let long = tg.to_long_textgrid_string_with(&options);
+        assert!(long.contains("xmax = 0.3000 "));
+        let reread = read_from_str(&long, true, FileType::Long).unwrap();
+        assert_eq!(reread.tiers[0].items[1].tmin, 0.3);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_line_ending() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let options = WriteOptions {
+            line_ending: LineEnding::Lf,
+            ..Default::default()
+        };
+        let long = tg.to_long_textgrid_string_with(&options);
+        let short = tg.to_short_textgrid_string_with(&options);
+        assert!(!long.contains('\r'));
+        assert!(!short.contains('\r'));
+        assert_eq!(long, tg.to_long_textgrid_string().replace("\r\n", "\n"));
+        assert_eq!(short, tg.to_short_textgrid_string().replace("\r\n", "\n"));
+
+        let reread = read_from_str(&long, true, FileType::Long).unwrap();
+        assert_eq!(
+            reread.to_long_textgrid_string(),
+            tg.to_long_textgrid_string()
+        );
+        let reread = read_from_str(&short, true, FileType::Short).unwrap();
+        assert_eq!(
+            reread.to_short_textgrid_string(),
+            tg.to_short_textgrid_string()
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_praat_trailing_spaces() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let options = WriteOptions {
+            praat_trailing_spaces: false,
+            ..Default::default()
+        };
+        let long = tg.to_long_textgrid_string_with(&options);
+        assert!(!long.contains(" \r\n"));
+        assert_eq!(long, tg.to_long_textgrid_string().replace(" \r\n", "\r\n"));
+        assert_eq!(
+            tg.to_short_textgrid_string_with(&options),
+            tg.to_short_textgrid_string()
+        );
+        let reread = read_from_str(&long, true, FileType::Long).unwrap();
+        assert_eq!(
+            reread.to_long_textgrid_string(),
+            tg.to_long_textgrid_string()
+        );
+    }
+
+    #[test]
+    fn test_quote_escaping() {
+        let data = vec![(
+            String::from("say \"hi\""),
+            true,
+            vec![
+                (0.0, 0.5, String::from("he said \"hello\"")),
+                (0.5, 1.0, String::from("\"")),
+            ],
+        )];
+        let tg = TextGrid::from_data(data, None, None, None).unwrap();
+        let long = tg.to_long_textgrid_string();
+        let short = tg.to_short_textgrid_string();
+        assert!(long.contains("text = \"he said \"\"hello\"\"\" "));
+        assert!(short.contains("\r\n\"\"\"\"\r\n"));
+        for reread in [
+            read_from_str(&long, true, FileType::Long).unwrap(),
+            read_from_str(&short, true, FileType::Short).unwrap(),
+        ] {
+            assert_eq!(reread.tiers[0].name, "say \"hi\"");
+            assert_eq!(reread.tiers[0].items[0].label, "he said \"hello\"");
+            assert_eq!(reread.tiers[0].items[1].label, "\"");
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_bom() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let options = WriteOptions {
+            bom: true,
+            ..Default::default()
+        };
+        let long = tg.to_long_textgrid_string_with(&options);
+        let short = tg.to_short_textgrid_string_with(&options);
+        assert!(long.starts_with('\u{feff}'));
+        assert!(short.starts_with('\u{feff}'));
+        assert!(!tg.to_long_textgrid_string().starts_with('\u{feff}'));
+
+        for (content, file_type) in [
+            (&long, FileType::Long),
+            (&short, FileType::Short),
+            (&long, FileType::Auto),
+        ] {
+            let reread = read_from_str(content, true, file_type).unwrap();
+            assert_eq!(
+                reread.to_long_textgrid_string(),
+                tg.to_long_textgrid_string()
+            );
+        }
+    }
+
+    #[test]
+    fn test_utf16_encoding() {
+        let data = vec![(
+            String::from("words"),
+            true,
+            vec![(0.0, 1.0, String::from("héllo wörld 你好"))],
+        )];
+        let tg = TextGrid::from_data(data, None, None, None).unwrap();
+        let expected = tg.to_long_textgrid_string();
+        assert_eq!(
+            tg.to_textgrid_bytes(TextGridFormat::Long, &WriteOptions::default()),
+            expected.as_bytes()
+        );
+
+        let le = tg.to_textgrid_bytes(
+            TextGridFormat::Long,
+            &WriteOptions {
+                encoding: Encoding::Utf16Le,
+                ..Default::default()
+            },
+        );
+        assert_eq!(&le[..2], &[0xff, 0xfe]);
+        let units: Vec<u16> = le[2..]
+            .chunks(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        assert_eq!(String::from_utf16(&units).unwrap(), expected);
+
+        let be = tg.to_textgrid_bytes(
+            TextGridFormat::Short,
+            &WriteOptions {
+                encoding: Encoding::Utf16Be,
+                bom: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(&be[..4], &[0xfe, 0xff, 0x00, b'F']);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_save_textgrid_errors() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let result = tg.save_textgrid(
+            "no_such_dir/tmp.TextGrid",
+            TextGridFormat::Long,
+            &WriteOptions::default(),
+        );
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+        let options = WriteOptions {
+            line_ending: LineEnding::Lf,
+            ..Default::default()
+        };
+        tg.save_textgrid("tmp_options.TextGrid", TextGridFormat::Short, &options)
+            .unwrap();
+        let content = fs::read_to_string("tmp_options.TextGrid").unwrap();
+        assert_eq!(content, tg.to_short_textgrid_string_with(&options));
+        fs::remove_file("tmp_options.TextGrid").unwrap();
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_save_csv() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        tg.save_csv("tmp_default.csv", &CsvOptions::default())
+            .unwrap();
+        let content = fs::read_to_string("tmp_default.csv").unwrap();
+        fs::remove_file("tmp_default.csv").unwrap();
+        let n_items: usize = tg.tiers.iter().map(|tier| tier.items.len()).sum();
+        assert_eq!(content.lines().count(), n_items + 1);
+        assert!(content.starts_with("\"tmin\",\"tmax\",\"label\",\"tier\",\"is_interval\"\n"));
+
+        let options = CsvOptions {
+            delimiter: b'\t',
+            quoting: CsvQuoting::Necessary,
+            include_filename: true,
+            columns: vec![CsvColumn::Tier, CsvColumn::Label],
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        tg.write_csv_to(&mut output, &options).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("filename\ttier\tlabel"));
+        let first = &tg.tiers[0].items[0];
+        assert_eq!(
+            lines.next().unwrap(),
+            format!("long_format\t{}\t{}", tg.tiers[0].name, first.label)
+        );
+
+        assert!(
+            tg.save_csv("no_such_dir/tmp.csv", &CsvOptions::default())
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_save_table() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        tg.save_table("tmp_table.tsv", TableFormat::Tsv, TableLayout::Long)
+            .unwrap();
+        let tsv = fs::read_to_string("tmp_table.tsv").unwrap();
+        fs::remove_file("tmp_table.tsv").unwrap();
+        assert!(tsv.starts_with("tmin\ttmax\tlabel\ttier\tis_interval\n"));
+
+        let mut csv = Vec::new();
+        tg.write_table_to(&mut csv, TableFormat::Csv, TableLayout::Long)
+            .unwrap();
+        let mut default_csv = Vec::new();
+        tg.write_csv_to(&mut default_csv, &CsvOptions::default())
+            .unwrap();
+        assert_eq!(csv, default_csv);
+
+        let mut wide = Vec::new();
+        tg.write_table_to(&mut wide, TableFormat::Custom(b'|'), TableLayout::Wide)
+            .unwrap();
+        let wide = String::from_utf8(wide).unwrap();
+        let max_items = tg.tiers.iter().map(|tier| tier.items.len()).max().unwrap();
+        assert_eq!(wide.lines().count(), max_items + 1);
+        let header = wide.lines().next().unwrap();
+        assert!(header.starts_with("phone_tmin|phone_tmax|phone_label|word_tmin"));
+        assert!(header.ends_with("points_time|points_label"));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_read_from_file_mmap() {
+        for (file, file_type) in [(LONG_FILE, FileType::Long), (SHORT_FILE, FileType::Short)] {
+            let mapped = read_from_file_mmap(file, true, file_type).unwrap();
+            let read = read_from_file(file, true, file_type).unwrap();
+            assert_eq!(mapped.name, read.name);
+            assert_eq!(mapped.to_data(), read.to_data());
+        }
+        assert!(read_from_file_mmap("no_such_file.TextGrid", true, FileType::Auto).is_err());
+
+        fs::write("tmp_invalid.TextGrid", [0xff, 0xfe, 0x00]).unwrap();
+        let result = read_from_file_mmap("tmp_invalid.TextGrid", true, FileType::Auto);
+        fs::remove_file("tmp_invalid.TextGrid").unwrap();
+        assert_eq!(
+            result.err().unwrap().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_read_from_str_ref() {
+        for (file, file_type) in [(LONG_FILE, FileType::Long), (SHORT_FILE, FileType::Short)] {
+            let content = fs::read_to_string(file).unwrap();
+            let borrowed = read_from_str_ref(&content, true, file_type).unwrap();
+            let owned = read_from_str(&content, true, file_type).unwrap();
+            assert_eq!(borrowed.tiers.len(), owned.tiers.len());
+            for (tier_ref, tier) in borrowed.tiers.iter().zip(owned.tiers.iter()) {
+                assert_eq!(tier_ref.name, tier.name.as_str());
+                assert!(tier_ref.items.iter().zip(tier.items.iter()).all(|(a, b)| {
+                    a.tmin == b.tmin && a.tmax == b.tmax && a.label == b.label.as_str()
+                }));
+            }
+            assert_eq!(borrowed.into_owned().to_data(), owned.to_data());
+        }
+
+        let tg = TextGrid::from_data(
+            vec![(
+                String::from("words"),
+                true,
+                vec![
+                    (0.0, 0.5, String::from("plain")),
+                    (0.5, 1.0, String::from("say \"hi\"")),
+                ],
+            )],
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let content = tg.to_long_textgrid_string();
+        let borrowed = read_from_str_ref(&content, true, FileType::Auto).unwrap();
+        let items = &borrowed.get_tier("words").unwrap().items;
+        assert!(matches!(
+            items[0].label,
+            std::borrow::Cow::Borrowed("plain")
+        ));
+        assert!(matches!(items[1].label, std::borrow::Cow::Owned(_)));
+        assert_eq!(items[1].label, "say \"hi\"");
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_parallel_config() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let expected = tg.to_data();
+        assert_eq!(parallel_config(), ParallelConfig::default());
+
+        let configs = [
+            ParallelConfig {
+                num_threads: Some(2),
+                min_len: 1,
+                ..Default::default()
+            },
+            ParallelConfig {
+                enabled: false,
+                ..Default::default()
+            },
+        ];
+        for config in configs {
+            set_parallel_config(config).unwrap();
+            assert_eq!(parallel_config(), config);
+            assert_eq!(tg.to_data(), expected);
+            let files = vec![String::from(LONG_FILE), String::from(SHORT_FILE)];
+            assert_eq!(
+                files_to_data(&files, true, FileType::Auto)[0]
+                    .as_ref()
+                    .unwrap(),
+                &expected
+            );
+        }
+
+        let zero_threads = ParallelConfig {
+            num_threads: Some(0),
+            ..Default::default()
+        };
+        assert!(set_parallel_config(zero_threads).is_err());
+        set_parallel_config(ParallelConfig::default()).unwrap();
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_read_from_file_filtered() {
+        let keep = ["word", "points"];
+        for (file, file_type) in [(LONG_FILE, FileType::Long), (SHORT_FILE, FileType::Short)] {
+            let full = read_from_file(file, true, file_type).unwrap();
+            let filtered =
+                read_from_file_filtered(file, true, file_type, |name| keep.contains(&name))
+                    .unwrap();
+            assert_eq!(filtered.name, full.name);
+            assert_eq!(filtered.size, keep.len());
+            let expected: Vec<TierData> = full
+                .to_data()
+                .2
+                .into_iter()
+                .filter(|(name, _, _)| keep.contains(&name.as_str()))
+                .collect();
+            assert_eq!(filtered.to_data().2, expected);
+
+            let none = read_from_file_filtered(file, true, file_type, |_| false).unwrap();
+            assert_eq!(none.size, 0);
+            assert!(none.tiers.is_empty());
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_files_to_data_errors() {
+        let files = vec![
+            String::from(LONG_FILE),
+            String::from("no_such_file.TextGrid"),
+            String::from(SHORT_FILE),
+        ];
+        let data = files_to_data(&files, true, FileType::Auto);
+        assert_eq!(data.len(), 3);
+        assert!(data[0].is_ok() && data[2].is_ok());
+        let error = data[1].as_ref().err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert!(error.to_string().starts_with("no_such_file.TextGrid: "));
+
+        let vectors = files_to_vectors(&files, true, FileType::Auto);
+        assert_eq!(vectors.len(), 3);
+        assert!(vectors[0].is_ok() && vectors[1].is_err() && vectors[2].is_ok());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_read_corpus() {
+        fs::create_dir_all("tmp_corpus/sub").unwrap();
+        fs::copy(LONG_FILE, "tmp_corpus/long.TextGrid").unwrap();
+        fs::copy(SHORT_FILE, "tmp_corpus/sub/short.textgrid").unwrap();
+        fs::write("tmp_corpus/sub/bad.TextGrid", [0xff, 0xfe, 0x00]).unwrap();
+        fs::write("tmp_corpus/notes.txt", "not a TextGrid").unwrap();
+
+        let flat = read_corpus("tmp_corpus", false, true);
+        let nested = read_corpus("tmp_corpus", true, true);
+        let pattern = read_corpus("tmp_corpus/**/*.TextGrid", false, true);
+        fs::remove_dir_all("tmp_corpus").unwrap();
+
+        let flat = flat.unwrap();
+        let keys: Vec<_> = flat.textgrids.keys().cloned().collect();
+        assert_eq!(keys, vec![std::path::PathBuf::from("long.TextGrid")]);
+        assert!(flat.failures.is_empty());
+        assert_eq!(flat.textgrids[&keys[0]].name, "long");
+
+        let nested = nested.unwrap();
+        assert_eq!(nested.textgrids.len(), 2);
+        assert!(
+            nested
+                .textgrids
+                .contains_key(std::path::Path::new("sub/short.textgrid"))
+        );
+        let failures: Vec<_> = nested.failures.keys().cloned().collect();
+        assert_eq!(failures, vec![std::path::PathBuf::from("sub/bad.TextGrid")]);
+
+        let pattern = pattern.unwrap();
+        assert_eq!(pattern.textgrids.len(), 1);
+        assert_eq!(pattern.failures.len(), 1);
+        assert!(read_corpus("tmp_corpus/[", false, true).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_write_many() {
+        let long = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let short = read_from_file(SHORT_FILE, true, FileType::Short).unwrap();
+        let grids = vec![
+            (String::from("tmp_many_long.TextGrid"), &long),
+            (String::from("no_such_dir/tmp_many.TextGrid"), &long),
+            (String::from("tmp_many_short.TextGrid"), &short),
+        ];
+        let results = write_many(&grids, TextGridFormat::Long, &WriteOptions::default());
+        let written_long = read_from_file("tmp_many_long.TextGrid", true, FileType::Long);
+        let written_short = read_from_file("tmp_many_short.TextGrid", true, FileType::Long);
+        fs::remove_file("tmp_many_long.TextGrid").unwrap();
+        fs::remove_file("tmp_many_short.TextGrid").unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok() && results[2].is_ok());
+        let error = results[1].as_ref().err().unwrap();
+        assert!(
+            error
+                .to_string()
+                .starts_with("no_such_dir/tmp_many.TextGrid: ")
+        );
+        assert_eq!(written_long.unwrap().to_data(), long.to_data());
+        assert_eq!(written_short.unwrap().to_data(), short.to_data());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_convert_files() {
+        fs::create_dir_all("tmp_convert_in/sub").unwrap();
+        fs::copy(LONG_FILE, "tmp_convert_in/long.TextGrid").unwrap();
+        fs::copy(SHORT_FILE, "tmp_convert_in/sub/short.TextGrid").unwrap();
+        fs::write("tmp_convert_in/sub/bad.TextGrid", [0xff, 0xfe, 0x00]).unwrap();
+
+        fn drop_points(tg: &mut TextGrid) -> std::io::Result<()> {
+            tg.remove_tier("points").map(|_| ())
+        }
+        let options = ConvertOptions {
+            strict: true,
+            repair: Some(drop_points),
+            ..Default::default()
+        };
+        let short = convert_files(
+            "tmp_convert_in",
+            "tmp_convert_out",
+            FileType::Auto,
+            ConvertTarget::Short,
+            &options,
+        )
+        .unwrap();
+        let json = convert_files(
+            "tmp_convert_in/*.TextGrid",
+            "tmp_convert_out",
+            FileType::Auto,
+            ConvertTarget::Json,
+            &ConvertOptions::default(),
+        )
+        .unwrap();
+        let converted = read_from_file("tmp_convert_out/sub/short.TextGrid", true, FileType::Short);
+        let json_data = fs::read_to_string("tmp_convert_out/long.json");
+        fs::remove_dir_all("tmp_convert_in").unwrap();
+        fs::remove_dir_all("tmp_convert_out").unwrap();
+
+        assert_eq!(short.len(), 3);
+        assert!(short[std::path::Path::new("sub/bad.TextGrid")].is_err());
+        assert_eq!(
+            short[std::path::Path::new("long.TextGrid")]
+                .as_ref()
+                .unwrap(),
+            std::path::Path::new("tmp_convert_out/long.TextGrid")
+        );
+        let mut expected = read_from_file(SHORT_FILE, true, FileType::Short).unwrap();
+        drop_points(&mut expected).unwrap();
+        assert_eq!(converted.unwrap().to_data(), expected.to_data());
+
+        assert_eq!(json.len(), 1);
+        let data: TextGridData = serde_json::from_str(&json_data.unwrap()).unwrap();
+        assert_eq!(
+            data,
+            read_from_file(LONG_FILE, true, FileType::Long)
+                .unwrap()
+                .to_data()
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_files_to_csv() {
+        let files = vec![
+            String::from(LONG_FILE),
+            String::from("no_such_file.TextGrid"),
+            String::from(SHORT_FILE),
+        ];
+        let options = CsvOptions {
+            delimiter: b'\t',
+            ..Default::default()
+        };
+        let results =
+            files_to_csv(&files, "tmp_corpus.tsv", true, FileType::Auto, &options).unwrap();
+        let content = fs::read_to_string("tmp_corpus.tsv").unwrap();
+        fs::remove_file("tmp_corpus.tsv").unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok() && results[1].is_err() && results[2].is_ok());
+        let mut lines = content.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "\"filename\"\t\"tmin\"\t\"tmax\"\t\"label\"\t\"tier\"\t\"is_interval\""
+        );
+        let n_items = read_from_file(LONG_FILE, true, FileType::Long)
+            .unwrap()
+            .to_vectors()
+            .0
+            .len();
+        let rows: Vec<&str> = lines.collect();
+        assert_eq!(rows.len(), 2 * n_items);
+        let quoted = |file: &str| format!("\"{}\"\t", file);
+        assert!(rows[0].starts_with(&quoted(LONG_FILE)));
+        assert!(rows[n_items].starts_with(&quoted(SHORT_FILE)));
+    }
+
+    #[test]
+    fn test_textgrid_builder() {
+        let tg = TextGrid::builder()
+            .name("built")
+            .interval_tier("words", |t| {
+                t.interval(0.0, 0.4, "hi").interval(0.4, 1.2, "there")
+            })
+            .point_tier("beats", |t| t.point(0.2, "b1").point(0.9, "b2"))
+            .tier(TierBuilder::intervals("empty"))
+            .build()
+            .unwrap();
+        assert_eq!(tg.name, "built");
+        assert_eq!((tg.tmin, tg.tmax, tg.size), (0.0, 1.2, 3));
+        assert!(
+            tg.tiers
+                .iter()
+                .all(|tier| tier.tmin == 0.0 && tier.tmax == 1.2)
+        );
+        assert_eq!(tg.tiers[0].size, 2);
+        assert!(!tg.tiers[1].interval_tier);
+        assert_eq!(tg.tiers[1].items[1].tmin, tg.tiers[1].items[1].tmax);
+        assert!(tg.tiers[2].items.is_empty());
+
+        let overlapping = TextGrid::builder()
+            .interval_tier("words", |t| {
+                t.interval(0.0, 0.6, "a").interval(0.5, 1.0, "b")
+            })
+            .build();
+        assert!(overlapping.is_err());
+    }
+
+    #[test]
+    fn test_tier_builder() {
+        let tier = TierBuilder::intervals("words")
+            .interval(0.5, 1.0, "b")
+            .interval(0.0, 0.2, "a")
+            .build()
+            .unwrap();
+        let spans: Vec<(f64, f64)> = tier.items.iter().map(|i| (i.tmin, i.tmax)).collect();
+        assert_eq!(spans, vec![(0.0, 0.2), (0.5, 1.0)]);
+        assert_eq!((tier.tmin, tier.tmax, tier.size), (0.0, 1.0, 2));
+
+        let filled = TierBuilder::intervals("words")
+            .bounds(0.0, 1.5)
+            .interval(0.5, 1.0, "b")
+            .interval(0.0, 0.2, "a")
+            .fill_gaps("sil")
+            .build()
+            .unwrap();
+        let labels: Vec<&str> = filled.items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["a", "sil", "b", "sil"]);
+        assert_eq!(filled.size, 4);
+
+        let points = TierBuilder::points("beats")
+            .point(0.7, "b2")
+            .point(0.1, "b1")
+            .build()
+            .unwrap();
+        assert_eq!(points.items[0].label, "b1");
+        assert_eq!((points.tmin, points.tmax), (0.0, 0.7));
+
+        let single = TierBuilder::points("p").point(1.0, "a").build().unwrap();
+        assert_eq!((single.tmin, single.tmax), (0.0, 1.0));
+        let from_grid = TextGrid::builder()
+            .point_tier("p", |t| t.point(1.0, "a"))
+            .build()
+            .unwrap();
+        assert_eq!(single, from_grid.tiers[0]);
+        for builder in [
+            TierBuilder::points("p").point(0.0, "a"),
+            TierBuilder::intervals("w"),
+        ] {
+            let err = builder.build().unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+
+        let rejected = [
+            TierBuilder::intervals("w")
+                .interval(0.0, 0.6, "a")
+                .interval(0.5, 1.0, "b"),
+            TierBuilder::intervals("w")
+                .interval(0.0, 1.0, "a")
+                .interval(0.5, 0.5, "b"),
+            TierBuilder::intervals("w")
+                .bounds(0.0, 1.0)
+                .interval(0.5, 1.5, "a"),
+            TierBuilder::points("p").point(0.5, "a").point(0.5, "b"),
+        ];
+        for builder in rejected {
+            assert_eq!(
+                builder.build().err().unwrap().kind(),
+                std::io::ErrorKind::InvalidData
+            );
+        }
+
+        let tg = TextGrid::builder()
+            .tmax(2.0)
+            .tier(
+                TierBuilder::intervals("words")
+                    .interval(0.5, 1.0, "hi")
+                    .fill_gaps(""),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(tg.tiers[0].items.len(), 3);
+        assert_eq!(tg.tiers[0].items[2].tmax, 2.0);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_core_derives() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let mut copy = tg.clone();
+        assert_eq!(copy, tg);
+
+        copy.tiers[0].size += 1;
+        copy.size += 1;
+        assert_eq!(copy, tg);
+
+        copy.tiers[0].items[0].label.push('!');
+        assert_ne!(copy, tg);
+        assert_ne!(copy.tiers[0], tg.tiers[0]);
+        assert_eq!(copy.tiers[1], tg.tiers[1]);
+
+        assert_eq!(Item::default(), Item::new());
+        assert_eq!(Tier::default(), Tier::new());
+        assert_eq!(TextGrid::default(), TextGrid::new());
+        assert!(format!("{:?}", tg.tiers[0].items[0]).starts_with("Item { tmin: "));
+    }
+
+    #[test]
+    fn test_display() {
+        let tg = TextGrid::builder()
+            .name("rec01")
+            .tmax(12.3)
+            .interval_tier("words", |t| {
+                t.interval(0.0, 1.0, "hi").interval(1.0, 12.3, "")
+            })
+            .point_tier("notes", |t| t.point(4.0, "note"))
+            .build()
+            .unwrap();
+        assert_eq!(tg.tiers[0].to_string(), "words(2 intervals)");
+        assert_eq!(tg.tiers[1].to_string(), "notes(1 point)");
+        assert_eq!(
+            tg.to_string(),
+            "TextGrid \"rec01\" [0, 12.3] – 2 tiers: words(2 intervals), notes(1 point)"
+        );
+        assert_eq!(
+            TextGrid::new().to_string(),
+            "TextGrid \"\" [0, 0] – 0 tiers"
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_iterators() {
+        let mut tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let n_items: usize = tg.tiers.iter().map(|tier| tier.items.len()).sum();
+        assert_eq!(tg.items().count(), n_items);
+        assert_eq!(tg.tiers().count(), tg.tiers.len());
+        let (tier, item) = tg.items().last().unwrap();
+        assert_eq!(tier.name, tg.tiers.last().unwrap().name);
+        assert_eq!(item, tier.items.last().unwrap());
+
+        for tier in &mut tg {
+            for item in &mut *tier {
+                item.label.make_ascii_uppercase();
+            }
+        }
+        for tier in tg.tiers_mut() {
+            tier.iter_mut().for_each(|item| item.label.push('!'));
+        }
+        assert!(
+            (&tg)
+                .into_iter()
+                .all(|tier| tier.iter().all(|item| item.label.ends_with('!')))
+        );
+
+        let first_labels: Vec<String> = tg
+            .into_iter()
+            .next()
+            .unwrap()
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        assert!(
+            first_labels
+                .iter()
+                .all(|label| label.to_ascii_uppercase() == *label)
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_index() {
+        let mut tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert_eq!(tg["word"].name, "word");
+        assert_eq!(tg["word"][3], tg.get_tier("word").unwrap().items[3]);
+        tg["word"][3].label = String::from("changed");
+        assert_eq!(tg.get_tier("word").unwrap().items[3].label, "changed");
+        assert!(std::panic::catch_unwind(|| tg["no such tier"].size).is_err());
+    }
+
+    #[test]
+    fn test_constructors() {
+        let point = Item::point(0.25, "p");
+        assert_eq!(
+            (point.tmin, point.tmax, point.label.as_str()),
+            (0.25, 0.25, "p")
+        );
+
+        let mut words = Tier::new_interval("words", 0.0, 1.0);
+        assert!(words.interval_tier && words.items.is_empty());
+        words.items.push(Item::interval(0.0, 1.0, "hello"));
+        words.size = 1;
+        let mut beats = Tier::new_point("beats", 0.0, 1.0);
+        assert!(!beats.interval_tier);
+        beats.items.push(Item::point(0.5, "b"));
+        beats.size = 1;
+
+        let built = TextGrid::builder()
+            .interval_tier("words", |t| t.interval(0.0, 1.0, "hello"))
+            .point_tier("beats", |t| t.point(0.5, "b"))
+            .build()
+            .unwrap();
+        assert_eq!(built.tiers, vec![words, beats]);
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let tg = TextGrid::builder()
+            .interval_tier("words", |t| {
+                t.interval(0.0, 0.5, "  e\u{301}t\u{e9}\t ")
+                    .interval(0.5, 1.0, "a  \"b\"   c")
+            })
+            .build()
+            .unwrap();
+        for format in [TextGridFormat::Long, TextGridFormat::Short] {
+            let content =
+                String::from_utf8(tg.to_textgrid_bytes(format, &WriteOptions::default())).unwrap();
+            let plain =
+                read_from_str_with(&content, true, FileType::Auto, &ParseOptions::default());
+            assert_eq!(
+                plain.unwrap(),
+                read_from_str(&content, true, FileType::Auto).unwrap()
+            );
+
+            let labels = |options: ParseOptions| -> Vec<String> {
+                let tg = read_from_str_with(&content, true, FileType::Auto, &options).unwrap();
+                tg.tiers[0].items.iter().map(|i| i.label.clone()).collect()
+            };
+            let trim = ParseOptions {
+                trim_labels: true,
+                ..Default::default()
+            };
+            assert_eq!(labels(trim), vec!["e\u{301}t\u{e9}", "a  \"b\"   c"]);
+            let collapse = ParseOptions {
+                collapse_whitespace: true,
+                ..Default::default()
+            };
+            assert_eq!(labels(collapse), vec![" e\u{301}t\u{e9} ", "a \"b\" c"]);
+            let all = ParseOptions {
+                trim_labels: true,
+                collapse_whitespace: true,
+                nfc_normalize: true,
+                ..Default::default()
+            };
+            assert_eq!(labels(all), vec!["\u{e9}t\u{e9}", "a \"b\" c"]);
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_labeled_items() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let expected = tg
+            .items()
+            .filter(|(_, item)| !item.label.trim().is_empty())
+            .count();
+        assert!(expected < tg.items().count());
+        assert_eq!(tg.labeled_items().count(), expected);
+        assert!(
+            tg.labeled_items()
+                .all(|(_, item)| !item.label.trim().is_empty())
+        );
+        let word = tg.get_tier("word").unwrap();
+        assert_eq!(
+            word.labeled_items().count(),
+            tg.labeled_items()
+                .filter(|(tier, _)| tier.name == "word")
+                .count()
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_tier_names() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert_eq!(
+            tg.tier_names(),
+            vec!["phone", "word", "mute", "Errors", "points"]
+        );
+        assert!(tg.has_tier("Errors"));
+        assert!(!tg.has_tier("errors"));
+        let points: Vec<&str> = tg.point_tiers().map(|tier| tier.name.as_str()).collect();
+        assert_eq!(points, vec!["points"]);
+        assert_eq!(tg.interval_tiers().count() + points.len(), tg.tiers.len());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_normalize() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let mut messy = tg.clone();
+        for tier in messy.tiers.iter_mut() {
+            tier.items.reverse();
+            tier.size = 0;
+        }
+        messy.size = 0;
+        messy.tmax = 0.0;
+        messy.tiers[0].tmax = 0.0;
+        assert!(messy.assert_valid().is_err());
+
+        messy.normalize();
+        assert!(messy.assert_valid().is_ok());
+        assert_eq!(messy, tg);
+        assert_eq!(messy.size, tg.size);
+        assert!(
+            messy
+                .tiers
+                .iter()
+                .zip(&tg.tiers)
+                .all(|(a, b)| a.size == b.size)
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_validation_report() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert!(tg.validate().is_empty());
+
+        let mut broken = tg.clone();
+        broken.size += 1;
+        broken.tiers[0].items[2].tmax = broken.tiers[0].items[2].tmin;
+        broken.tiers[4].items[0].tmax += 0.1;
+        let report = broken.validate();
+        assert!(!report.is_valid());
+        assert_eq!(report.warnings().count(), 0);
+        let locations: Vec<(Option<usize>, Option<usize>)> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.tier_index, issue.item_index))
+            .collect();
+        assert_eq!(
+            locations,
+            vec![(None, None), (Some(0), Some(2)), (Some(4), Some(0))]
+        );
+        assert!(
+            report
+                .issues
+                .iter()
+                .all(|issue| !issue.suggestion.is_empty())
+        );
+
+        let first_error = broken.assert_valid().err().unwrap();
+        assert_eq!(first_error.to_string(), report.issues[0].message);
+        assert_eq!(broken.tiers[4].validate().issues.len(), 1);
+        assert_eq!(broken.tiers[4].validate().issues[0].tier_index, None);
+        assert!(
+            report
+                .to_string()
+                .starts_with("error: TextGrid size does not match")
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_duplicate_tier_names() {
+        let mut tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let word = tg.get_tier("word").unwrap().clone();
+        tg.tiers.push(word.clone());
+        tg.tiers.push(word);
+        tg.tiers[2].name = String::from("word");
+        tg.tiers[2].interval_tier = true;
+        tg.size = tg.tiers.len();
+
+        let report = tg.validate();
+        assert!(report.is_valid());
+        let warned: Vec<Option<usize>> = report.warnings().map(|w| w.tier_index).collect();
+        assert_eq!(warned, vec![Some(2), Some(5), Some(6)]);
+
+        let mut renamed = tg.clone();
+        renamed
+            .dedup_tier_names(DuplicateNamePolicy::Suffix)
+            .unwrap();
+        assert_eq!(
+            renamed.tier_names(),
+            vec![
+                "phone", "word", "word_2", "Errors", "points", "word_3", "word_4"
+            ]
+        );
+        assert!(renamed.validate().is_empty());
+
+        let mut merged = tg.clone();
+        merged.tiers.truncate(5);
+        merged.tiers[2] = merged.tiers[1].clone();
+        merged.size = 5;
+        let n_words = merged.tiers[1].items.len();
+        merged.dedup_tier_names(DuplicateNamePolicy::Merge).unwrap();
+        assert_eq!(
+            merged.tier_names(),
+            vec!["phone", "word", "Errors", "points"]
+        );
+        assert_eq!(merged.tiers[1].items.len(), 2 * n_words);
+        assert_eq!(merged.size, 4);
+
+        let mut mixed = tg.clone();
+        mixed.tiers[6].interval_tier = false;
+        assert!(mixed.dedup_tier_names(DuplicateNamePolicy::Merge).is_err());
+        assert_eq!(mixed.tiers.len(), tg.tiers.len());
+    }
+
+    #[test]
+    fn test_point_tier_monotonicity() {
+        let mut tier = Tier::new_point("notes", 0.0, 3.0);
+        tier.items = vec![
+            Item::point(0.5, "a"),
+            Item::point(1.0, "b"),
+            Item::point(1.0, "c"),
+            Item::point(0.8, "d"),
+        ];
+        tier.size = tier.items.len();
+
+        let report = tier.validate();
+        let messages: Vec<&str> = report.errors().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Points 1 and 2 have the same time in PointTier notes",
+                "Points 2 and 3 are out of order in PointTier notes",
+            ]
+        );
+        assert_eq!(report.issues[0].item_index, Some(2));
+        assert!(tier.assert_valid().is_err());
+
+        tier.items.remove(2);
+        tier.normalize();
+        assert!(tier.assert_valid().is_ok());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_pedantic_validation() {
+        let pedantic = ValidationOptions {
+            pedantic: true,
+            ..Default::default()
+        };
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert!(tg.validate_with(&pedantic).is_valid());
+
+        let mut tier = Tier::new_interval("words", 0.0, 4.0);
+        tier.items = vec![
+            Item::interval(0.5, 1.0, "a"),
+            Item::interval(1.0, 2.0, "b"),
+            Item::interval(2.5, 3.0, "c"),
+        ];
+        tier.size = tier.items.len();
+        assert!(tier.validate().is_empty());
+
+        let report = tier.validate_with(&pedantic);
+        let located: Vec<(Option<usize>, &str)> = report
+            .errors()
+            .map(|e| (e.item_index, e.message.as_str()))
+            .collect();
+        assert_eq!(
+            located,
+            vec![
+                (
+                    Some(0),
+                    "Tier words starts at 0 but its first interval starts at 0.5"
+                ),
+                (
+                    Some(2),
+                    "Gap between items 1 and 2 in tier words: [2, 2.5] is not covered"
+                ),
+                (
+                    Some(2),
+                    "Tier words ends at 4 but its last interval ends at 3"
+                ),
+            ]
+        );
+
+        let empty = Tier::new_interval("empty", 0.0, 1.0);
+        let report = empty.validate_with(&pedantic);
+        assert_eq!(report.issues[0].message, "Tier empty has no intervals");
+        let points = Tier::new_point("notes", 0.0, 1.0);
+        assert!(points.validate_with(&pedantic).is_empty());
+    }
+
+    #[test]
+    fn test_sizes_kept_in_sync() {
+        let mut tier = Tier::new_interval("words", 0.0, 2.0);
+        tier.items.push(Item::interval(0.0, 1.0, "a"));
+        assert_eq!((tier.size, tier.len()), (0, 1));
+        tier.push_item(Item::interval(1.0, 2.0, "b"));
+        assert_eq!((tier.size, tier.len()), (2, 2));
+        tier.add_empty_item();
+        assert_eq!(tier.size, 3);
+        tier.items.pop();
+
+        let mut tg = TextGrid::new();
+        tg.tmax = 2.0;
+        assert!(tg.is_empty());
+        tier.size = 0;
+        tg.push_tier(tier);
+        assert_eq!((tg.size, tg.len(), tg.tiers[0].size), (1, 1, 2));
+        assert!(tg.assert_valid().is_ok());
+
+        tg.tiers[0].items.pop();
+        tg.tiers.push(Tier::new_point("notes", 0.0, 2.0));
+        let reread = read_from_str(&tg.to_long_textgrid_string(), true, FileType::Long).unwrap();
+        assert_eq!((reread.size, reread.tiers[0].size), (2, 1));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_file_type_enum() {
+        let by_enum = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let by_str = read_from_file(LONG_FILE, true, "long").unwrap();
+        let by_format = read_from_file(LONG_FILE, true, TextGridFormat::Long).unwrap();
+        assert_eq!(by_enum, by_str);
+        assert_eq!(by_enum, by_format);
+        let auto = read_from_file(SHORT_FILE, true, FileType::default()).unwrap();
+        assert_eq!(auto.tiers.len(), by_enum.tiers.len());
+
+        let err = read_from_file(LONG_FILE, true, "binary").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("Unknown file type: binary"));
+
+        let results = files_to_data(&[String::from(LONG_FILE)], true, "xml");
+        assert!(
+            results[0]
+                .as_ref()
+                .unwrap_err()
+                .to_string()
+                .contains(LONG_FILE)
+        );
+        assert_eq!(FileType::Short.to_string(), "short");
+        assert_eq!("auto".parse::<FileType>().unwrap(), FileType::Auto);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_format_detection() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let mut tricky = tg.clone();
+        tricky.tiers[0].items[0].label = String::from("item []:");
+        let short = tricky.to_short_textgrid_string();
+        assert_eq!(FileType::detect(&short).unwrap(), FileType::Short);
+        let reread = read_from_str(&short, true, FileType::Auto).unwrap();
+        assert_eq!(reread.tiers[0].items[0].label, "item []:");
+        let long = format!("\u{feff}{}", tg.to_long_textgrid_string());
+        assert_eq!(FileType::detect(&long).unwrap(), FileType::Long);
+
+        let chronological = "\"Praat chronological TextGrid text file\"\n0 1   ! Time domain.\n";
+        let pitch = "File type = \"ooTextFile\"\nObject class = \"Pitch 1\"\n\nxmin = 0\n";
+        for content in [chronological, pitch, "", "hello"] {
+            let err = read_from_str(content, false, FileType::Auto).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            assert!(err.to_string().starts_with("Unknown TextGrid format"));
+        }
+
+        fs::write("tmp_binary.TextGrid", b"ooBinaryFile\x08TextGrid\xff\x00").unwrap();
+        let result = read_from_file("tmp_binary.TextGrid", false, FileType::Auto);
+        fs::remove_file("tmp_binary.TextGrid").unwrap();
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("binary TextGrid"), "{}", message);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_preserve_unknown_fields() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let written = tg.to_long_textgrid_string();
+        let content = written
+            .replacen(
+                "tiers? <exists>",
+                "comment = \"exported by aligner v2\" \r\ntiers? <exists>",
+                1,
+            )
+            .replacen(
+                "intervals: size",
+                "speaker = \"A\" \r\n        confidence = 0.9 \r\n        intervals: size",
+                1,
+            );
+
+        let dropped = read_from_str(&content, true, FileType::Long).unwrap();
+        assert!(dropped.unknown_fields.is_empty());
+        assert_eq!(dropped.to_long_textgrid_string(), written);
+
+        let options = ParseOptions {
+            preserve_unknown_fields: true,
+            ..Default::default()
+        };
+        let kept = read_from_str_with(&content, true, FileType::Long, &options).unwrap();
+        assert_eq!(
+            kept.unknown_fields,
+            vec![(
+                String::from("comment"),
+                String::from("\"exported by aligner v2\"")
+            )]
+        );
+        assert_eq!(kept.tiers[0].unknown_fields.len(), 2);
+        assert_eq!(kept.tiers[0].unknown_fields[1].1, "0.9");
+        assert!(kept.tiers[1].unknown_fields.is_empty());
+        assert_eq!(kept.to_long_textgrid_string(), content);
+        assert_ne!(kept, dropped);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_approx_eq() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let mut noisy = tg.clone();
+        noisy.name = String::from("other");
+        for item in noisy.tiers[1].items.iter_mut() {
+            item.tmin += 1e-9;
+            item.tmax -= 1e-9;
+        }
+        assert_eq!(noisy.approx_eq(&tg, 1e-6, true), None);
+        assert!(noisy.approx_eq(&tg, 0.0, true).is_some());
+
+        noisy.tiers[1].items[2].label.push('!');
+        assert_eq!(noisy.approx_eq(&tg, 1e-6, false), None);
+        let message = noisy.approx_eq(&tg, 1e-6, true).unwrap();
+        assert!(
+            message.starts_with("tier 1 (word), item 2: label"),
+            "{}",
+            message
+        );
+
+        noisy.tiers.pop();
+        assert_eq!(
+            noisy.approx_eq(&tg, 1e-6, false).as_deref(),
+            Some("4 tiers differ from 5 tiers")
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_quantize() {
+        let mut tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert!(tg.quantize(0.0, Rounding::Nearest).is_err());
+        let step = 1.0 / 16000.0;
+        tg.quantize(0.01, Rounding::Floor).unwrap();
+        for tier in &tg.tiers {
+            for item in tier {
+                for time in [item.tmin, item.tmax] {
+                    let frames = time / 0.01;
+                    assert!((frames - frames.round()).abs() < 1e-6, "{}", time);
+                }
+            }
+        }
+        assert!(tg.assert_valid().is_ok());
+        assert!(tg.tmin <= 1358.8925 && tg.tmin > 1358.88);
+
+        let mut points = Tier::new_point("notes", 0.0, 1.0);
+        for (time, label) in [(0.1, "a"), (0.104, "b"), (0.2, "c")] {
+            points.push_item(Item::point(time, label));
+        }
+        points.quantize(0.01, Rounding::Nearest).unwrap();
+        assert_eq!(points.size, 2);
+        assert_eq!(points.items[0].label, "a");
+
+        let mut ceil = Tier::new_interval("words", 0.0, 1.0);
+        ceil.push_item(Item::interval(0.0, 0.3, "x"));
+        ceil.push_item(Item::interval(0.3, 1.0, "y"));
+        let before = ceil.clone();
+        ceil.quantize(0.1, Rounding::Ceil).unwrap();
+        assert_eq!(ceil, before);
+
+        let mut samples = Tier::new_interval("words", 0.0, 1.0);
+        samples.push_item(Item::interval(0.0, 0.50001, "x"));
+        samples.push_item(Item::interval(0.50001, 1.0, "y"));
+        samples.quantize(step, Rounding::Ceil).unwrap();
+        assert_eq!(samples.items[0].tmax, 8001.0 / 16000.0);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_snap_boundaries() {
+        let mut tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let word_boundaries: Vec<f64> = tg["word"].iter().map(|item| item.tmax).collect();
+        for item in tg.tiers[0].iter_mut() {
+            item.tmin += 0.0004;
+            item.tmax += 0.0004;
+        }
+        tg.tiers[0].items[0].tmin = tg.tiers[0].tmin;
+        let last = tg.tiers[0].items.len() - 1;
+        tg.tiers[0].items[last].tmax = tg.tiers[0].tmax;
+        let noisy = tg.clone();
+
+        tg.snap_boundaries("word", &["phone"], 0.001).unwrap();
+        assert!(tg.assert_valid().is_ok());
+        let phone_ends: Vec<f64> = tg["phone"].iter().map(|item| item.tmax).collect();
+        for boundary in &word_boundaries {
+            assert!(phone_ends.contains(boundary), "{}", boundary);
+        }
+
+        let mut unchanged = noisy.clone();
+        unchanged.snap_boundaries(1, &[0], 0.0001).unwrap();
+        assert_eq!(unchanged, noisy);
+        assert!(
+            tg.clone()
+                .snap_boundaries("word", &["nope"], 0.001)
+                .is_err()
+        );
+
+        let mut collapsing = noisy.clone();
+        assert!(collapsing.snap_boundaries(1, &[0], 100.0).is_err());
+        assert_eq!(collapsing, noisy);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_tier_set_operations() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let words = &tg["word"];
+        let phones = &tg["phone"];
+        let labelled =
+            |tier: &Tier| -> f64 { tier.labeled_items().map(|item| item.tmax - item.tmin).sum() };
+
+        let both = words
+            .intersect(phones, |w, p| format!("{}/{}", w, p))
+            .unwrap();
+        assert!(both.assert_valid().is_ok());
+        assert_eq!(both.name, "word");
+        assert!(both.items.iter().all(|item| item.label.contains('/')));
+        let either = words.union(phones, |w, p| format!("{}/{}", w, p)).unwrap();
+        let words_only = words.subtract(phones).unwrap();
+        let phones_only = phones.subtract(words).unwrap();
+        let total = labelled(&both) + labelled(&words_only) + labelled(&phones_only);
+        assert!((labelled(&either) - total).abs() < 1e-6);
+        assert!((labelled(&both) + labelled(&words_only) - labelled(words)).abs() < 1e-6);
+        assert!(either.items.len() >= phones.labeled_items().count());
+
+        let empty = words.subtract(words).unwrap();
+        assert!(empty.is_empty());
+        assert!(
+            words
+                .intersect(&tg["points"], |a, _| a.to_string())
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_chunk() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        assert!(tg.chunk(0.0, 1.0).is_err());
+        let duration = tg.tmax - tg.tmin;
+
+        let chunks = tg.chunk(10.0, 10.0).unwrap();
+        assert_eq!(chunks.len(), (duration / 10.0).ceil() as usize);
+        assert_eq!(chunks[0].name, "long_format_0");
+        for chunk in &chunks {
+            assert_eq!(chunk.tmin, 0.0);
+            assert!(chunk.tmax <= 10.0 + 1e-9);
+            assert!(chunk.assert_valid().is_ok());
+            assert_eq!(chunk.tiers.len(), tg.tiers.len());
+        }
+        let total: f64 = chunks.iter().map(|chunk| chunk.tmax).sum();
+        assert!((total - duration).abs() < 1e-6);
+
+        let overlapping = tg.chunk(10.0, 5.0).unwrap();
+        assert!(overlapping.len() > chunks.len());
+
+        let grid = TextGrid::builder()
+            .tmax(10.0)
+            .interval_tier("words", |t| t.interval(0.0, 10.0, "a"))
+            .build()
+            .unwrap();
+        let spans = |hop: f64| -> Vec<f64> {
+            let chunks = grid.chunk(1.0, hop).unwrap();
+            chunks.iter().map(|chunk| chunk.tmax).collect()
+        };
+        assert_eq!(spans(4.0), [1.0, 1.0, 1.0]);
+        assert_eq!(spans(5.0), [1.0, 1.0]);
+        assert_eq!(grid.chunk(3.0, 4.0).unwrap().len(), 3);
+        assert_eq!(grid.chunk(3.0, 4.0).unwrap()[2].tmax, 2.0);
+
+        let by_word = tg.chunk_by_tier("word", 5.0).unwrap();
+        let word_ends: Vec<f64> = tg["word"].iter().map(|item| item.tmax).collect();
+        let mut start = tg["word"].items[0].tmin;
+        for chunk in &by_word {
+            let end = start + chunk.tmax;
+            assert!(word_ends.iter().any(|t| (t - end).abs() < 1e-6));
+            assert!(chunk.tmax <= 5.0 + 1e-6 || chunk.tiers[1].items.len() == 1);
+            start = end;
+        }
+        assert!(tg.chunk_by_tier("points", 5.0).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_split_tiers() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let parts = tg.split_tiers();
+        assert_eq!(parts.len(), tg.tiers.len());
+        let mut merged = TextGrid::new();
+        for (part, tier) in parts.into_iter().zip(&tg.tiers) {
+            assert_eq!(part.name, format!("long_format_{}", tier.name));
+            assert_eq!((part.tmin, part.tmax, part.size), (tg.tmin, tg.tmax, 1));
+            assert_eq!(&part.tiers[0], tier);
+            merged.merge_with(part, "_dup");
+        }
+        merged.name = tg.name.clone();
+        assert_eq!(merged, tg);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_extract_matching() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let phones = &tg["phone"];
+        let vowel = Regex::new(r"^[AEIOU]").unwrap();
+        let vowels = phones.extract_matching(&vowel);
+        assert!(vowels.size > 0 && vowels.size < phones.size);
+        assert!(vowels.assert_valid().is_ok());
+        assert_eq!((vowels.tmin, vowels.tmax), (phones.tmin, phones.tmax));
+        assert!(vowels.iter().all(|item| vowel.is_match(&item.label)));
+        let expected = phones.iter().filter(|i| vowel.is_match(&i.label)).count();
+        assert_eq!(vowels.size, expected);
+
+        let label = phones.items[1].label.clone();
+        let exact = phones.extract_matching(label.as_str());
+        assert!(exact.iter().all(|item| item.label == label));
+        assert_eq!(exact.size, phones.extract_matching(&label).size);
+        assert!(phones.extract_matching("no such label").is_empty());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_find_sequence() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let phones = &tg["phone"];
+        let runs = phones.find_sequence(&["R", "AY1", "T"]);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].items, 1..4);
+        assert_eq!(runs[0].tmin, phones[1].tmin);
+        assert_eq!(runs[0].tmax, phones[3].tmax);
+
+        let vowel = Regex::new(r"^[AEIOU]").unwrap();
+        let before_t = phones.find_sequence(&[&vowel as &dyn LabelPattern, &"T"]);
+        assert!(!before_t.is_empty());
+        for run in &before_t {
+            assert_eq!(phones[run.items.end - 1].label, "T");
+        }
+        let silences = phones.find_sequence(&["sil"]);
+        assert_eq!(silences.len(), phones.extract_matching("sil").size);
+        assert!(phones.find_sequence::<&str>(&[]).is_empty());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_query_builder() {
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let vowels = tg
+            .query()
+            .tier("phone")
+            .label_matches("^[AEIOU]")
+            .duration_gt(0.05)
+            .overlapping("word", |w| !w.label.is_empty())
+            .collect()
+            .unwrap();
+        assert!(!vowels.is_empty());
+        let words = tg.get_tier("word").unwrap();
+        for m in &vowels {
+            assert_eq!(m.tier.name, "phone");
+            assert!(m.item.label.starts_with(['A', 'E', 'I', 'O', 'U']));
+            assert!(m.item.tmax - m.item.tmin > 0.05);
+            assert_eq!(&m.tier.items[m.item_index], m.item);
+            assert!(
+                words
+                    .items_between(m.item.tmin, m.item.tmax, false)
+                    .iter()
+                    .any(|(_, w)| !w.label.is_empty())
+            );
+        }
+        assert_eq!(tg.query().count().unwrap(), tg.items().count());
+        assert_eq!(
+            tg.query().tier("phone").tier("word").count().unwrap(),
+            tg.get_tier("phone").unwrap().len() + words.len()
+        );
+        assert!(tg.query().label_matches("(").collect().is_err());
+        assert!(
+            tg.query()
+                .overlapping("syllable", |_| true)
+                .count()
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_binary_search_lookups() {
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        for tier in &tg.tiers {
+            assert!(tier.is_sorted());
+            let (tmin, tmax) = (tier.tmin, tier.tmax);
+            for step in 0..=200 {
+                let time = tmin + (tmax - tmin) * step as f64 / 200.0;
+                let expected = if tier.interval_tier {
+                    tier.items
+                        .iter()
+                        .position(|item| item.tmin <= time && time < item.tmax)
+                        .or_else(|| tier.items.iter().rposition(|item| item.tmax == time))
+                } else {
+                    tier.items
+                        .iter()
+                        .position(|item| (item.tmin - time).abs() <= 1e-6)
+                };
+                assert_eq!(
+                    tier.index_at_time(time),
+                    expected,
+                    "{} at {}",
+                    tier.name,
+                    time
+                );
+
+                let window = (time, time + 0.3);
+                for strict in [false, true] {
+                    let linear: Vec<usize> = tier
+                        .items
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, item)| {
+                            if strict {
+                                item.tmin >= window.0 - 1e-6 && item.tmax <= window.1 + 1e-6
+                            } else if tier.interval_tier {
+                                item.tmin < window.1 - 1e-6 && item.tmax > window.0 + 1e-6
+                            } else {
+                                item.tmin >= window.0 - 1e-6 && item.tmin <= window.1 + 1e-6
+                            }
+                        })
+                        .map(|(index, _)| index)
+                        .collect();
+                    let found: Vec<usize> = tier
+                        .items_between(window.0, window.1, strict)
+                        .into_iter()
+                        .map(|(index, _)| index)
+                        .collect();
+                    assert_eq!(found, linear);
+                }
+            }
+        }
+        let points = &tg.tiers[4];
+        if let Some(point) = points.items.first() {
+            assert_eq!(points.index_at_time(point.tmin + 1e-7), Some(0));
+        }
+        assert_eq!(tg.tiers[0].index_at_time(tg.tiers[0].tmax + 1.0), None);
+
+        // Boundaries belong to the interval starting there, and gap starts to the one ending there
+        let mut gapped = Tier::new_interval("words", 0.0, 3.0);
+        gapped.push_item(Item::interval(0.0, 1.0, "a"));
+        gapped.push_item(Item::interval(1.0, 1.5, "b"));
+        gapped.push_item(Item::interval(2.0, 3.0, "c"));
+        let found: Vec<Option<usize>> = [1.0, 1.5, 1.7, 2.0, 3.0]
+            .iter()
+            .map(|&time| gapped.index_at_time(time))
+            .collect();
+        assert_eq!(found, [Some(1), Some(1), None, Some(2), Some(2)]);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_tier_index() {
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        for tier in &tg.tiers {
+            let index = tier.build_index();
+            for step in 0..100 {
+                let t0 = tier.tmin + (tier.tmax - tier.tmin) * step as f64 / 100.0;
+                let t1 = t0 + 0.25;
+                assert_eq!(index.query(t0, t1), tier.items_between(t0, t1, false));
+            }
+        }
+
+        // Overlapping, unsorted segments, as produced by diarization
+        let mut speakers = Tier::new_interval("speakers", 0.0, 100.0);
+        for i in 0..200 {
+            let tmin = (i * 37 % 97) as f64;
+            let tmax = tmin + 1.0 + (i % 7) as f64;
+            speakers.push_item(Item::interval(tmin, tmax, &format!("spk{}", i % 3)));
+        }
+        let index = speakers.build_index();
+        for step in 0..50 {
+            let (t0, t1) = (step as f64 * 2.0, step as f64 * 2.0 + 3.5);
+            let expected: Vec<usize> = speakers
+                .items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| item.tmin < t1 - 1e-6 && item.tmax > t0 + 1e-6)
+                .map(|(i, _)| i)
+                .collect();
+            let found: Vec<usize> = index.query(t0, t1).into_iter().map(|(i, _)| i).collect();
+            assert_eq!(found, expected);
+        }
+        assert!(std::ptr::eq(index.tier(), &speakers));
+        assert!(
+            Tier::new_interval("empty", 0.0, 1.0)
+                .build_index()
+                .query(0.0, 1.0)
+                .is_empty()
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_vocabulary() {
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let phones = tg.get_tier("phone").unwrap();
+        let vocabulary = phones.vocabulary();
+        assert!(!vocabulary.contains_key(""));
+        let total: usize = vocabulary.values().map(|stats| stats.count).sum();
+        assert_eq!(total, phones.labeled_items().count());
+        let duration: f64 = vocabulary.values().map(|stats| stats.duration).sum();
+        let expected: f64 = phones.labeled_items().map(|i| i.tmax - i.tmin).sum();
+        assert!((duration - expected).abs() < 1e-9);
+
+        let per_tier = tg.vocabulary(true);
+        assert_eq!(per_tier.len(), tg.tiers.len());
+        assert_eq!(per_tier["phone"], vocabulary);
+        assert!(
+            per_tier["points"]
+                .values()
+                .all(|stats| stats.duration == 0.0)
+        );
+
+        let all = tg.vocabulary(false);
+        assert_eq!(all.len(), 1);
+        let mut merged = std::collections::BTreeMap::<String, LabelStats>::new();
+        for tier_vocabulary in per_tier.values() {
+            for (label, stats) in tier_vocabulary {
+                *merged.entry(label.clone()).or_default() += *stats;
+            }
+        }
+        assert_eq!(all[""].len(), merged.len());
+        for (label, stats) in &merged {
+            assert_eq!(all[""][label].count, stats.count);
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_confusion_matrix() {
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let phones = tg.get_tier("phone").unwrap();
+        let exact = confusion_matrix(phones, phones, ConfusionSampling::Exact).unwrap();
+        assert_eq!(exact.labels_a, exact.labels_b);
+        assert!((exact.total() - (phones.tmax - phones.tmin)).abs() < 1e-6);
+        assert!((exact.agreed() - exact.total()).abs() < 1e-9);
+        for (label, stats) in phones.vocabulary() {
+            assert!((exact.get(&label, &label) - stats.duration).abs() < 1e-6);
+        }
+
+        let mut shifted = phones.clone();
+        shifted.shift(0.01, false).unwrap();
+        shifted.tmin = phones.tmin;
+        shifted.tmax = phones.tmax;
+        let exact = confusion_matrix(phones, &shifted, ConfusionSampling::Exact).unwrap();
+        let sampled = confusion_matrix(phones, &shifted, ConfusionSampling::Step(0.001)).unwrap();
+        assert!(exact.agreed() < exact.total());
+        assert!((exact.total() - sampled.total()).abs() < 1e-6);
+        assert!((exact.agreed() - sampled.agreed()).abs() < 0.05);
+        assert_eq!(exact.get("no such label", "sil"), 0.0);
+
+        assert!(confusion_matrix(phones, &tg.tiers[4], ConfusionSampling::Exact).is_err());
+        assert!(confusion_matrix(phones, phones, ConfusionSampling::Step(0.0)).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_praat_script() {
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let script = tg.to_praat_script(PraatScriptMode::Create).unwrap();
+        let mut lines = script.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            format!(
+                "Create TextGrid: {}, {}, \"tier1 tier2 tier3 tier4 tier5\", \"tier5\"",
+                tg.tmin, tg.tmax
+            )
+        );
+        assert_eq!(lines.next().unwrap(), "Rename: \"long_format\"");
+        assert!(script.contains("Set tier name: 1, \"phone\"\n"));
+        let points = &tg.tiers[4];
+        assert_eq!(
+            script.matches("Insert point: 5, ").count(),
+            points.items.len()
+        );
+        let words = &tg.tiers[1];
+        let labelled = words.items.iter().filter(|i| !i.label.is_empty()).count();
+        assert_eq!(script.matches("Set interval text: 2, ").count(), labelled);
+
+        let modify = tg.to_praat_script(PraatScriptMode::Modify).unwrap();
+        assert!(modify.starts_with("n_tiers = Get number of tiers\n"));
+        assert!(modify.contains("Insert point tier: n_tiers + 5, \"points\"\n"));
+        assert!(modify.contains("Insert boundary: n_tiers + 1, "));
+
+        let quoted = TextGrid::builder()
+            .interval_tier("t", |t| t.interval(0.0, 1.0, "say \"hi\"\nthere"))
+            .build()
+            .unwrap();
+        let script = quoted.to_praat_script(PraatScriptMode::Create).unwrap();
+        assert!(
+            script.contains("Set interval text: 1, 1, \"say \"\"hi\"\"\" + newline$ + \"there\"\n")
+        );
+        assert!(
+            TextGrid::new()
+                .to_praat_script(PraatScriptMode::Create)
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_praat_table_export() {
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let table = tg.to_praat_table_string();
+        let labelled = tg
+            .items()
+            .filter(|(_, item)| !item.label.is_empty())
+            .count();
+        assert!(table.contains(&format!("numberOfRows = {} \n", labelled)));
+        assert_eq!(
+            table.matches("numberOfColumns = 4 \n").count(),
+            labelled + 1
+        );
+
+        // Cells in row order: tmin, tier, text, tmax
+        let cells: Vec<&str> = table
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("string = \""))
+            .map(|value| value.trim_end().trim_end_matches('"'))
+            .collect();
+        assert_eq!(cells.len(), labelled * 4);
+        let tmins: Vec<f64> = cells.chunks(4).map(|row| row[0].parse().unwrap()).collect();
+        assert!(tmins.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(
+            cells
+                .chunks(4)
+                .all(|row| row[0].split('.').nth(1).unwrap().len() == 6)
+        );
+        let first = tg
+            .items()
+            .filter(|(_, item)| !item.label.is_empty())
+            .min_by(|a, b| a.1.tmin.total_cmp(&b.1.tmin))
+            .unwrap();
+        assert_eq!(cells[1], first.0.name);
+        assert_eq!(cells[2], first.1.label);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_read_asr_json() {
+        let whisperx = r#"{
+            "segments": [
+                {"start": 0.5, "end": 2.0, "text": " I have 2 cats.", "speaker": "SPEAKER_00",
+                 "words": [
+                    {"word": "I", "start": 0.5, "end": 0.7, "score": 0.9, "speaker": "SPEAKER_00"},
+                    {"word": "have", "start": 0.65, "end": 1.0, "score": 0.8},
+                    {"word": "2"},
+                    {"word": "cats.", "start": 1.3, "end": 2.0, "score": 0.7}
+                 ]},
+                {"start": 2.5, "end": 3.0, "text": " Yes.", "speaker": "SPEAKER_01",
+                 "words": [{"word": "Yes.", "start": 2.5, "end": 3.0}]}
+            ],
+            "word_segments": []
+        }"#;
+        let path = std::env::temp_dir().join("textgrid_test_asr.json");
+        fs::write(&path, whisperx).unwrap();
+        let tg = read_asr_json(path.to_str().unwrap(), AsrSchema::Auto).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(tg.name, "textgrid_test_asr");
+        assert_eq!((tg.tmin, tg.tmax), (0.0, 3.0));
+        let names: Vec<&str> = tg.tiers.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["segments", "words", "speakers"]);
+        let words: Vec<(f64, f64, &str)> = tg.tiers[1]
+            .labeled_items()
+            .map(|i| (i.tmin, i.tmax, i.label.as_str()))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                (0.5, 0.7, "I"),
+                (0.7, 1.0, "have"),
+                (1.3, 2.0, "cats."),
+                (2.5, 3.0, "Yes.")
+            ]
+        );
+        let speakers: Vec<&str> = tg.tiers[2]
+            .labeled_items()
+            .map(|i| i.label.as_str())
+            .collect();
+        assert_eq!(speakers, ["SPEAKER_00", "SPEAKER_01"]);
+        assert_eq!(tg.tiers[0].items[0].label, "");
+        assert!(tg.assert_valid().is_ok());
+
+        // Whisper requires timestamps on every word
+        let err = TextGrid::from_asr_json(whisperx, AsrSchema::Whisper).unwrap_err();
+        assert!(err.to_string().contains("Word 2 of segment 0"));
+        let bare = r#"[{"start": 0.0, "end": 1.0, "text": "hi", "words": []}]"#;
+        let tg = TextGrid::from_asr_json(bare, AsrSchema::StableTs).unwrap();
+        assert_eq!(tg.tiers.len(), 2);
+        assert!(TextGrid::from_asr_json("{}", AsrSchema::Auto).is_err());
+        for empty in ["[]", r#"{"text": "", "segments": []}"#] {
+            let err = TextGrid::from_asr_json(empty, AsrSchema::Auto).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+        assert!(TextGrid::from_asr_json("not json", AsrSchema::Auto).is_err());
+    }
+
+    #[test]
+    fn test_kaldi_textgrids() {
+        let segments = "\
+            rec1-0001 rec1 1.0 3.0\n\
+            rec1-0002 rec1 3.5 5.0\n\
+            rec2-0001 rec2 0.0 2.0\n";
+        let utt2spk = "rec1-0001 spk1\nrec1-0002 spk1\nrec2-0001 spk2\n";
+        let ctm = "\
+            ;; words aligned by Kaldi\n\
+            rec1-0001 1 0.5 0.5 the 1.00\n\
+            rec1-0001 1 0.0 0.25 so\n\
+            rec1-0001 1 0.9 0.5 cat\n\
+            rec1-0002 1 0.0 1.5 sat\n\
+            rec2-0001 1 0.5 1.0 hi\n";
+        let textgrids = kaldi::build_textgrids(ctm, segments, utt2spk).unwrap();
+        assert_eq!(textgrids.keys().collect::<Vec<_>>(), ["rec1", "rec2"]);
+
+        let rec1 = &textgrids["rec1"];
+        assert_eq!(rec1.name, "rec1");
+        assert_eq!((rec1.tmin, rec1.tmax), (0.0, 5.0));
+        let utterances: Vec<(f64, f64, &str)> = rec1.tiers[0]
+            .labeled_items()
+            .map(|i| (i.tmin, i.tmax, i.label.as_str()))
+            .collect();
+        assert_eq!(utterances, [(1.0, 3.0, "so the cat"), (3.5, 5.0, "sat")]);
+        let words: Vec<(f64, f64, &str)> = rec1.tiers[1]
+            .labeled_items()
+            .map(|i| (i.tmin, i.tmax, i.label.as_str()))
+            .collect();
+        // "cat" overlaps "the" and is moved to its end
+        assert_eq!(
+            words,
+            [
+                (1.0, 1.25, "so"),
+                (1.5, 2.0, "the"),
+                (2.0, 2.4, "cat"),
+                (3.5, 5.0, "sat")
+            ]
+        );
+        assert!(rec1.assert_valid().is_ok());
+        assert_eq!(textgrids["rec2"].tiers[1].name, "spk2 - words");
+
+        let err = kaldi::build_textgrids("rec9-0001 1 0.0 1.0 x\n", segments, utt2spk).unwrap_err();
+        assert!(err.to_string().contains("rec9-0001 is not in segments"));
+        assert!(kaldi::build_textgrids("", segments, "rec1-0001 spk1\n").is_err());
+        assert!(kaldi::build_textgrids("rec1-0001 1 zero 1.0 x\n", segments, utt2spk).is_err());
+    }
+
+    #[cfg(feature = "audio")]
+    #[test]
+    fn test_audio_duration() {
+        // 16-bit mono PCM at 8 kHz, 2.5 s long
+        let (sample_rate, n_samples) = (8000u32, 20000u32);
+        let data_len = n_samples * 2;
+        let mut wav = Vec::new();
+        wav.extend(b"RIFF");
+        wav.extend((36 + data_len).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(16u32.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(sample_rate.to_le_bytes());
+        wav.extend((sample_rate * 2).to_le_bytes());
+        wav.extend(2u16.to_le_bytes());
+        wav.extend(16u16.to_le_bytes());
+        wav.extend(b"data");
+        wav.extend(data_len.to_le_bytes());
+        wav.resize(wav.len() + data_len as usize, 0);
+        let path = std::env::temp_dir().join("textgrid_test_audio.wav");
+        fs::write(&path, &wav).unwrap();
+        let wav_path = path.to_str().unwrap();
+
+        assert_eq!(audio_duration(wav_path).unwrap(), 2.5);
+        let tg = TextGrid::builder()
+            .interval_tier("words", |t| {
+                t.interval(0.0, 1.0, "a").interval(1.0, 2.4, "b")
+            })
+            .point_tier("beats", |t| t.point(0.5, "x"))
+            .build()
+            .unwrap();
+        assert!(tg.check_audio_duration(wav_path, 0.2).is_ok());
+        assert!(tg.check_audio_duration(wav_path, 0.05).is_err());
+
+        let extended = tg.clone().with_audio_duration(wav_path).unwrap();
+        assert_eq!(extended.tmax, 2.5);
+        assert!(extended.tiers.iter().all(|tier| tier.tmax == 2.5));
+        assert_eq!(extended.tiers[0].items[1].tmax, 2.5);
+        assert!(extended.assert_valid().is_ok());
+
+        let data = vec![(
+            String::from("words"),
+            true,
+            vec![(0.0, 3.0, String::from("too long"))],
+        )];
+        assert!(TextGrid::from_data_with_audio(data, None, wav_path).is_err());
+        let long = TextGrid::builder()
+            .interval_tier("words", |t| {
+                t.interval(0.0, 2.6, "a").interval(2.6, 3.0, "b")
+            })
+            .build()
+            .unwrap();
+        assert!(long.with_audio_duration(wav_path).is_err());
+        let past_end = TextGrid::builder()
+            .tmax(3.0)
+            .interval_tier("words", |t| t.interval(0.0, 2.8, "a"))
+            .build()
+            .unwrap();
+        assert!(past_end.with_audio_duration(wav_path).is_err());
+        let starts_after = TextGrid::builder()
+            .tmin(2.5)
+            .tmax(3.0)
+            .point_tier("beats", |t| t)
+            .build()
+            .unwrap();
+        let err = starts_after.with_audio_duration(wav_path).unwrap_err();
+        assert!(err.to_string().starts_with("The TextGrid starts at 2.5"));
+
+        let shrunk = TextGrid::builder()
+            .interval_tier("words", |t| {
+                t.interval(0.0, 1.0, "a").interval(1.0, 2.51, "b")
+            })
+            .point_tier("beats", |t| t.point(0.5, "x"))
+            .build()
+            .unwrap();
+        assert!(shrunk.check_audio_duration(wav_path, 0.02).is_ok());
+        let shrunk = shrunk.with_audio_duration(wav_path).unwrap();
+        assert_eq!(shrunk.tmax, 2.5);
+        assert!(shrunk.tiers.iter().all(|tier| tier.tmax == 2.5));
+        assert_eq!(shrunk.tiers[0].items[1].tmax, 2.5);
+        assert!(shrunk.assert_valid().is_ok());
+        fs::remove_file(&path).unwrap();
+        assert!(audio_duration(wav_path).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_concat() {
+        let mut tg = read_from_file(SHORT_FILE, false, FileType::Short).unwrap();
+        let other = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let (duration, other_duration) = (tg.tmax - tg.tmin, other.tmax - other.tmin);
+        let counts: Vec<usize> = tg.tiers.iter().map(|t| t.items.len()).collect();
+        let end = tg.tmax;
+        tg.concat(other.clone()).unwrap();
+        assert!((tg.tmax - tg.tmin - duration - other_duration).abs() < 1e-9);
+        for (index, tier) in tg.tiers.iter().enumerate() {
+            assert_eq!(
+                tier.items.len(),
+                counts[index] + other.tiers[index].items.len()
+            );
+            assert_eq!(tier.tmax, tg.tmax);
+            assert_eq!(tier.size, tier.items.len());
+            let first_appended = &tier.items[counts[index]];
+            let original = &other.tiers[index].items[0];
+            assert!((first_appended.tmin - (original.tmin - other.tmin + end)).abs() < 1e-9);
+        }
+        assert!(tg.assert_valid().is_ok());
+
+        let mut words = TextGrid::builder()
+            .interval_tier("words", |t| t.interval(0.0, 1.0, "a"))
+            .build()
+            .unwrap();
+        let points = TextGrid::builder()
+            .point_tier("words", |t| t.point(0.5, "b"))
+            .build()
+            .unwrap();
+        let before = words.clone();
+        assert!(words.concat(points).is_err());
+        assert_eq!(words, before);
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_cli_merge() {
+        use std::process::Command;
+        let dir = std::env::temp_dir().join("textgrid_test_cli_merge");
+        fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        let a = TextGrid::builder()
+            .interval_tier("words", |t| t.interval(0.0, 1.0, "hello"))
+            .build()
+            .unwrap();
+        let b = TextGrid::builder()
+            .interval_tier("words", |t| t.interval(0.0, 2.0, "world"))
+            .point_tier("beats", |t| t.point(1.0, "x"))
+            .build()
+            .unwrap();
+        a.save_textgrid(
+            &path("a.TextGrid"),
+            TextGridFormat::Long,
+            &WriteOptions::default(),
+        )
+        .unwrap();
+        b.save_textgrid(
+            &path("b.TextGrid"),
+            TextGridFormat::Short,
+            &WriteOptions::default(),
+        )
+        .unwrap();
+
+        let gridio = env!("CARGO_BIN_EXE_gridio");
+        let status = Command::new(gridio)
+            .args([
+                "merge",
+                &path("a.TextGrid"),
+                &path("b.TextGrid"),
+                "-o",
+                &path("m.TextGrid"),
+            ])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let merged = read_from_file(&path("m.TextGrid"), false, FileType::Long).unwrap();
+        let names: Vec<&str> = merged.tiers.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["words", "words_b", "beats"]);
+        assert_eq!(merged.tmax, 2.0);
+
+        let status = Command::new(gridio)
+            .args(["merge", "--concat", "--format", "short"])
+            .args([
+                &path("a.TextGrid"),
+                &path("b.TextGrid"),
+                "-o",
+                &path("c.TextGrid"),
+            ])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let concatenated = read_from_file(&path("c.TextGrid"), false, FileType::Short).unwrap();
+        assert_eq!(concatenated.tmax, 3.0);
+        assert_eq!(concatenated.tiers[0].items.len(), 2);
+        assert_eq!(concatenated.tiers[1].items[0].tmin, 2.0);
+
+        let output = Command::new(gridio)
+            .args([
+                "merge",
+                &path("missing.TextGrid"),
+                "-o",
+                &path("x.TextGrid"),
+            ])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).starts_with("gridio: "));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_cli_extract() {
+        use std::process::Command;
+        let dir = std::env::temp_dir().join("textgrid_test_cli_extract");
+        fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("out.TextGrid");
+        let output = output.to_str().unwrap();
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let (t0, t1) = (tg.tmin + 1.0, tg.tmin + 3.0);
+
+        let gridio = env!("CARGO_BIN_EXE_gridio");
+        let status = Command::new(gridio)
+            .args(["extract", "--tiers", "word,phone", LONG_FILE, "-o", output])
+            .args(["--from", &t0.to_string(), "--to", &t1.to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let extracted = read_from_file(output, false, FileType::Auto).unwrap();
+        let names: Vec<&str> = extracted.tiers.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["word", "phone"]);
+        assert_eq!((extracted.tmin, extracted.tmax), (t0, t1));
+        assert_eq!(
+            extracted.tiers[1],
+            tg.tiers[0].extract(t0, t1, false).unwrap()
+        );
+
+        let status = Command::new(gridio)
+            .args([
+                "extract",
+                "--from",
+                &t0.to_string(),
+                "--rebase",
+                LONG_FILE,
+                "-o",
+                output,
+            ])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let rebased = read_from_file(output, false, FileType::Auto).unwrap();
+        assert_eq!(rebased.tiers.len(), tg.tiers.len());
+        assert_eq!(rebased.tmin, 0.0);
+        assert!((rebased.tmax - (tg.tmax - t0)).abs() < 1e-9);
+
+        let status = Command::new(gridio)
+            .args(["extract", "--tiers", "syllable", LONG_FILE, "-o", output])
+            .status()
+            .unwrap();
+        assert!(!status.success());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_cli_relabel() {
+        use std::process::Command;
+        let dir = std::env::temp_dir().join("textgrid_test_cli_relabel");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy(LONG_FILE, dir.join("a.TextGrid")).unwrap();
+        fs::copy(SHORT_FILE, dir.join("b.TextGrid")).unwrap();
+        let pattern = dir.join("*.TextGrid");
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        // A file written by another tool, which relabeling must not otherwise rewrite
+        let layout = WriteOptions {
+            line_ending: LineEnding::Lf,
+            praat_trailing_spaces: false,
+            bom: true,
+            ..Default::default()
+        };
+        let with_layout = |tg: &TextGrid| {
+            let content = tg.to_long_textgrid_string_with(&layout);
+            let xmax = format!("xmax = {}\n", tg.tmax);
+            content.replacen(&xmax, &format!("{}tool = \"aligner\"\n", xmax), 1)
+        };
+        assert!(with_layout(&tg).contains("tool = "));
+        fs::write(dir.join("c.TextGrid"), with_layout(&tg)).unwrap();
+        let expected = tg.tiers[0]
+            .items
+            .iter()
+            .filter(|item| item.label.contains("AH"))
+            .count();
+        assert!(expected > 0);
+
+        let gridio = env!("CARGO_BIN_EXE_gridio");
+        let relabel = |dry_run: bool| {
+            let mut command = Command::new(gridio);
+            command.args(["relabel", "--tier", "phone", "--pattern", "AH(\\d?)"]);
+            command.args(["--replace", "ə$1", pattern.to_str().unwrap()]);
+            if dry_run {
+                command.arg("--dry-run");
+            }
+            let output = command.output().unwrap();
+            assert!(output.status.success());
+            String::from_utf8(output.stdout).unwrap()
+        };
+
+        let report = relabel(true);
+        assert!(report.contains(&format!("{} labels would change", expected)));
+        let unchanged = read_from_file(
+            dir.join("a.TextGrid").to_str().unwrap(),
+            false,
+            FileType::Auto,
+        );
+        assert_eq!(unchanged.unwrap().tiers, tg.tiers);
+
+        let report = relabel(false);
+        assert!(report.contains(&format!("{} labels changed", expected)));
+        let a = read_from_file(
+            dir.join("a.TextGrid").to_str().unwrap(),
+            false,
+            FileType::Long,
+        )
+        .unwrap();
+        let b = read_from_file(
+            dir.join("b.TextGrid").to_str().unwrap(),
+            false,
+            FileType::Short,
+        )
+        .unwrap();
+        for relabeled in [&a, &b] {
+            assert!(
+                relabeled.tiers[0]
+                    .items
+                    .iter()
+                    .all(|item| !item.label.contains("AH"))
+            );
+            assert_eq!(
+                relabeled.tiers[0]
+                    .items
+                    .iter()
+                    .filter(|item| item.label.starts_with('ə'))
+                    .count(),
+                expected
+            );
+            assert_eq!(relabeled.tiers[1], tg.tiers[1]);
+        }
+        let ah = Regex::new("AH(\\d?)").unwrap();
+        let mut expected_c = tg.clone();
+        for item in &mut expected_c.tiers[0].items {
+            item.label = ah.replace_all(&item.label, "ə$1").into_owned();
+        }
+        assert_eq!(
+            fs::read_to_string(dir.join("c.TextGrid")).unwrap(),
+            with_layout(&expected_c)
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_cli_chunk() {
+        use std::process::Command;
+        let dir = std::env::temp_dir().join("textgrid_test_cli_chunk");
+        let _ = fs::remove_dir_all(&dir);
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let gridio = env!("CARGO_BIN_EXE_gridio");
+
+        let windows = dir.join("windows");
+        let status = Command::new(gridio)
+            .args(["chunk", "--window", "2", LONG_FILE, "-o"])
+            .arg(&windows)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let expected = tg.chunk(2.0, 2.0).unwrap();
+        let expected_windows = expected.len();
+        assert_eq!(fs::read_dir(&windows).unwrap().count(), expected_windows);
+        for chunk in &expected {
+            let path = windows.join(format!("{}.TextGrid", chunk.name));
+            let written = read_from_file(path.to_str().unwrap(), false, FileType::Auto).unwrap();
+            assert_eq!(written.tmin, 0.0);
+            assert_eq!(written.tiers, chunk.tiers);
+        }
+
+        let words = dir.join("words");
+        let status = Command::new(gridio)
+            .args([
+                "chunk",
+                "--window",
+                "1.5",
+                "--by-tier",
+                "word",
+                LONG_FILE,
+                "-o",
+            ])
+            .arg(&words)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let expected = tg.chunk_by_tier("word", 1.5).unwrap();
+        assert_eq!(fs::read_dir(&words).unwrap().count(), expected.len());
+
+        let status = Command::new(gridio)
+            .args([
+                "chunk",
+                "--window",
+                "2",
+                "--by-tier",
+                "nope",
+                LONG_FILE,
+                "-o",
+            ])
+            .arg(&words)
+            .status()
+            .unwrap();
+        assert!(!status.success());
+
+        for sub in ["a", "b"] {
+            fs::create_dir_all(dir.join(sub)).unwrap();
+            fs::copy(LONG_FILE, dir.join(sub).join("s1.TextGrid")).unwrap();
+        }
+        let output = Command::new(gridio)
+            .args(["chunk", "--window", "2"])
+            .args([dir.join("a/s1.TextGrid"), dir.join("b/s1.TextGrid")])
+            .arg("-o")
+            .arg(dir.join("same_stem"))
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(
+            String::from_utf8(output.stderr)
+                .unwrap()
+                .contains("would overwrite a chunk of")
+        );
+        assert_eq!(
+            fs::read_dir(dir.join("same_stem")).unwrap().count(),
+            expected_windows
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_cli_diff() {
+        use std::process::Command;
+        let dir = std::env::temp_dir().join("textgrid_test_cli_diff");
+        fs::create_dir_all(&dir).unwrap();
+        let new_path = dir.join("new.TextGrid");
+        let new_path = new_path.to_str().unwrap();
+        let mut tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let gridio = env!("CARGO_BIN_EXE_gridio");
+        let diff = |tol: &str| {
+            let output = Command::new(gridio)
+                .args(["diff", LONG_FILE, new_path, "--tol", tol])
+                .output()
+                .unwrap();
+            (
+                output.status.code(),
+                String::from_utf8(output.stdout).unwrap(),
+            )
+        };
+
+        tg.save_textgrid(new_path, TextGridFormat::Short, &WriteOptions::default())
+            .unwrap();
+        let (code, report) = diff("0.01");
+        assert_eq!(code, Some(0));
+        assert!(report.contains("No differences"));
+
+        tg.tiers[1].items[1].label = String::from("changed");
+        tg.tiers[1].items[2].tmin += 0.005;
+        tg.tiers[1].items[1].tmax += 0.005;
+        tg.save_textgrid(new_path, TextGridFormat::Long, &WriteOptions::default())
+            .unwrap();
+        let (code, report) = diff("0.01");
+        assert_eq!(code, Some(1));
+        assert!(report.starts_with(&format!("--- {}\n+++ {}\n", LONG_FILE, new_path)));
+        assert!(report.contains("tier \"word\":"));
+        assert!(report.contains("changed"));
+        let (code, report) = diff("0.001");
+        assert_eq!(code, Some(1));
+        assert_eq!(
+            report,
+            format!(
+                "--- {}\n+++ {}\n{}",
+                LONG_FILE,
+                new_path,
+                read_from_file(LONG_FILE, false, FileType::Long)
+                    .unwrap()
+                    .diff(&tg, 0.001)
+            )
+        );
+
+        let mut longer = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        longer.tmax += 1.0;
+        for tier in &mut longer.tiers {
+            tier.tmax += 1.0;
+        }
+        longer
+            .save_textgrid(new_path, TextGridFormat::Long, &WriteOptions::default())
+            .unwrap();
+        let (code, report) = diff("0.01");
+        assert_eq!(code, Some(1));
+        assert!(report.contains("~ bounds"));
+
+        let output = Command::new(gridio)
+            .args(["diff", LONG_FILE, "missing.TextGrid"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "pipeline")]
+    #[test]
+    fn test_run_pipeline() {
+        let dir = std::env::temp_dir().join("textgrid_test_run_pipeline");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("in/sub")).unwrap();
+        fs::copy(LONG_FILE, dir.join("in/a.TextGrid")).unwrap();
+        fs::copy(SHORT_FILE, dir.join("in/sub/b.TextGrid")).unwrap();
+        fs::write(dir.join("in/broken.TextGrid"), "not a TextGrid").unwrap();
+        let toml = format!(
+            r#"
+inputs = "{input}"
+
+[repair]
+normalize = true
+
+[[steps]]
+op = "relabel"
+tiers = ["phone"]
+pattern = "AH(\\d?)"
+replace = "ə$1"
+
+[[steps]]
+op = "shift"
+offset = 1.0
+
+[output]
+dir = "{output}"
+format = "short"
+"#,
+            input = dir.join("in").display(),
+            output = dir.join("out").display()
+        );
+        let config = PipelineConfig::from_toml(&toml).unwrap();
+        assert_eq!(config.validation, ValidationLevel::Default);
+        assert_eq!(config.steps.len(), 2);
+
+        let results = run_pipeline(&config).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[std::path::Path::new("broken.TextGrid")].is_err());
+        let written = results[std::path::Path::new("sub/b.TextGrid")]
+            .as_ref()
+            .unwrap();
+        assert_eq!(written, &dir.join("out/sub/b.TextGrid"));
+        let original = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let processed = read_from_file(written.to_str().unwrap(), false, FileType::Short).unwrap();
+        assert!((processed.tmin - (original.tmin + 1.0)).abs() < 1e-9);
+        let phones = |tg: &TextGrid| {
+            tg.tiers[0]
+                .items
+                .iter()
+                .map(|item| item.label.clone())
+                .collect::<Vec<_>>()
+        };
+        let expected: Vec<String> = phones(&original)
+            .iter()
+            .map(|label| label.replace("AH", "ə"))
+            .collect();
+        assert_eq!(phones(&processed), expected);
+        assert_eq!(
+            processed.tiers[1].items[0].label,
+            original.tiers[1].items[0].label
+        );
+
+        let yaml = format!(
+            "inputs: {}\nvalidation: pedantic\nsteps:\n  - op: fill_gaps\n    label: sil\noutput:\n  dir: {}\n",
+            dir.join("in/a.TextGrid").display(),
+            dir.join("yaml").display()
+        );
+        let config = PipelineConfig::from_yaml(&yaml).unwrap();
+        assert_eq!(
+            config.steps,
+            [PipelineStep::FillGaps {
+                tiers: vec![],
+                label: String::from("sil")
+            }]
+        );
+        assert_eq!(config.output.format, OutputFormat::Long);
+        assert!(PipelineConfig::from_toml("inputs = 1").is_err());
+        assert!(PipelineConfig::from_yaml("inputs: x\nunknown: 1\noutput:\n  dir: y\n").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_numeric_parse_errors() {
+        let long = fs::read_to_string(LONG_FILE).unwrap();
+        let short = fs::read_to_string(SHORT_FILE).unwrap();
+        let tg = read_from_str(&long, false, FileType::Long).unwrap();
+
+        // Scientific notation is read exactly
+        let first = &tg.tiers[0].items[1];
+        let scientific = long.replacen(
+            &format!("xmin = {} ", first.tmin),
+            &format!("xmin = {:e} ", first.tmin),
+            1,
+        );
+        assert_ne!(scientific, long);
+        let parsed = read_from_str(&scientific, false, FileType::Long).unwrap();
+        assert_eq!(parsed.tiers[0].items[1].tmin, first.tmin);
+
+        // A locale-formatted number is an error naming its line, not a silent 0.0
+        let line_number = long
+            .lines()
+            .position(|line| line.trim() == "size = 5")
+            .unwrap();
+        let broken = long.replacen("size = 5 ", "size = 5,0 ", 1);
+        let err = read_from_str(&broken, false, FileType::Long).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            format!("Line {}: invalid count \"5,0\"", line_number + 1)
+        );
+        let mut lines: Vec<String> = short.lines().map(String::from).collect();
+        let index = lines.iter().position(|line| line.contains('.')).unwrap();
+        lines[index] = lines[index].replace('.', ",");
+        let err = read_from_str(&lines.join("\n"), false, FileType::Short).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with(&format!("Line {}: invalid number", index + 1))
+        );
+        let nan = short.replacen(&lines[3].replace(',', "."), "nan", 1);
+        assert!(read_from_str(&nan, false, FileType::Short).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_accept_decimal_comma() {
+        let tg = read_from_file(LONG_FILE, false, FileType::Long).unwrap();
+        let options = ParseOptions {
+            accept_decimal_comma: true,
+            ..Default::default()
+        };
+        for (file, file_type) in [(LONG_FILE, FileType::Long), (SHORT_FILE, FileType::Short)] {
+            let content = fs::read_to_string(file).unwrap();
+            // Only times contain dots: no label of the fixtures does
+            let european = content
+                .lines()
+                .map(|line| match line.contains('"') {
+                    true => line.to_string(),
+                    false => line.replace('.', ","),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            assert!(read_from_str(&european, false, file_type).is_err());
+            let parsed = read_from_str_with(&european, false, file_type, &options).unwrap();
+            assert_eq!(parsed.tiers, tg.tiers);
+            assert_eq!((parsed.tmin, parsed.tmax), (tg.tmin, tg.tmax));
+            let unchanged = read_from_str_with(&content, false, file_type, &options).unwrap();
+            assert_eq!(unchanged.tiers, tg.tiers);
+        }
+        let options = ParseOptions {
+            accept_decimal_comma: true,
+            ..Default::default()
+        };
+        let grid =
+            "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1,2,5\n<exists>\n0\n";
+        assert!(read_from_str_with(grid, false, FileType::Short, &options).is_err());
+    }
+
+    #[test]
+    fn test_multiline_labels() {
+        let label = "first line\n    item [2]:\n\"IntervalTier\"\n";
+        let tg = TextGrid::builder()
+            .tmin(0.0)
+            .tmax(2.0)
+            .interval_tier("words", |t| {
+                t.interval(0.0, 1.0, label).interval(1.0, 2.0, "after")
+            })
+            .point_tier("events", |t| t.point(0.5, "a\nb").point(1.5, "c"))
+            .build()
+            .unwrap();
+        for line_ending in [LineEnding::Lf, LineEnding::CrLf] {
+            let options = WriteOptions {
+                line_ending,
+                ..Default::default()
+            };
+            let long = tg.to_long_textgrid_string_with(&options);
+            let short = tg.to_short_textgrid_string_with(&options);
+            if line_ending == LineEnding::CrLf {
+                assert!(!long.replace("\r\n", "").contains('\n'));
+            }
+            for (content, file_type) in [(long, FileType::Long), (short, FileType::Short)] {
+                let parsed = read_from_str(&content, true, file_type).unwrap();
+                assert_eq!(parsed.tiers, tg.tiers);
+            }
+        }
+
+        // As written by Praat, with a label spanning three physical lines
+        let praat = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1\n<exists>\n1\n\"IntervalTier\"\n\"words\"\n0\n1\n2\n0\n0.5\n\"one\n\"\"two\"\"\nthree\"\n0.5\n1\n\"four\"\n";
+        let parsed = read_from_str(praat, true, FileType::Short).unwrap();
+        let labels: Vec<&str> = parsed.tiers[0]
+            .items
+            .iter()
+            .map(|i| i.label.as_str())
+            .collect();
+        assert_eq!(labels, ["one\n\"two\"\nthree", "four"]);
+    }
+
+    #[test]
+    fn test_short_parser_tolerates_layout() {
+        let short = fs::read_to_string(SHORT_FILE).unwrap();
+        let tg = read_from_str(&short, true, FileType::Short).unwrap();
+
+        // Blank lines, whitespace-only lines and indentation anywhere
+        let loose: String = short
+            .lines()
+            .enumerate()
+            .map(|(i, line)| match i % 4 {
+                0 => format!("{}\n\n", line),
+                1 => format!("  {}  \n \t \n", line),
+                _ => format!("{}\n", line),
+            })
+            .collect();
+        let parsed = read_from_str(&loose, true, FileType::Short).unwrap();
+        assert_eq!(parsed.tiers, tg.tiers);
+
+        // Values sharing a line, as Praat also accepts
+        let packed = "File type = \"ooTextFile\"\r\nObject class = \"TextGrid\"\r\n\r\n0 2 <exists> 1\r\n\"IntervalTier\" \"words\" 0 2 2\r\n0 1 \"a b\" 1 2 \"\"\"c\"\"\"\r\n\r\n";
+        let parsed = read_from_str(packed, true, FileType::Short).unwrap();
+        let labels: Vec<&str> = parsed.tiers[0]
+            .items
+            .iter()
+            .map(|i| i.label.as_str())
+            .collect();
+        assert_eq!(labels, ["a b", "\"c\""]);
+        assert_eq!(parsed.tiers[0].items[1].tmax, 2.0);
+
+        let absent = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n1\n<absent>\n";
+        assert!(
+            read_from_str(absent, false, FileType::Short)
+                .unwrap()
+                .tiers
+                .is_empty()
+        );
+        let err = read_from_str("0\n1\n5\n", false, FileType::Short).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 3: expected <exists> or <absent>, found 5"
+        );
+    }
+
+    #[test]
+    fn test_truncated_file_errors() {
+        let eof = |content: &str, file_type: FileType| {
+            let err = read_from_str(content, false, file_type).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+            match ParseError::from_io(&err) {
+                Some(ParseError::UnexpectedEof { expected, at_line }) => {
+                    (expected.clone(), *at_line)
+                }
+                other => panic!("unexpected error {:?}", other),
+            }
+        };
+        let long = fs::read_to_string(LONG_FILE).unwrap();
+        let short = fs::read_to_string(SHORT_FILE).unwrap();
+        let cut = |content: &str, lines: usize| -> String {
+            content.split_inclusive('\n').take(lines).collect()
+        };
+
+        // Long format: cut within an item, within a tier header, and between tiers
+        let (expected, at_line) = eof(&cut(&long, 17), FileType::Long);
+        assert_eq!(expected, "`text` of interval 1 of tier 1");
+        assert_eq!(at_line, 18);
+        assert_eq!(eof(&cut(&long, 11), FileType::Long).0, "`xmin` of tier 1");
+        assert_eq!(
+            eof(&cut(&long, 18), FileType::Long).0,
+            "interval 2 of tier 1"
+        );
+        let tier_two = long
+            .lines()
+            .position(|line| line.trim() == "item [2]:")
+            .unwrap();
+        assert_eq!(eof(&cut(&long, tier_two), FileType::Long).0, "tier 2");
+        assert_eq!(
+            eof(&cut(&long, 4), FileType::Long).0,
+            "`xmax` of the TextGrid"
+        );
+        let open_quote = format!("{}            text = \"unfinished\n", cut(&long, 17));
+        assert_eq!(
+            eof(&open_quote, FileType::Long).0,
+            "the closing quote of the string starting on line 18"
+        );
+
+        // Short format
+        assert_eq!(eof(&cut(&short, 7), FileType::Short).0, "tier 1");
+        assert_eq!(
+            eof(&cut(&short, 10), FileType::Short).0,
+            "the end time of tier 1"
+        );
+        assert_eq!(
+            eof(&cut(&short, 14), FileType::Short).0,
+            "the text of interval 1 of tier 1"
+        );
+        let unclosed = format!("{}\"unfinished\n", cut(&short, 14));
+        assert_eq!(
+            eof(&unclosed, FileType::Short),
+            (
+                String::from("the closing quote of the string starting on line 15"),
+                16
+            )
+        );
+
+        // Complete files still parse
+        assert!(read_from_str(&long, true, FileType::Long).is_ok());
+        assert!(read_from_str(&short, true, FileType::Short).is_ok());
+    }
+
+    #[test]
+    fn test_bounds_containment_validation() {
+        let mut tg = TextGrid::builder()
+            .tmin(0.0)
+            .tmax(2.0)
+            .interval_tier("words", |t| {
+                t.interval(0.0, 1.0, "a").interval(1.0, 2.0, "b")
+            })
+            .point_tier("events", |t| t.point(0.5, "x"))
+            .build()
+            .unwrap();
+        assert!(tg.assert_valid().is_ok());
+
+        // Within the epsilon is accepted
+        tg.tiers[0].items[1].tmax = 2.0 + 1e-9;
+        assert!(tg.assert_valid().is_ok());
+
+        tg.tiers[0].items[1].tmax = 2.5;
+        let report = tg.validate();
+        assert_eq!(report.errors().count(), 1);
+        assert_eq!(
+            (report.issues[0].tier_index, report.issues[0].item_index),
+            (Some(0), Some(1))
+        );
+        assert!(tg.tiers[0].assert_valid().is_err());
+
+        tg.tiers[0].items[1].tmax = 2.0;
+        tg.tiers[1].tmax = 3.0;
+        let err = tg.assert_valid().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Tier events [0, 3] lies outside the bounds [0, 2] of the TextGrid"
+        );
+        assert!(tg.tiers[1].assert_valid().is_ok());
+        let content = tg.to_short_textgrid_string();
+        assert!(read_from_str(&content, true, FileType::Short).is_err());
+        assert!(read_from_str(&content, false, FileType::Short).is_ok());
+        tg.normalize();
+        assert!(tg.assert_valid().is_ok());
+    }
+
+    #[test]
+    fn test_clamp_to_bounds() {
+        let content = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n10\n<exists>\n1\n\"IntervalTier\"\n\"words\"\n0\n10\n3\n0\n5\n\"a\"\n5\n10.0003\n\"b\"\n10.0003\n10.5\n\"c\"\n";
+        assert!(read_from_str(content, true, FileType::Short).is_err());
+        let options = ParseOptions {
+            clamp_to_bounds: true,
+            ..Default::default()
+        };
+        let tg = read_from_str_with(content, true, FileType::Short, &options).unwrap();
+        let tier = &tg.tiers[0];
+        assert_eq!(tier.items.len(), 2);
+        assert_eq!(tier.size, 2);
+        assert_eq!(tier.items[1].tmax, 10.0);
+        assert_eq!(tg.warnings.len(), 2);
+        assert!(
+            tg.warnings[0].contains("clamped to [5, 10]"),
+            "{}",
+            tg.warnings[0]
+        );
+        assert!(
+            tg.warnings[1].ends_with("was removed"),
+            "{}",
+            tg.warnings[1]
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_recompute_bounds() {
+        let mut tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let (tmin, tmax) = (tg.tmin, tg.tmax);
+        let words = tg
+            .tiers
+            .iter_mut()
+            .find(|tier| tier.name == "word")
+            .unwrap();
+        let first = words.items.remove(0);
+        words.items.push(Item::interval(tmax, tmax + 1.0, "extra"));
+
+        tg.recompute_bounds(true);
+        assert_eq!((tg.tmin, tg.tmax), (tmin, tmax + 1.0));
+        assert!(tg.tiers.iter().all(|tier| tier.tmin == tmin));
+
+        tg.recompute_bounds(false);
+        let words = tg.tiers.iter().find(|tier| tier.name == "word").unwrap();
+        assert_eq!(words.tmin, first.tmax);
+        assert_eq!(tg.tmax, tmax + 1.0);
+        let empty = tg.tiers.iter().find(|tier| tier.items.is_empty());
+        assert!(empty.is_none_or(|tier| tier.tmin == tmin));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_size_mismatch() {
+        let short = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n2\n<exists>\n1\n\"IntervalTier\"\n\"words\"\n0\n2\n3\n0\n1\n\"a\"\n1\n2\n\"b\"\n\"TextTier\"\n\"notes\"\n0\n2\n0\n1\n\"x\"\n";
+        let err = read_from_str(short, true, FileType::Short).unwrap_err();
+        assert_eq!(err.to_string(), "The TextGrid declares 1 tiers but has 2");
+        let tg = read_from_str(short, false, FileType::Short).unwrap();
+        assert_eq!(tg.size, 2);
+        assert_eq!((tg.tiers[0].size, tg.tiers[1].size), (2, 1));
+        assert_eq!(tg.tiers[1].items[0].label, "x");
+        assert_eq!(
+            tg.warnings,
+            [
+                "The TextGrid declares 1 tiers but has 2",
+                "Tier words declares 3 intervals but has 2",
+                "Tier notes declares 0 points but has 1",
+            ]
+        );
+
+        let long = fs::read_to_string(LONG_FILE).unwrap().replacen(
+            "intervals: size = ",
+            "intervals: size = 1",
+            1,
+        );
+        let err = read_from_str(&long, true, FileType::Long).unwrap_err();
+        assert!(
+            err.to_string().starts_with("Tier phone declares 1"),
+            "{}",
+            err
+        );
+        let tg = read_from_str(&long, false, FileType::Long).unwrap();
+        assert_eq!(tg.warnings.len(), 1);
+        assert_eq!(tg.tiers[0].size, tg.tiers[0].items.len());
+        assert!(
+            read_from_file(LONG_FILE, false, FileType::Long)
+                .unwrap()
+                .warnings
+                .is_empty()
+        );
+        assert!(
+            read_from_file(SHORT_FILE, false, FileType::Short)
+                .unwrap()
+                .warnings
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_corrupted_files_do_not_panic() {
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut random = |n: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % n as u64) as usize
+        };
+        for fname in [LONG_FILE, SHORT_FILE] {
+            let content = fs::read_to_string(fname).unwrap();
+            let lines: Vec<&str> = content.lines().collect();
+            for round in 0..400 {
+                let mut corrupted = lines.clone();
+                for _ in 0..1 + random(3) {
+                    let (i, j) = (random(corrupted.len()), random(corrupted.len()));
+                    match round % 4 {
+                        0 => drop(corrupted.remove(i)),
+                        1 => corrupted.insert(i, corrupted[j]),
+                        2 => corrupted.swap(i, j),
+                        _ => {
+                            corrupted[i] =
+                                ["\"", "item [", "intervals [1]:", "class = \"X\"", "?"][j % 5]
+                        }
+                    }
+                }
+                let mut corrupted = corrupted.join("\n");
+                corrupted.truncate(corrupted.floor_char_boundary(random(corrupted.len() * 2)));
+                for strict in [true, false] {
+                    for format in [FileType::Auto, FileType::Long, FileType::Short] {
+                        let _ = read_from_str(&corrupted, strict, format);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_length_policy() {
+        let content = "File type = \"ooTextFile\"\nObject class = \"TextGrid\"\n\n0\n3\n<exists>\n1\n\"IntervalTier\"\n\"words\"\n0\n3\n5\n0\n0\n\"uh\"\n0\n1\n\"a\"\n1\n1\n\"\"\n1\n2\n\"b\"\n2\n2\n\"c\"\n";
+        assert!(read_from_str(content, true, FileType::Short).is_err());
+        let read = |zero_length| {
+            let options = ParseOptions {
+                zero_length,
+                ..Default::default()
+            };
+            read_from_str_with(content, true, FileType::Short, &options).unwrap()
+        };
+        let labels = |tg: &TextGrid| -> Vec<String> {
+            tg.tiers[0]
+                .items
+                .iter()
+                .map(|item| item.label.clone())
+                .collect()
+        };
+
+        let tg = read(ZeroLengthPolicy::Warn);
+        assert_eq!(tg.tiers[0].size, 5);
+        assert_eq!(tg.warnings.len(), 3);
+        assert!(tg.validate().errors().count() == 3);
+        let lenient = ValidationOptions {
+            zero_length: ZeroLengthPolicy::Warn,
+            ..Default::default()
+        };
+        assert!(tg.validate_with(&lenient).is_valid());
+
+        let tg = read(ZeroLengthPolicy::Drop);
+        assert_eq!(labels(&tg), ["a", "b"]);
+        assert_eq!(tg.tiers[0].size, 2);
+        assert!(tg.warnings[0].ends_with("has zero length and was removed"));
+
+        let tg = read(ZeroLengthPolicy::Merge);
+        assert_eq!(labels(&tg), ["uh a", "b c"]);
+        assert_eq!(tg.tiers[0].items[0].tmin, 0.0);
+        assert_eq!(tg.warnings.len(), 3);
+        assert!(tg.assert_valid().is_ok());
+
+        let leading = content.replacen("5\n0\n0\n\"uh\"\n", "6\n0\n0\n\"uh\"\n0\n0\n\"um\"\n", 1);
+        let options = ParseOptions {
+            zero_length: ZeroLengthPolicy::Merge,
+            ..Default::default()
+        };
+        let tg = read_from_str_with(&leading, true, FileType::Short, &options).unwrap();
+        assert_eq!(labels(&tg), ["uh um a", "b c"]);
+    }
+
+    #[test]
+    fn test_zero_tier_textgrids() {
+        let mut tg = TextGrid::new();
+        assert!(tg.assert_valid().is_ok());
+        assert_eq!(tg.validate().issues.len(), 1);
+        assert!(TextGrid::builder().build().is_ok());
+
+        tg.tmax = 2.5;
+        assert!(tg.validate().issues.is_empty());
+        let long = tg.to_long_textgrid_string();
+        assert!(long.ends_with("tiers? <absent> \r\n"), "{}", long);
+        let short = tg.to_short_textgrid_string();
+        assert!(short.ends_with("2.5\r\n<absent>\r\n"), "{}", short);
+        for (content, format) in [(long, FileType::Long), (short, FileType::Short)] {
+            let read = read_from_str(&content, true, format).unwrap();
+            assert_eq!(read, tg);
+            assert_eq!(read.size, 0);
+            assert!(read.warnings.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_from_data_keeps_empty_tiers() {
+        let words = |items: Vec<ItemData>| (String::from("words"), true, items);
+        let data = vec![
+            (String::from("notes"), false, vec![]),
+            words(vec![(0.5, 1.0, String::from("hello"))]),
+            (String::from("errors"), true, vec![]),
+        ];
+        let tg = TextGrid::from_data(data.clone(), None, None, None).unwrap();
+        let names: Vec<&str> = tg.tiers.iter().map(|tier| tier.name.as_str()).collect();
+        assert_eq!(names, ["notes", "words", "errors"]);
+        assert_eq!(tg.size, 3);
+        assert!(
+            tg.tiers
+                .iter()
+                .all(|tier| (tier.tmin, tier.tmax) == (0.5, 1.0))
+        );
+
+        let tg = TextGrid::from_data(data.clone(), None, Some(0.0), Some(2.0)).unwrap();
+        assert_eq!((tg.tiers[2].tmin, tg.tiers[2].tmax), (0.0, 2.0));
+
+        let tg = TextGrid::from_data_with(data, None, None, None, true).unwrap();
+        assert_eq!(tg.tiers.len(), 1);
+        assert!(TextGrid::from_data(vec![words(vec![])], None, None, None).is_err());
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_to_vectors_ref() {
+        let tg = read_from_file(LONG_FILE, true, FileType::Long).unwrap();
+        let (tmins, tmaxs, labels, tier_names, is_intervals) = tg.to_vectors();
+        let borrowed = tg.to_vectors_ref();
+        assert_eq!(borrowed.0, tmins);
+        assert_eq!(borrowed.1, tmaxs);
+        assert_eq!(borrowed.2, labels);
+        assert_eq!(borrowed.3, tier_names);
+        assert_eq!(borrowed.4, is_intervals);
+        assert!(std::ptr::eq(
+            borrowed.2[0],
+            tg.tiers[0].items[0].label.as_str()
+        ));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn test_into_data() {
+        for fname in [LONG_FILE, SHORT_FILE] {
+            let tg = read_from_file(fname, true, FileType::Auto).unwrap();
+            let expected = tg.to_data();
+            assert_eq!(tg.into_data(), expected);
+        }
+    }
 }